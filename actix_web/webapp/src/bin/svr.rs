@@ -6,7 +6,7 @@ use actix_web::{web, App, HttpServer};
 use dotenv::dotenv;
 use tera::Tera;
 use routers::app_config;
-use crate::wa::{routers, handlers, models, errors};
+use crate::wa::{routers, handlers, models, errors, filters};
 
 
 #[actix_web::main]
@@ -17,7 +17,8 @@ async fn main() -> std::io::Result<()> {
     println!("Listening on: {}", &host_port);
 
     HttpServer::new(move || {
-        let tera = Tera::new(concat!(env!("CARGO_MANIFEST_DIR"), "/static/**/*")).unwrap();
+        let mut tera = Tera::new(concat!(env!("CARGO_MANIFEST_DIR"), "/static/**/*")).unwrap();
+        tera.register_filter("money", filters::money);
         App::new().app_data(web::Data::new(tera)).configure(app_config)
     })
         .bind(&host_port)?
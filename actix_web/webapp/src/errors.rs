@@ -39,7 +39,22 @@ impl MyError {
 
 impl fmt::Display for MyError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{}", self)
+        write!(f, "{}", self.error_response())
+    }
+}
+
+impl error::ResponseError for MyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MyError::ActixError(_) | MyError::TeraError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            MyError::NotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(MyErrorResponse {
+            error_message: self.error_response(),
+        })
     }
 }
 
@@ -0,0 +1,13 @@
+use std::collections::HashMap;
+use tera::{Result, Value};
+use crate::models::Money;
+
+//`{{ course.price | money }}` renders a price object as e.g. ¥99.00, no price renders empty
+pub fn money(value: &Value, _args: &HashMap<String, Value>) -> Result<Value> {
+    if value.is_null() {
+        return Ok(Value::String(String::new()));
+    }
+    let money: Money = serde_json::from_value(value.clone())
+        .map_err(|err| tera::Error::msg(format!("money filter expects a price object: {}", err)))?;
+    Ok(Value::String(money.to_string()))
+}
@@ -1,6 +1,6 @@
 use actix_web::{HttpResponse, web, Error, Result};
 use serde_json::json;
-use crate::models::{TeacherResponse, TeacherRegisterForm};
+use crate::models::{TeacherPage, TeacherResponse, TeacherRegisterForm};
 use crate::errors::MyError;
 
 pub async fn get_all_teachers(tmpl: web::Data<tera::Tera>) -> Result<HttpResponse, Error> {
@@ -11,18 +11,18 @@ pub async fn get_all_teachers(tmpl: web::Data<tera::Tera>) -> Result<HttpRespons
         .send()
         .await
         .unwrap()
-        .json::<Vec<TeacherResponse>>()
+        .json::<TeacherPage>()
         .await
         .unwrap();
 
     //add data to template by using context
     let mut ctx = tera::Context::new();
     ctx.insert("error", "");
-    ctx.insert("teachers", &res);
+    ctx.insert("teachers", &res.items);
 
     let s = tmpl
         .render("teacher.html", &ctx)
-        .map_err(|_| MyError::TeraError("Template error".to_string()));
+        .map_err(|_| MyError::TeraError("Template error".to_string()))?;
 
     Ok(HttpResponse::Ok().content_type("text/html").body(s))
 }
@@ -36,7 +36,7 @@ pub async fn show_register_form(tmpl: web::Data<tera::Tera>) -> Result<HttpRespo
 
     let s = tmpl
         .render("register.html", &ctx)
-        .map_err(|_| MyError::TeraError("Template error".to_string()));
+        .map_err(|_| MyError::TeraError("Template error".to_string()))?;
 
     Ok(HttpResponse::Ok().content_type("text/html").body(s))
 }
@@ -56,7 +56,7 @@ pub async fn handle_register(
 
         s = tmpl
             .render("register.html", &ctx)
-            .map_err(|_| MyError::TeraError("Template error".to_string()));
+            .map_err(|_| MyError::TeraError("Template error".to_string()))?;
     }else {
         let new_teacher = json!({
             "name": &params.name,
@@ -73,8 +73,8 @@ pub async fn handle_register(
             .body()
             .await?;
 
-        let teacher_response: TeacherResponse = serde_json::from_str(&std::str::from_utf8(&res)?)?;
-        s = Ok(format!("Congratulation! Your ID is: {}.", teacher_response.id));
+        let teacher_response: TeacherResponse = serde_json::from_str(std::str::from_utf8(&res)?)?;
+        s = format!("Congratulation! Your ID is: {}.", teacher_response.id);
     }
     Ok(HttpResponse::Ok().content_type("text/html").body(s))
 }
\ No newline at end of file
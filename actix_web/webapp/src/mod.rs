@@ -1,4 +1,5 @@
 pub mod errors;
+pub mod filters;
 pub mod handlers;
 pub mod models;
 pub mod routers;
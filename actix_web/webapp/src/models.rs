@@ -13,4 +13,78 @@ pub struct TeacherResponse {
     pub name: String,
     pub picture_url: String,
     pub profile: String,
-}
\ No newline at end of file
+}
+
+//one page of GET /teacher/
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TeacherPage {
+    pub items: Vec<TeacherResponse>,
+    pub total: i64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+//course price as the service returns it, `amount` is in minor units of `currency`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Money {
+    pub amount: i64,
+    pub currency: String,
+}
+
+impl Money {
+    //JPY and KRW have no minor unit, the other supported currencies have cents
+    fn minor_units(&self) -> u32 {
+        match self.currency.as_str() {
+            "JPY" | "KRW" => 0,
+            _ => 2,
+        }
+    }
+
+    fn symbol(&self) -> &str {
+        match self.currency.as_str() {
+            "CNY" | "JPY" => "¥",
+            "USD" => "$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "HKD" => "HK$",
+            "KRW" => "₩",
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        //integer division keeps amounts past 2^53 exact
+        let digits = self.minor_units();
+        let unit = 10u64.pow(digits);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let amount = self.amount.unsigned_abs();
+        write!(f, "{}{}{}", sign, self.symbol(), amount / unit)?;
+        if digits > 0 {
+            write!(f, ".{:0width$}", amount % unit, width = digits as usize)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: i64, currency: &str) -> Money {
+        Money {
+            amount,
+            currency: currency.into(),
+        }
+    }
+
+    #[test]
+    fn money_displays_exact_minor_units() {
+        assert_eq!(money(9900, "CNY").to_string(), "¥99.00");
+        assert_eq!(money(1205, "USD").to_string(), "$12.05");
+        assert_eq!(money(1200, "JPY").to_string(), "¥1200");
+        assert_eq!(money(-50, "EUR").to_string(), "-€0.50");
+        assert_eq!(money(9_007_199_254_740_993, "USD").to_string(), "$90071992547409.93");
+    }
+}
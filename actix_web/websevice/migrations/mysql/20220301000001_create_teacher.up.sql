@@ -0,0 +1,8 @@
+-- an existing database may have the teacher table from before the migrations, it is kept as it is
+CREATE TABLE IF NOT EXISTS teacher (
+    id          INT          NOT NULL AUTO_INCREMENT,
+    name        VARCHAR(100) NOT NULL,
+    picture_url VARCHAR(200) NOT NULL,
+    profile     VARCHAR(2000) NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
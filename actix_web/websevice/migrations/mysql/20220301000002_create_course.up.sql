@@ -0,0 +1,19 @@
+-- an existing database may have the course table from before the migrations, it is kept as it is
+CREATE TABLE IF NOT EXISTS course (
+    id          INT           NOT NULL AUTO_INCREMENT,
+    teacher_id  INT           NOT NULL,
+    name        VARCHAR(140)  NOT NULL,
+    time        TIMESTAMP     NULL DEFAULT CURRENT_TIMESTAMP,
+    description VARCHAR(2000) NULL,
+    format      VARCHAR(30)   NULL,
+    structure   VARCHAR(200)  NULL,
+    duration    VARCHAR(30)   NULL,
+    price       INT           NULL,
+    language    VARCHAR(30)   NULL,
+    level       VARCHAR(30)   NULL,
+    PRIMARY KEY (id),
+    KEY idx_course_teacher (teacher_id),
+    -- used by GET /courses/search
+    FULLTEXT KEY ft_course_text (name, description, structure),
+    CONSTRAINT fk_course_teacher FOREIGN KEY (teacher_id) REFERENCES teacher (id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
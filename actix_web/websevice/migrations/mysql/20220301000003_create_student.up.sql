@@ -0,0 +1,8 @@
+CREATE TABLE student (
+    id      INT           NOT NULL AUTO_INCREMENT,
+    name    VARCHAR(100)  NOT NULL,
+    email   VARCHAR(200)  NOT NULL,
+    profile VARCHAR(2000) NULL,
+    PRIMARY KEY (id),
+    UNIQUE KEY uq_student_email (email)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
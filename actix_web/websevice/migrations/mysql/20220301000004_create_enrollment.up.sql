@@ -0,0 +1,11 @@
+CREATE TABLE enrollment (
+    id          INT       NOT NULL AUTO_INCREMENT,
+    student_id  INT       NOT NULL,
+    course_id   INT       NOT NULL,
+    enrolled_at TIMESTAMP NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY uq_enrollment (student_id, course_id),
+    KEY idx_enrollment_course (course_id),
+    CONSTRAINT fk_enrollment_student FOREIGN KEY (student_id) REFERENCES student (id) ON DELETE CASCADE,
+    CONSTRAINT fk_enrollment_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
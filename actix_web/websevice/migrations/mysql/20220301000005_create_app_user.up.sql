@@ -0,0 +1,12 @@
+CREATE TABLE app_user (
+    id            INT          NOT NULL AUTO_INCREMENT,
+    username      VARCHAR(100) NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    role          VARCHAR(20)  NOT NULL,
+    teacher_id    INT          NULL,
+    student_id    INT          NULL,
+    PRIMARY KEY (id),
+    UNIQUE KEY uq_app_user_username (username),
+    CONSTRAINT fk_app_user_teacher FOREIGN KEY (teacher_id) REFERENCES teacher (id) ON DELETE SET NULL,
+    CONSTRAINT fk_app_user_student FOREIGN KEY (student_id) REFERENCES student (id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,49 @@
+-- the codes go back to their labels, values the up migration could not map come back as they were
+UPDATE course SET level = CASE level
+    WHEN 'beginner' THEN 'Beginner'
+    WHEN 'intermediate' THEN 'Intermediate'
+    WHEN 'advanced' THEN 'Advanced'
+    ELSE level
+END
+WHERE level IS NOT NULL;
+
+UPDATE course SET format = CASE format
+    WHEN 'online' THEN 'Online'
+    WHEN 'offline' THEN 'Offline'
+    WHEN 'hybrid' THEN 'Hybrid'
+    WHEN 'self_paced' THEN 'Self-paced'
+    ELSE format
+END
+WHERE format IS NOT NULL;
+
+UPDATE course SET language = CASE language
+    WHEN 'zh' THEN 'Chinese'
+    WHEN 'en' THEN 'English'
+    WHEN 'ja' THEN 'Japanese'
+    WHEN 'ko' THEN 'Korean'
+    WHEN 'fr' THEN 'French'
+    WHEN 'de' THEN 'German'
+    WHEN 'es' THEN 'Spanish'
+    WHEN 'pt' THEN 'Portuguese'
+    WHEN 'it' THEN 'Italian'
+    WHEN 'ru' THEN 'Russian'
+    ELSE language
+END
+WHERE language IS NOT NULL;
+
+UPDATE course SET level = (
+    SELECT raw_value FROM course_vocabulary_legacy l WHERE l.course_id = course.id AND l.field = 'level'
+)
+WHERE level IS NULL AND id IN (SELECT course_id FROM course_vocabulary_legacy WHERE field = 'level');
+
+UPDATE course SET format = (
+    SELECT raw_value FROM course_vocabulary_legacy l WHERE l.course_id = course.id AND l.field = 'format'
+)
+WHERE format IS NULL AND id IN (SELECT course_id FROM course_vocabulary_legacy WHERE field = 'format');
+
+UPDATE course SET language = (
+    SELECT raw_value FROM course_vocabulary_legacy l WHERE l.course_id = course.id AND l.field = 'language'
+)
+WHERE language IS NULL AND id IN (SELECT course_id FROM course_vocabulary_legacy WHERE field = 'language');
+
+DROP TABLE course_vocabulary_legacy;
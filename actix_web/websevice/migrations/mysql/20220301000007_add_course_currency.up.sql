@@ -0,0 +1,5 @@
+-- prices move to minor units of an ISO 4217 currency, existing ones were whole yuan
+ALTER TABLE course MODIFY price BIGINT NULL;
+ALTER TABLE course ADD COLUMN currency VARCHAR(3) NULL AFTER price;
+
+UPDATE course SET price = price * 100, currency = 'CNY' WHERE price IS NOT NULL;
@@ -0,0 +1,24 @@
+CREATE TABLE course_module (
+    id         INT           NOT NULL AUTO_INCREMENT,
+    course_id  INT           NOT NULL,
+    title      VARCHAR(140)  NOT NULL,
+    summary    VARCHAR(2000) NULL,
+    position   INT           NOT NULL,
+    PRIMARY KEY (id),
+    KEY idx_module_course (course_id, position),
+    CONSTRAINT fk_module_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE lesson (
+    id               INT          NOT NULL AUTO_INCREMENT,
+    module_id        INT          NOT NULL,
+    title            VARCHAR(140) NOT NULL,
+    body             TEXT         NULL,
+    duration_minutes INT          NULL,
+    -- json array of {"name", "url"}
+    attachments      TEXT         NULL,
+    position         INT          NOT NULL,
+    PRIMARY KEY (id),
+    KEY idx_lesson_module (module_id, position),
+    CONSTRAINT fk_lesson_module FOREIGN KEY (module_id) REFERENCES course_module (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,16 @@
+-- times are stored in UTC, timezone is the IANA zone the session is held in
+CREATE TABLE course_session (
+    id           INT          NOT NULL AUTO_INCREMENT,
+    course_id    INT          NOT NULL,
+    starts_at    DATETIME     NOT NULL,
+    ends_at      DATETIME     NOT NULL,
+    timezone     VARCHAR(64)  NOT NULL,
+    location     VARCHAR(200) NULL,
+    meeting_url  VARCHAR(500) NULL,
+    frequency    VARCHAR(30)  NULL,
+    occurrences  INT          NULL,
+    repeat_until DATETIME     NULL,
+    PRIMARY KEY (id),
+    KEY idx_session_course (course_id, starts_at),
+    CONSTRAINT fk_session_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
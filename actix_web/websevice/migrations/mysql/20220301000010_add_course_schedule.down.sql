@@ -0,0 +1,6 @@
+ALTER TABLE course
+    DROP KEY idx_course_room_time,
+    DROP KEY idx_course_teacher_time,
+    DROP COLUMN room,
+    DROP COLUMN ends_at,
+    DROP COLUMN starts_at;
@@ -0,0 +1,8 @@
+-- when and where a course is held, times in UTC
+ALTER TABLE course
+    ADD COLUMN starts_at DATETIME    NULL AFTER time,
+    ADD COLUMN ends_at   DATETIME    NULL AFTER starts_at,
+    ADD COLUMN room      VARCHAR(50) NULL AFTER ends_at,
+    -- used by the conflict checks and GET /teacher/{id}/availability
+    ADD KEY idx_course_teacher_time (teacher_id, starts_at),
+    ADD KEY idx_course_room_time (room, starts_at);
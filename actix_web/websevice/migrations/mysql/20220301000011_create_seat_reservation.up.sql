@@ -0,0 +1,16 @@
+-- seats that can be reserved, no limit when NULL
+ALTER TABLE course ADD COLUMN capacity INT NULL AFTER room;
+
+-- waitlisted reservations move up in id order as seats are released
+CREATE TABLE seat_reservation (
+    id          INT         NOT NULL AUTO_INCREMENT,
+    course_id   INT         NOT NULL,
+    student_id  INT         NOT NULL,
+    status      VARCHAR(20) NOT NULL,
+    reserved_at TIMESTAMP   NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY uq_seat_reservation (course_id, student_id),
+    KEY idx_seat_reservation_status (course_id, status, id),
+    CONSTRAINT fk_seat_reservation_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE,
+    CONSTRAINT fk_seat_reservation_student FOREIGN KEY (student_id) REFERENCES student (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
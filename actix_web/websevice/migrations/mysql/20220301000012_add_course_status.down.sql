@@ -0,0 +1,6 @@
+DROP TABLE course_status_change;
+
+ALTER TABLE course
+    DROP KEY idx_course_status,
+    DROP COLUMN published_at,
+    DROP COLUMN status;
@@ -0,0 +1,22 @@
+-- new courses start as drafts, the ones already live stay published
+ALTER TABLE course
+    ADD COLUMN status       VARCHAR(20) NOT NULL DEFAULT 'draft' AFTER capacity,
+    -- when a published course went live, or when a scheduled one will, in UTC
+    ADD COLUMN published_at DATETIME    NULL AFTER status,
+    ADD KEY idx_course_status (status, published_at);
+
+UPDATE course SET status = 'published', published_at = time;
+
+-- who moved a course between statuses and when
+CREATE TABLE course_status_change (
+    id          INT         NOT NULL AUTO_INCREMENT,
+    course_id   INT         NOT NULL,
+    from_status VARCHAR(20) NOT NULL,
+    to_status   VARCHAR(20) NOT NULL,
+    changed_by  INT         NULL,
+    changed_at  DATETIME    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    KEY idx_course_status_change_course (course_id, id),
+    CONSTRAINT fk_course_status_change_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE,
+    CONSTRAINT fk_course_status_change_user FOREIGN KEY (changed_by) REFERENCES app_user (id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,3 @@
+-- bumped by every write, the ETag clients send back in If-Match
+ALTER TABLE teacher ADD COLUMN version INT NOT NULL DEFAULT 1;
+ALTER TABLE course ADD COLUMN version INT NOT NULL DEFAULT 1 AFTER published_at;
@@ -0,0 +1,9 @@
+DELETE FROM course WHERE deleted_at IS NOT NULL;
+DELETE FROM teacher WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE course
+    DROP KEY idx_course_deleted,
+    DROP COLUMN deleted_at;
+ALTER TABLE teacher
+    DROP KEY idx_teacher_deleted,
+    DROP COLUMN deleted_at;
@@ -0,0 +1,7 @@
+-- set when a teacher or course is moved to the trash, the row goes for good once it is purged
+ALTER TABLE teacher
+    ADD COLUMN deleted_at DATETIME NULL,
+    ADD KEY idx_teacher_deleted (deleted_at);
+ALTER TABLE course
+    ADD COLUMN deleted_at DATETIME NULL,
+    ADD KEY idx_course_deleted (deleted_at);
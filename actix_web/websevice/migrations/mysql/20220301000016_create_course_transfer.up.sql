@@ -0,0 +1,12 @@
+-- every move of a course to another teacher, old /courses/{teacher_id}/{course_id} paths redirect through it
+CREATE TABLE course_transfer (
+    id              INT      NOT NULL AUTO_INCREMENT,
+    course_id       INT      NOT NULL,
+    -- no foreign keys on the teachers, the old one may be purged
+    from_teacher_id INT      NOT NULL,
+    to_teacher_id   INT      NOT NULL,
+    transferred_at  DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    KEY idx_course_transfer_from (course_id, from_teacher_id),
+    CONSTRAINT fk_course_transfer_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,13 @@
+-- co-teachers and assistants of a course, its lead stays course.teacher_id
+CREATE TABLE course_instructor (
+    course_id  INT         NOT NULL,
+    teacher_id INT         NOT NULL,
+    -- co_teacher or ta
+    role       VARCHAR(20) NOT NULL,
+    added_at   DATETIME    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (course_id, teacher_id),
+    -- GET /courses/{teacher_id} lists the courses a teacher has any role in
+    KEY idx_course_instructor_teacher (teacher_id),
+    CONSTRAINT fk_course_instructor_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE,
+    CONSTRAINT fk_course_instructor_teacher FOREIGN KEY (teacher_id) REFERENCES teacher (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,14 @@
+-- public names of courses for /c/{slug}, old slugs stay so renamed courses keep their links
+CREATE TABLE course_slug (
+    slug       VARCHAR(100) NOT NULL,
+    course_id  INT          NOT NULL,
+    -- the slug the course is known by now, the others redirect to it
+    current    BOOLEAN      NOT NULL DEFAULT TRUE,
+    created_at DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (slug),
+    KEY idx_course_slug_course (course_id),
+    CONSTRAINT fk_course_slug_course FOREIGN KEY (course_id) REFERENCES course (id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- existing courses get a slug from their name the next time they are saved
+INSERT INTO course_slug (slug, course_id) SELECT CONCAT('course-', id), id FROM course;
@@ -0,0 +1,21 @@
+-- an existing database may have the course table from before the migrations, it is kept as it is
+CREATE TABLE IF NOT EXISTS course (
+    id          SERIAL        PRIMARY KEY,
+    teacher_id  INT           NOT NULL REFERENCES teacher (id),
+    name        VARCHAR(140)  NOT NULL,
+    time        TIMESTAMPTZ   NULL DEFAULT CURRENT_TIMESTAMP,
+    description VARCHAR(2000) NULL,
+    format      VARCHAR(30)   NULL,
+    structure   VARCHAR(200)  NULL,
+    duration    VARCHAR(30)   NULL,
+    price       INT           NULL,
+    language    VARCHAR(30)   NULL,
+    level       VARCHAR(30)   NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_course_teacher ON course (teacher_id);
+
+-- used by GET /courses/search
+CREATE INDEX IF NOT EXISTS ft_course_text ON course USING GIN (
+    to_tsvector('simple', name || ' ' || coalesce(description, '') || ' ' || coalesce(structure, ''))
+);
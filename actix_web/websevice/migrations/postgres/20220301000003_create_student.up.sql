@@ -0,0 +1,6 @@
+CREATE TABLE student (
+    id      SERIAL        PRIMARY KEY,
+    name    VARCHAR(100)  NOT NULL,
+    email   VARCHAR(200)  NOT NULL UNIQUE,
+    profile VARCHAR(2000) NULL
+);
@@ -0,0 +1,9 @@
+CREATE TABLE enrollment (
+    id          SERIAL      PRIMARY KEY,
+    student_id  INT         NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    course_id   INT         NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    enrolled_at TIMESTAMPTZ NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (student_id, course_id)
+);
+
+CREATE INDEX idx_enrollment_course ON enrollment (course_id);
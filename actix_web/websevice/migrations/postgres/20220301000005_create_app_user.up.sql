@@ -0,0 +1,8 @@
+CREATE TABLE app_user (
+    id            SERIAL       PRIMARY KEY,
+    username      VARCHAR(100) NOT NULL UNIQUE,
+    password_hash VARCHAR(255) NOT NULL,
+    role          VARCHAR(20)  NOT NULL,
+    teacher_id    INT          NULL REFERENCES teacher (id) ON DELETE SET NULL,
+    student_id    INT          NULL REFERENCES student (id) ON DELETE SET NULL
+);
@@ -0,0 +1,22 @@
+CREATE TABLE course_module (
+    id         SERIAL        PRIMARY KEY,
+    course_id  INT           NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    title      VARCHAR(140)  NOT NULL,
+    summary    VARCHAR(2000) NULL,
+    position   INT           NOT NULL
+);
+
+CREATE INDEX idx_module_course ON course_module (course_id, position);
+
+CREATE TABLE lesson (
+    id               SERIAL       PRIMARY KEY,
+    module_id        INT          NOT NULL REFERENCES course_module (id) ON DELETE CASCADE,
+    title            VARCHAR(140) NOT NULL,
+    body             TEXT         NULL,
+    duration_minutes INT          NULL,
+    -- json array of {"name", "url"}
+    attachments      TEXT         NULL,
+    position         INT          NOT NULL
+);
+
+CREATE INDEX idx_lesson_module ON lesson (module_id, position);
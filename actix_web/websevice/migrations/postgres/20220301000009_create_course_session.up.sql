@@ -0,0 +1,15 @@
+-- timezone is the IANA zone the session is held in
+CREATE TABLE course_session (
+    id           SERIAL       PRIMARY KEY,
+    course_id    INT          NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    starts_at    TIMESTAMPTZ  NOT NULL,
+    ends_at      TIMESTAMPTZ  NOT NULL,
+    timezone     VARCHAR(64)  NOT NULL,
+    location     VARCHAR(200) NULL,
+    meeting_url  VARCHAR(500) NULL,
+    frequency    VARCHAR(30)  NULL,
+    occurrences  INT          NULL,
+    repeat_until TIMESTAMPTZ  NULL
+);
+
+CREATE INDEX idx_session_course ON course_session (course_id, starts_at);
@@ -0,0 +1,7 @@
+DROP INDEX idx_course_room_time;
+DROP INDEX idx_course_teacher_time;
+
+ALTER TABLE course
+    DROP COLUMN room,
+    DROP COLUMN ends_at,
+    DROP COLUMN starts_at;
@@ -0,0 +1,9 @@
+-- when and where a course is held
+ALTER TABLE course
+    ADD COLUMN starts_at TIMESTAMPTZ NULL,
+    ADD COLUMN ends_at   TIMESTAMPTZ NULL,
+    ADD COLUMN room      VARCHAR(50) NULL;
+
+-- used by the conflict checks and GET /teacher/{id}/availability
+CREATE INDEX idx_course_teacher_time ON course (teacher_id, starts_at);
+CREATE INDEX idx_course_room_time ON course (room, starts_at);
@@ -0,0 +1,14 @@
+-- seats that can be reserved, no limit when NULL
+ALTER TABLE course ADD COLUMN capacity INT NULL;
+
+-- waitlisted reservations move up in id order as seats are released
+CREATE TABLE seat_reservation (
+    id          SERIAL      PRIMARY KEY,
+    course_id   INT         NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    student_id  INT         NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    status      VARCHAR(20) NOT NULL,
+    reserved_at TIMESTAMPTZ NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (course_id, student_id)
+);
+
+CREATE INDEX idx_seat_reservation_status ON seat_reservation (course_id, status, id);
@@ -0,0 +1,6 @@
+DROP TABLE course_status_change;
+
+DROP INDEX idx_course_status;
+ALTER TABLE course
+    DROP COLUMN published_at,
+    DROP COLUMN status;
@@ -0,0 +1,21 @@
+-- new courses start as drafts, the ones already live stay published
+ALTER TABLE course
+    ADD COLUMN status       VARCHAR(20) NOT NULL DEFAULT 'draft',
+    -- when a published course went live, or when a scheduled one will
+    ADD COLUMN published_at TIMESTAMPTZ NULL;
+
+UPDATE course SET status = 'published', published_at = time;
+
+CREATE INDEX idx_course_status ON course (status, published_at);
+
+-- who moved a course between statuses and when
+CREATE TABLE course_status_change (
+    id          SERIAL      PRIMARY KEY,
+    course_id   INT         NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    from_status VARCHAR(20) NOT NULL,
+    to_status   VARCHAR(20) NOT NULL,
+    changed_by  INT         NULL REFERENCES app_user (id) ON DELETE SET NULL,
+    changed_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_course_status_change_course ON course_status_change (course_id, id);
@@ -0,0 +1,29 @@
+-- every saved version of a course's editable fields, numbered per course from 1
+CREATE TABLE course_revision (
+    id          SERIAL        PRIMARY KEY,
+    course_id   INT           NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    revision    INT           NOT NULL,
+    name        VARCHAR(140)  NOT NULL,
+    starts_at   TIMESTAMPTZ   NULL,
+    ends_at     TIMESTAMPTZ   NULL,
+    room        VARCHAR(50)   NULL,
+    capacity    INT           NULL,
+    description VARCHAR(2000) NULL,
+    format      VARCHAR(30)   NULL,
+    structure   VARCHAR(200)  NULL,
+    duration    VARCHAR(30)   NULL,
+    price       BIGINT        NULL,
+    currency    VARCHAR(3)    NULL,
+    language    VARCHAR(30)   NULL,
+    level       VARCHAR(30)   NULL,
+    changed_by  INT           NULL REFERENCES app_user (id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (course_id, revision)
+);
+
+-- existing courses start their history as they are now
+INSERT INTO course_revision (course_id, revision, name, starts_at, ends_at, room, capacity, description,
+    format, structure, duration, price, currency, language, level, created_at)
+SELECT id, 1, name, starts_at, ends_at, room, capacity, description,
+    format, structure, duration, price, currency, language, level, COALESCE(time, CURRENT_TIMESTAMP)
+FROM course;
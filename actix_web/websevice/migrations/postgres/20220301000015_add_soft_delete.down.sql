@@ -0,0 +1,7 @@
+DELETE FROM course WHERE deleted_at IS NOT NULL;
+DELETE FROM teacher WHERE deleted_at IS NOT NULL;
+
+DROP INDEX idx_course_deleted;
+DROP INDEX idx_teacher_deleted;
+ALTER TABLE course DROP COLUMN deleted_at;
+ALTER TABLE teacher DROP COLUMN deleted_at;
@@ -0,0 +1,11 @@
+-- every move of a course to another teacher, old /courses/{teacher_id}/{course_id} paths redirect through it
+CREATE TABLE course_transfer (
+    id              SERIAL      PRIMARY KEY,
+    course_id       INT         NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    -- no foreign keys on the teachers, the old one may be purged
+    from_teacher_id INT         NOT NULL,
+    to_teacher_id   INT         NOT NULL,
+    transferred_at  TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_course_transfer_from ON course_transfer (course_id, from_teacher_id);
@@ -0,0 +1,12 @@
+-- co-teachers and assistants of a course, its lead stays course.teacher_id
+CREATE TABLE course_instructor (
+    course_id  INT         NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    teacher_id INT         NOT NULL REFERENCES teacher (id) ON DELETE CASCADE,
+    -- co_teacher or ta
+    role       VARCHAR(20) NOT NULL,
+    added_at   TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (course_id, teacher_id)
+);
+
+-- GET /courses/{teacher_id} lists the courses a teacher has any role in
+CREATE INDEX idx_course_instructor_teacher ON course_instructor (teacher_id);
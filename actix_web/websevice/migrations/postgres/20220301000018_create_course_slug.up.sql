@@ -0,0 +1,13 @@
+-- public names of courses for /c/{slug}, old slugs stay so renamed courses keep their links
+CREATE TABLE course_slug (
+    slug       VARCHAR(100) PRIMARY KEY,
+    course_id  INT          NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    -- the slug the course is known by now, the others redirect to it
+    current    BOOLEAN      NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ  NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_course_slug_course ON course_slug (course_id);
+
+-- existing courses get a slug from their name the next time they are saved
+INSERT INTO course_slug (slug, course_id) SELECT 'course-' || id, id FROM course;
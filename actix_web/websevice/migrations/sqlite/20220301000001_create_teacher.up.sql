@@ -0,0 +1,7 @@
+-- an existing database may have the teacher table from before the migrations, it is kept as it is
+CREATE TABLE IF NOT EXISTS teacher (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    name        TEXT    NOT NULL,
+    picture_url TEXT    NOT NULL,
+    profile     TEXT    NOT NULL
+);
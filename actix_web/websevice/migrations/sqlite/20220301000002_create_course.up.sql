@@ -0,0 +1,16 @@
+-- an existing database may have the course table from before the migrations, it is kept as it is
+CREATE TABLE IF NOT EXISTS course (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    teacher_id  INTEGER NOT NULL REFERENCES teacher (id),
+    name        TEXT    NOT NULL,
+    time        TEXT    NULL DEFAULT CURRENT_TIMESTAMP,
+    description TEXT    NULL,
+    format      TEXT    NULL,
+    structure   TEXT    NULL,
+    duration    TEXT    NULL,
+    price       INTEGER NULL,
+    language    TEXT    NULL,
+    level       TEXT    NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_course_teacher ON course (teacher_id);
@@ -0,0 +1,6 @@
+CREATE TABLE student (
+    id      INTEGER PRIMARY KEY AUTOINCREMENT,
+    name    TEXT    NOT NULL,
+    email   TEXT    NOT NULL UNIQUE,
+    profile TEXT    NULL
+);
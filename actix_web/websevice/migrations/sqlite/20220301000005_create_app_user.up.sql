@@ -0,0 +1,8 @@
+CREATE TABLE app_user (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    username      TEXT    NOT NULL UNIQUE,
+    password_hash TEXT    NOT NULL,
+    role          TEXT    NOT NULL,
+    teacher_id    INTEGER NULL REFERENCES teacher (id) ON DELETE SET NULL,
+    student_id    INTEGER NULL REFERENCES student (id) ON DELETE SET NULL
+);
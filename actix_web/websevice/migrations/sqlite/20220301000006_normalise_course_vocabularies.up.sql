@@ -0,0 +1,61 @@
+-- level, format and language become closed vocabularies stored as their code,
+-- see CourseLevel, CourseFormat and Language; values that match none are cleared
+-- from course and kept in course_vocabulary_legacy, the down migration puts them back
+CREATE TABLE course_vocabulary_legacy (
+    course_id INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    -- level, format or language
+    field     TEXT    NOT NULL,
+    raw_value TEXT    NOT NULL,
+    PRIMARY KEY (course_id, field)
+);
+
+-- every value is kept until the mapping below shows which ones it could not place
+INSERT INTO course_vocabulary_legacy (course_id, field, raw_value)
+SELECT id, 'level', level FROM course WHERE level IS NOT NULL;
+
+INSERT INTO course_vocabulary_legacy (course_id, field, raw_value)
+SELECT id, 'format', format FROM course WHERE format IS NOT NULL;
+
+INSERT INTO course_vocabulary_legacy (course_id, field, raw_value)
+SELECT id, 'language', language FROM course WHERE language IS NOT NULL;
+
+UPDATE course SET level = CASE
+    WHEN LOWER(TRIM(level)) IN ('beginner', 'basic', 'easy', 'entry', '初级', '入门') THEN 'beginner'
+    WHEN LOWER(TRIM(level)) IN ('intermediate', 'medium', '中级') THEN 'intermediate'
+    WHEN LOWER(TRIM(level)) IN ('advanced', 'hard', 'expert', '高级') THEN 'advanced'
+    ELSE NULL
+END
+WHERE level IS NOT NULL;
+
+UPDATE course SET format = CASE
+    WHEN LOWER(TRIM(format)) IN ('online', 'remote', '线上', '在线') THEN 'online'
+    WHEN LOWER(TRIM(format)) IN ('offline', 'in person', 'in-person', 'classroom', '线下', '面授') THEN 'offline'
+    WHEN LOWER(TRIM(format)) IN ('hybrid', 'blended', '混合') THEN 'hybrid'
+    WHEN LOWER(TRIM(format)) IN ('self_paced', 'self-paced', 'self paced', 'recorded', '录播') THEN 'self_paced'
+    ELSE NULL
+END
+WHERE format IS NOT NULL;
+
+UPDATE course SET language = CASE
+    WHEN LOWER(TRIM(language)) IN ('zh', 'chinese', 'zh-cn', 'mandarin', '中文', '汉语') THEN 'zh'
+    WHEN LOWER(TRIM(language)) IN ('en', 'english', '英语', '英文') THEN 'en'
+    WHEN LOWER(TRIM(language)) IN ('ja', 'japanese', '日语', '日本語') THEN 'ja'
+    WHEN LOWER(TRIM(language)) IN ('ko', 'korean', '韩语', '한국어') THEN 'ko'
+    WHEN LOWER(TRIM(language)) IN ('fr', 'french', '法语', 'français') THEN 'fr'
+    WHEN LOWER(TRIM(language)) IN ('de', 'german', '德语', 'deutsch') THEN 'de'
+    WHEN LOWER(TRIM(language)) IN ('es', 'spanish', '西班牙语', 'español') THEN 'es'
+    WHEN LOWER(TRIM(language)) IN ('pt', 'portuguese', '葡萄牙语', 'português') THEN 'pt'
+    WHEN LOWER(TRIM(language)) IN ('it', 'italian', '意大利语', 'italiano') THEN 'it'
+    WHEN LOWER(TRIM(language)) IN ('ru', 'russian', '俄语', 'русский') THEN 'ru'
+    ELSE NULL
+END
+WHERE language IS NOT NULL;
+
+DELETE FROM course_vocabulary_legacy
+WHERE field = 'level' AND course_id IN (SELECT id FROM course WHERE level IS NOT NULL);
+
+DELETE FROM course_vocabulary_legacy
+WHERE field = 'format' AND course_id IN (SELECT id FROM course WHERE format IS NOT NULL);
+
+DELETE FROM course_vocabulary_legacy
+WHERE field = 'language' AND course_id IN (SELECT id FROM course WHERE language IS NOT NULL);
@@ -0,0 +1,5 @@
+-- prices in other currencies cannot be kept as whole yuan and are cleared
+UPDATE course SET price = NULL WHERE currency IS NULL OR currency <> 'CNY';
+UPDATE course SET price = price / 100 WHERE price IS NOT NULL;
+
+ALTER TABLE course DROP COLUMN currency;
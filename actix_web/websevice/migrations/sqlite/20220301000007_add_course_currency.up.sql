@@ -0,0 +1,4 @@
+-- prices move to minor units of an ISO 4217 currency, existing ones were whole yuan
+ALTER TABLE course ADD COLUMN currency TEXT NULL;
+
+UPDATE course SET price = price * 100, currency = 'CNY' WHERE price IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE lesson;
+DROP TABLE course_module;
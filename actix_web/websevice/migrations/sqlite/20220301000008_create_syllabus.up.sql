@@ -0,0 +1,22 @@
+CREATE TABLE course_module (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    course_id  INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    title      TEXT    NOT NULL,
+    summary    TEXT    NULL,
+    position   INTEGER NOT NULL
+);
+
+CREATE INDEX idx_module_course ON course_module (course_id, position);
+
+CREATE TABLE lesson (
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    module_id        INTEGER NOT NULL REFERENCES course_module (id) ON DELETE CASCADE,
+    title            TEXT    NOT NULL,
+    body             TEXT    NULL,
+    duration_minutes INTEGER NULL,
+    -- json array of {"name", "url"}
+    attachments      TEXT    NULL,
+    position         INTEGER NOT NULL
+);
+
+CREATE INDEX idx_lesson_module ON lesson (module_id, position);
@@ -0,0 +1,15 @@
+-- times are stored in UTC, timezone is the IANA zone the session is held in
+CREATE TABLE course_session (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    course_id    INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    starts_at    TEXT    NOT NULL,
+    ends_at      TEXT    NOT NULL,
+    timezone     TEXT    NOT NULL,
+    location     TEXT    NULL,
+    meeting_url  TEXT    NULL,
+    frequency    TEXT    NULL,
+    occurrences  INTEGER NULL,
+    repeat_until TEXT    NULL
+);
+
+CREATE INDEX idx_session_course ON course_session (course_id, starts_at);
@@ -0,0 +1,6 @@
+DROP INDEX idx_course_room_time;
+DROP INDEX idx_course_teacher_time;
+
+ALTER TABLE course DROP COLUMN room;
+ALTER TABLE course DROP COLUMN ends_at;
+ALTER TABLE course DROP COLUMN starts_at;
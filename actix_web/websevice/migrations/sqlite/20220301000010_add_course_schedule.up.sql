@@ -0,0 +1,8 @@
+-- when and where a course is held, times in UTC
+ALTER TABLE course ADD COLUMN starts_at TEXT NULL;
+ALTER TABLE course ADD COLUMN ends_at TEXT NULL;
+ALTER TABLE course ADD COLUMN room TEXT NULL;
+
+-- used by the conflict checks and GET /teacher/{id}/availability
+CREATE INDEX idx_course_teacher_time ON course (teacher_id, starts_at);
+CREATE INDEX idx_course_room_time ON course (room, starts_at);
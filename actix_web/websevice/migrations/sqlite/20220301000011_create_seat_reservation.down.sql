@@ -0,0 +1,3 @@
+DROP TABLE seat_reservation;
+
+ALTER TABLE course DROP COLUMN capacity;
@@ -0,0 +1,14 @@
+-- seats that can be reserved, no limit when NULL
+ALTER TABLE course ADD COLUMN capacity INTEGER NULL;
+
+-- waitlisted reservations move up in id order as seats are released
+CREATE TABLE seat_reservation (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    course_id   INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    student_id  INTEGER NOT NULL REFERENCES student (id) ON DELETE CASCADE,
+    status      TEXT    NOT NULL,
+    reserved_at TEXT    NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (course_id, student_id)
+);
+
+CREATE INDEX idx_seat_reservation_status ON seat_reservation (course_id, status, id);
@@ -0,0 +1,5 @@
+DROP TABLE course_status_change;
+
+DROP INDEX idx_course_status;
+ALTER TABLE course DROP COLUMN published_at;
+ALTER TABLE course DROP COLUMN status;
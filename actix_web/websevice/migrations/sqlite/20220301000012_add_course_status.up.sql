@@ -0,0 +1,20 @@
+-- new courses start as drafts, the ones already live stay published
+ALTER TABLE course ADD COLUMN status TEXT NOT NULL DEFAULT 'draft';
+-- when a published course went live, or when a scheduled one will, in UTC
+ALTER TABLE course ADD COLUMN published_at TEXT NULL;
+
+UPDATE course SET status = 'published', published_at = time;
+
+CREATE INDEX idx_course_status ON course (status, published_at);
+
+-- who moved a course between statuses and when
+CREATE TABLE course_status_change (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    course_id   INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    from_status TEXT    NOT NULL,
+    to_status   TEXT    NOT NULL,
+    changed_by  INTEGER NULL REFERENCES app_user (id) ON DELETE SET NULL,
+    changed_at  TEXT    NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_course_status_change_course ON course_status_change (course_id, id);
@@ -0,0 +1,29 @@
+-- every saved version of a course's editable fields, numbered per course from 1
+CREATE TABLE course_revision (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    course_id   INTEGER NOT NULL REFERENCES course (id) ON DELETE CASCADE,
+    revision    INTEGER NOT NULL,
+    name        TEXT    NOT NULL,
+    starts_at   TEXT    NULL,
+    ends_at     TEXT    NULL,
+    room        TEXT    NULL,
+    capacity    INTEGER NULL,
+    description TEXT    NULL,
+    format      TEXT    NULL,
+    structure   TEXT    NULL,
+    duration    TEXT    NULL,
+    price       INTEGER NULL,
+    currency    TEXT    NULL,
+    language    TEXT    NULL,
+    level       TEXT    NULL,
+    changed_by  INTEGER NULL REFERENCES app_user (id) ON DELETE SET NULL,
+    created_at  TEXT    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (course_id, revision)
+);
+
+-- existing courses start their history as they are now
+INSERT INTO course_revision (course_id, revision, name, starts_at, ends_at, room, capacity, description,
+    format, structure, duration, price, currency, language, level, created_at)
+SELECT id, 1, name, starts_at, ends_at, room, capacity, description,
+    format, structure, duration, price, currency, language, level, COALESCE(time, CURRENT_TIMESTAMP)
+FROM course;
@@ -0,0 +1,6 @@
+-- set when a teacher or course is moved to the trash, the row goes for good once it is purged
+ALTER TABLE teacher ADD COLUMN deleted_at TEXT NULL;
+ALTER TABLE course ADD COLUMN deleted_at TEXT NULL;
+
+CREATE INDEX idx_teacher_deleted ON teacher (deleted_at);
+CREATE INDEX idx_course_deleted ON course (deleted_at);
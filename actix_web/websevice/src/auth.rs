@@ -0,0 +1,172 @@
+use crate::errors::MyError;
+use crate::models::instructor::{CourseInstructor, InstructorRole};
+use crate::models::user::{Role, User};
+use crate::state::AppState;
+use actix_web::dev::Payload;
+use actix_web::{http::header, web, FromRequest, HttpRequest};
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::future::{ready, Ready};
+
+//how long an issued token stays valid, in seconds
+pub const TOKEN_LIFETIME: i64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Claims {
+    pub sub: i32,
+    pub role: Role,
+    pub teacher_id: Option<i32>,
+    pub student_id: Option<i32>,
+    pub exp: i64,
+}
+
+pub fn issue_token(secret: &str, user: &User) -> Result<String, MyError> {
+    let claims = Claims {
+        sub: user.id,
+        role: user.role,
+        teacher_id: user.teacher_id,
+        student_id: user.student_id,
+        exp: Utc::now().timestamp() + TOKEN_LIFETIME,
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+        .map_err(|err| MyError::ActixError(err.to_string()))
+}
+
+pub fn decode_token(secret: &str, token: &str) -> Result<Claims, MyError> {
+    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
+        .map(|data| data.claims)
+        .map_err(|_| MyError::Unauthorized("Invalid or expired token".into()))
+}
+
+pub fn hash_password(password: &str) -> Result<String, MyError> {
+    bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|err| MyError::ActixError(err.to_string()))
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    bcrypt::verify(password, password_hash).unwrap_or(false)
+}
+
+//the caller of a request, taken from its bearer token
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub role: Role,
+    pub teacher_id: Option<i32>,
+    pub student_id: Option<i32>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+
+    pub fn require_admin(&self) -> Result<(), MyError> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden("Only admins can do this".into()))
+        }
+    }
+
+    //admins, or the teacher owning the resource
+    pub fn require_teacher(&self, teacher_id: i32) -> Result<(), MyError> {
+        if self.is_admin() || (self.role == Role::Teacher && self.teacher_id == Some(teacher_id)) {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden("You can only manage your own courses".into()))
+        }
+    }
+
+    //admins, or a teacher holding one of `roles` among the course's instructors
+    pub fn require_instructor(
+        &self, instructors: &[CourseInstructor], roles: &[InstructorRole]
+    ) -> Result<(), MyError> {
+        let holds_role = instructors
+            .iter()
+            .any(|instructor| roles.contains(&instructor.role) && self.require_teacher(instructor.teacher_id).is_ok());
+        if self.is_admin() || holds_role {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden("You can only manage courses you teach".into()))
+        }
+    }
+
+    //admins, or the student the resource belongs to
+    pub fn require_student(&self, student_id: i32) -> Result<(), MyError> {
+        if self.is_admin() || (self.role == Role::Student && self.student_id == Some(student_id)) {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden("You can only manage your own student record".into()))
+        }
+    }
+
+    //any staff member, used for reads that expose student data
+    pub fn require_staff(&self) -> Result<(), MyError> {
+        match self.role {
+            Role::Admin | Role::Teacher => Ok(()),
+            Role::Student => Err(MyError::Forbidden("Only staff can do this".into())),
+        }
+    }
+}
+
+impl From<Claims> for AuthUser {
+    fn from(claims: Claims) -> Self {
+        AuthUser {
+            user_id: claims.sub,
+            role: claims.role,
+            teacher_id: claims.teacher_id,
+            student_id: claims.student_id,
+        }
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(authenticate(req))
+    }
+}
+
+fn authenticate(req: &HttpRequest) -> Result<AuthUser, MyError> {
+    let app_state = req
+        .app_data::<web::Data<AppState>>()
+        .ok_or_else(|| MyError::ActixError("App state is not configured".into()))?;
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| MyError::Unauthorized("Missing bearer token".into()))?;
+
+    decode_token(&app_state.jwt_secret, token).map(AuthUser::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_round_trip() {
+        let user = User {
+            id: 7,
+            username: "teacher7".into(),
+            password_hash: "".into(),
+            role: Role::Teacher,
+            teacher_id: Some(3),
+            student_id: None,
+        };
+
+        let token = issue_token("secret", &user).unwrap();
+        let auth_user = AuthUser::from(decode_token("secret", &token).unwrap());
+
+        assert_eq!(auth_user.user_id, 7);
+        assert!(auth_user.require_teacher(3).is_ok());
+        assert!(auth_user.require_teacher(4).is_err());
+        assert!(decode_token("other secret", &token).is_err());
+    }
+}
@@ -5,10 +5,18 @@ use routers::*;
 use state::AppState;
 use dotenv::dotenv;
 use std::env;
-use sqlx::mysql::MySqlPoolOptions;
+use std::sync::Arc;
 use crate::errors::MyError;
+use crate::models::user::{CreateUser, Role};
+use crate::repository::Repository;
+use chrono::Utc;
+use std::time::Duration;
 use actix_cors::Cors;
 
+#[cfg(test)]
+#[macro_use]
+#[path = "../test_support.rs"]
+mod test_support;
 #[path = "../state.rs"]
 mod state;
 #[path = "../routers.rs"]
@@ -17,10 +25,21 @@ mod routers;
 mod handlers;
 #[path = "../models/mod.rs"]
 mod models;
+#[cfg(feature = "mysql")]
 #[path = "../dbaccess/mod.rs"]
 mod dbaccess;
+#[path = "../repository/mod.rs"]
+mod repository;
 #[path="../errors.rs"]
 mod errors;
+#[path="../auth.rs"]
+mod auth;
+#[path="../migrate.rs"]
+mod migrate;
+#[path="../openapi.rs"]
+mod openapi;
+#[path="../request_id.rs"]
+mod request_id;
 
 #[actix_rt::main]
 async fn main() -> io::Result<()> {
@@ -28,14 +47,76 @@ async fn main() -> io::Result<()> {
     dotenv().ok();
     let db_url = env::var("DATABASE_URL").expect("DATABASE Not found in .env");
 
-    //connect to database
-    let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
+    //connect to database, the backend follows the DATABASE_URL scheme
+    let db = repository::connect(&db_url).await.unwrap();
+
+    //`teacher-service migrate up|down|status` manages the schema and exits
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("migrate") {
+        return migrate_command(db, args.get(1).map(String::as_str)).await;
+    }
+
+    //deleted teachers and courses can be restored for TRASH_RETENTION_DAYS, 30 by default
+    let retention = models::trash::retention(env::var("TRASH_RETENTION_DAYS").ok().as_deref());
+
+    //`teacher-service purge` empties the expired part of the trash once and exits
+    if args.first().map(String::as_str) == Some("purge") {
+        let purged = db
+            .purge_trash(Utc::now() - retention)
+            .await
+            .map_err(|err| io::Error::other(format!("{:?}", err)))?;
+        println!("Purged {} records", purged);
+        return Ok(());
+    }
+
+    //`teacher-service --migrate` brings the schema up to date before serving
+    if args.iter().any(|arg| arg == "--migrate") {
+        db.run_migrations()
+            .await
+            .map_err(|err| io::Error::other(format!("{:?}", err)))?;
+    }
+
+    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET Not found in .env");
+
+    //bootstrap the first admin account, only when there is none yet
+    if let (Ok(username), Ok(password)) = (env::var("ADMIN_USERNAME"), env::var("ADMIN_PASSWORD")) {
+        if db.count_admins().await.unwrap() == 0 {
+            let admin = CreateUser {
+                username,
+                password: password.clone(),
+                role: Role::Admin,
+                teacher_id: None,
+                student_id: None,
+            };
+            let password_hash = auth::hash_password(&password).unwrap();
+            db.post_new_user(admin, password_hash).await.unwrap();
+        }
+    }
+
+    //rates for showing prices in another currency, e.g. "CNY=1,USD=0.157"
+    let exchange_rates = env::var("EXCHANGE_RATES")
+        .map(|rates| rates.parse().expect("EXCHANGE_RATES is invalid"))
+        .unwrap_or_default();
+
+    //purge expired trash every hour while serving
+    let purge_db = db.clone();
+    actix_rt::spawn(async move {
+        let mut every_hour = actix_rt::time::interval(Duration::from_secs(3600));
+        loop {
+            every_hour.tick().await;
+            if let Err(err) = purge_db.purge_trash(Utc::now() - retention).await {
+                println!("Purging the trash failed: {:?}", err);
+            }
+        }
+    });
 
     //init a app state
     let shared_data = web::Data::new(AppState {
         health_check_response: "I'm OK.".to_string(),
         visit_count: Mutex::new(0),
-        db: db_pool,
+        db,
+        jwt_secret,
+        exchange_rates,
     });
 
     //instance a app and register routes
@@ -45,21 +126,55 @@ async fn main() -> io::Result<()> {
             .allowed_origin_fn(|origin, _req_head| {
                 origin.as_bytes().starts_with(b"http://localhost")
             })
-            .allowed_methods(vec!["POST", "GET"])
-            .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
+            .allowed_methods(vec!["POST", "GET", "PUT", "PATCH", "DELETE"])
+            .allowed_headers(vec![
+                http::header::AUTHORIZATION,
+                http::header::ACCEPT,
+                http::header::IF_MATCH,
+                http::header::IF_NONE_MATCH,
+            ])
             .allowed_header(http::header::CONTENT_TYPE)
+            .expose_headers(vec![
+                http::header::HeaderName::from_static(request_id::REQUEST_ID_HEADER),
+                http::header::ETAG,
+            ])
             .max_age(3600);
 
         App::new()
             .app_data(shared_data.clone())
-            .app_data(web::JsonConfig::default().error_handler(|_err, _req| {
-                MyError::InvalidInput("Please provide valid Json input".to_string()).into()
-            }))
-            .configure(general_routes)
-            .configure(course_routes)
-            .configure(teacher_routes)
+            .configure(app_config)
+            .wrap_fn(request_id::assign)
             .wrap(cors)
     };
     //run a http server
     HttpServer::new(app).bind("127.0.0.1:3000")?.run().await
-}
\ No newline at end of file
+}
+
+async fn migrate_command(db: Arc<dyn Repository>, action: Option<&str>) -> io::Result<()> {
+    let to_io = |err: MyError| io::Error::other(format!("{:?}", err));
+
+    match action {
+        Some("up") => {
+            db.run_migrations().await.map_err(to_io)?;
+            println!("Migrations applied");
+        }
+        Some("down") => match db.revert_last_migration().await.map_err(to_io)? {
+            Some(version) => println!("Reverted migration {}", version),
+            None => println!("No migration to revert"),
+        },
+        Some("status") => {
+            for m in db.migration_status().await.map_err(to_io)? {
+                let state = if m.applied { "applied" } else { "pending" };
+                println!("{} {} ({})", m.version, m.description, state);
+            }
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "usage: teacher-service migrate <up|down|status>",
+            ))
+        }
+    }
+
+    Ok(())
+}
@@ -1,19 +1,139 @@
-use crate::models::course::{Course, CreateCourse, UpdateCourse};
-use crate::errors::MyError;
+use crate::models::course::{Course, CourseQuery, CourseRow, CreateCourse, UpdateCourse};
+use crate::models::etag::IfMatch;
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Language};
+use crate::models::patch::MergePatch;
+use crate::models::search::{CourseSearchQuery, FacetCount, SearchFacets};
+use crate::models::timetable::Booking;
+use crate::models::trash::TrashItem;
+use crate::dbaccess::{bind_args, bind_scalar_args, status_condition, SqlArg};
+use crate::dbaccess::revision::record_revision_db;
+use crate::dbaccess::seat::fill_seats_db;
+use crate::dbaccess::slug::assign_slug_db;
+use crate::errors::{MyError, Resource};
+use chrono::{DateTime, Utc};
 use sqlx::mysql::MySqlPool;
+use sqlx::{MySql, Transaction};
 
 pub async fn get_course_for_teacher_db(
-    pool: &MySqlPool, teacher_id: i32
-) -> Result<Vec<Course>, MyError> {
-    let rows = sqlx::query_as!(
-        Course,
-        r#"SELECT *
-        FROM course
-        WHERE teacher_id = ? "#,
-        teacher_id
-    ).fetch_all(pool).await?;
+    pool: &MySqlPool, teacher_id: i32, query: &CourseQuery
+) -> Result<(Vec<Course>, i64), MyError> {
+    let (page, per_page) = query.page_bounds();
+    let order_by = query.order_by()?;
+
+    //build the WHERE clause from the filters that were given
+    //courses the teacher leads or helps with
+    let mut filters = String::from(
+        "WHERE (teacher_id = ? OR id IN (SELECT course_id FROM course_instructor WHERE teacher_id = ?)) \
+        AND deleted_at IS NULL"
+    );
+    let mut args = vec![SqlArg::Int(teacher_id), SqlArg::Int(teacher_id)];
+    if let Some(language) = &query.language {
+        filters.push_str(" AND language = ?");
+        args.push(SqlArg::Str(language.as_str().into()));
+    }
+    if let Some(level) = &query.level {
+        filters.push_str(" AND level = ?");
+        args.push(SqlArg::Str(level.as_str().into()));
+    }
+    if let Some(min_price) = query.min_price {
+        filters.push_str(" AND price >= ?");
+        args.push(SqlArg::BigInt(min_price));
+    }
+    if let Some(max_price) = query.max_price {
+        filters.push_str(" AND price <= ?");
+        args.push(SqlArg::BigInt(max_price));
+    }
+    if let Some(currency) = &query.currency {
+        filters.push_str(" AND currency = ?");
+        args.push(SqlArg::Str(currency.as_str().into()));
+    }
+    if let Some(status) = query.status_filter() {
+        let condition = status_condition(status, Utc::now(), |arg| {
+            args.push(arg);
+            "?".into()
+        });
+        filters.push_str(&format!(" AND {}", condition));
+    }
 
-    Ok(rows)
+    let count_sql = format!("SELECT COUNT(*) FROM course {}", filters);
+    let rows_sql = format!("SELECT * FROM course {} {} LIMIT ? OFFSET ?", filters, order_by);
+
+    let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+        .fetch_one(pool).await?;
+    let rows = bind_args(sqlx::query_as::<_, CourseRow>(&rows_sql), &args)
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .fetch_all(pool).await?;
+
+    Ok((rows.into_iter().map(Course::from).collect(), total))
+}
+
+pub async fn search_courses_db(
+    pool: &MySqlPool, query: &CourseSearchQuery
+) -> Result<(Vec<Course>, i64, SearchFacets), MyError> {
+    let terms = query.terms()?;
+    let (page, per_page) = query.page_bounds();
+
+    //relies on the FULLTEXT index over (name, description, structure),
+    //which MySQL keeps up to date on every insert, update and delete
+    let mut filters = String::from(
+        "WHERE MATCH(name, description, structure) AGAINST (? IN NATURAL LANGUAGE MODE) AND deleted_at IS NULL"
+    );
+    let mut args = vec![SqlArg::Str(terms.to_string())];
+    if let Some(language) = &query.language {
+        filters.push_str(" AND language = ?");
+        args.push(SqlArg::Str(language.as_str().into()));
+    }
+    if let Some(level) = &query.level {
+        filters.push_str(" AND level = ?");
+        args.push(SqlArg::Str(level.as_str().into()));
+    }
+    if let Some(format) = &query.format {
+        filters.push_str(" AND format = ?");
+        args.push(SqlArg::Str(format.as_str().into()));
+    }
+    //search is public, drafts and archived courses never show up
+    let live = status_condition(CourseStatus::Published, Utc::now(), |arg| {
+        args.push(arg);
+        "?".into()
+    });
+    filters.push_str(&format!(" AND {}", live));
+
+    let count_sql = format!("SELECT COUNT(*) FROM course {}", filters);
+    let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+        .fetch_one(pool).await?;
+
+    //most relevant first
+    let rows_sql = format!(
+        "SELECT * FROM course {} \
+        ORDER BY MATCH(name, description, structure) AGAINST (? IN NATURAL LANGUAGE MODE) DESC, id \
+        LIMIT ? OFFSET ?",
+        filters
+    );
+    let rows: Vec<Course> = bind_args(sqlx::query_as::<_, CourseRow>(&rows_sql), &args)
+        .bind(terms)
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .fetch_all(pool).await?
+        .into_iter()
+        .map(Course::from)
+        .collect();
+
+    let mut facets = SearchFacets::default();
+    for (field, counts) in [
+        ("language", &mut facets.language),
+        ("level", &mut facets.level),
+        ("format", &mut facets.format),
+    ] {
+        let facet_sql = format!(
+            "SELECT {} AS value, COUNT(*) AS count FROM course {} GROUP BY {} ORDER BY count DESC",
+            field, filters, field
+        );
+        *counts = bind_args(sqlx::query_as::<_, FacetCount>(&facet_sql), &args)
+            .fetch_all(pool).await?;
+    }
+
+    Ok((rows, total, facets))
 }
 
 pub async fn get_course_detail_db(
@@ -21,143 +141,259 @@ pub async fn get_course_detail_db(
 ) -> Result<Course, MyError> {
     //fetch_optional: can find it or can't
     let row = sqlx::query_as!(
-        Course,
-        r#"SELECT *
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
         FROM course
-        WHERE teacher_id = ? and id = ? "#,
+        WHERE teacher_id = ? and id = ? and deleted_at IS NULL"#,
         teacher_id,
         course_id,
     ).fetch_optional(pool).await?;
 
     if let Some(course) = row {
-        Ok(course)
+        Ok(course.into())
     }else {
-        Err(MyError::NotFound("Course ID not found".to_string()))
+        Err(missing_course_db(pool, teacher_id, course_id).await)
+    }
+}
+
+//why a course could not be read: 410 while it is in the trash, 404 otherwise
+pub async fn missing_course_db(pool: &MySqlPool, teacher_id: i32, course_id: i32) -> MyError {
+    let trashed = sqlx::query_scalar!(
+        "SELECT id FROM course WHERE teacher_id = ? and id = ? and deleted_at IS NOT NULL",
+        teacher_id,
+        course_id,
+    ).fetch_optional(pool).await;
+
+    match trashed {
+        Ok(Some(_)) => MyError::Gone(Resource::Course, "Course was deleted".into()),
+        Ok(None) => MyError::NotFound(Resource::Course, "Course ID not found".into()),
+        Err(err) => err.into(),
     }
 }
 
+//the course as it is now, its row stays locked until the transaction ends
+//so nothing saved in between gets lost
+pub async fn lock_course_detail_db(
+    tx: &mut Transaction<'_, MySql>, teacher_id: i32, course_id: i32
+) -> Result<Course, MyError> {
+    let row = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE teacher_id = ? and id = ? and deleted_at IS NULL
+        FOR UPDATE"#,
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut *tx).await?;
+    if let Some(course) = row {
+        return Ok(course.into());
+    }
+
+    //not there or in the trash
+    let trashed = sqlx::query_scalar!(
+        "SELECT id FROM course WHERE teacher_id = ? and id = ?",
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut *tx).await?;
+    match trashed {
+        Some(_) => Err(MyError::Gone(Resource::Course, "Course was deleted".into())),
+        None => Err(MyError::NotFound(Resource::Course, "Course ID not found".into())),
+    }
+}
+
+//fails with the courses the booking would overlap, locking them until the transaction ends
+pub async fn check_timetable_db(tx: &mut Transaction<'_, MySql>, booking: &Booking) -> Result<(), MyError> {
+    let rows = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE (teacher_id = ? OR room = ?) AND starts_at < ? AND ends_at > ? AND id <> ?
+        AND deleted_at IS NULL
+        FOR UPDATE"#,
+        booking.teacher_id,
+        booking.room,
+        booking.ends_at,
+        booking.starts_at,
+        booking.course_id.unwrap_or(0),
+    ).fetch_all(&mut *tx).await?;
+
+    booking.check(&rows.into_iter().map(Course::from).collect::<Vec<_>>())
+}
+
+pub async fn get_courses_between_db(
+    pool: &MySqlPool, teacher_id: i32, from: DateTime<Utc>, to: DateTime<Utc>
+) -> Result<Vec<Course>, MyError> {
+    let rows = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE teacher_id = ? AND starts_at < ? AND ends_at > ? AND deleted_at IS NULL
+        ORDER BY starts_at, id"#,
+        teacher_id,
+        to,
+        from,
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(Course::from).collect())
+}
+
 pub async fn post_new_course_db(
-    pool: &MySqlPool, new_course: CreateCourse
+    pool: &MySqlPool, new_course: CreateCourse, changed_by: i32
 ) -> Result<Course, MyError> {
-    let _ = sqlx::query!(
-        r#"INSERT INTO course (teacher_id, name, description, format, structure, duration, price, language, level)
-        VALUE (?, ?, ?, ?, ?, ?, ?, ?, ?) "#,
+    let mut tx = pool.begin().await?;
+    if let Some(booking) = Booking::of_new(&new_course)? {
+        check_timetable_db(&mut tx, &booking).await?;
+    }
+    let post_row = sqlx::query!(
+        r#"INSERT INTO course (teacher_id, name, starts_at, ends_at, room, capacity, description, format,
+        structure, duration, price, currency, language, level)
+        VALUE (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) "#,
         new_course.teacher_id,
-        new_course.name.clone(),
+        new_course.name,
+        new_course.starts_at,
+        new_course.ends_at,
+        new_course.room,
+        new_course.capacity,
         new_course.description,
         new_course.format,
         new_course.structure,
         new_course.duration,
-        new_course.price,
+        new_course.price.map(|price| price.amount),
+        new_course.price.map(|price| price.currency),
         new_course.language,
         new_course.level,
-    ).execute(pool).await?;
+    ).execute(&mut tx).await?;
 
-    //just fetch one, do NOT consider duplicate course name
+    //read back by id, names are not unique
     let course_row = sqlx::query_as!(
-        Course,
-        r#"SELECT * FROM course WHERE name = ? "#,
-        new_course.name
-    ).fetch_one(pool).await?;
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course WHERE id = ?"#,
+        post_row.last_insert_id()
+    ).fetch_one(&mut tx).await?;
+
+    let course: Course = course_row.into();
+    record_revision_db(&mut tx, &course, changed_by).await?;
+    assign_slug_db(&mut tx, &course).await?;
+    tx.commit().await?;
 
-    //println!("row = {:?}", row);
-    Ok(course_row)
+    Ok(course)
 }
 
+//moves the course to the trash, the row and everything hanging off it stay until purge_trash_db
 pub async fn delete_course_db(
-    pool: &MySqlPool, teacher_id: i32, id: i32
-) -> Result<String, MyError> {
+    pool: &MySqlPool, teacher_id: i32, id: i32, if_match: &IfMatch
+) -> Result<TrashItem, MyError> {
+    let current = get_course_detail_db(pool, teacher_id, id).await?;
+    if_match.check(current.version)?;
+
+    let deleted_at = Utc::now();
     let course_row = sqlx::query!(
-        "DELETE FROM course WHERE teacher_id = ? and id = ?",
+        "UPDATE course SET deleted_at = ?, version = version + 1 \
+        WHERE teacher_id = ? and id = ? and version = ? and deleted_at IS NULL",
+        deleted_at,
         teacher_id,
         id,
+        current.version,
     ).execute(pool).await?;
+    if course_row.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The course has changed since it was read".into()));
+    }
 
-    Ok(format!("Deleted {:?} record", course_row))
+    Ok(TrashItem::course(&Course { version: current.version + 1, ..current }, deleted_at))
 }
 
 pub async fn update_course_db(
-    pool: &MySqlPool, teacher_id: i32, id: i32, update_course: UpdateCourse
+    pool: &MySqlPool, teacher_id: i32, id: i32, update_course: UpdateCourse, changed_by: i32, if_match: &IfMatch
 ) -> Result<Course, MyError> {
-    let current_course_row = sqlx::query_as!(
-        Course,
-        "SELECT * FROM course WHERE teacher_id = ? and id = ?",
-        teacher_id,
-        id,
-    )
-        .fetch_one(pool)
-        .await
-        .map_err(|_| MyError::NotFound("Course ID not found".into()))?;
+    let mut tx = pool.begin().await?;
+    let current = lock_course_detail_db(&mut tx, teacher_id, id).await?;
+    if_match.check(current.version)?;
 
-    let name = update_course.name;
+    let course = update_course.replace(current);
+    if let Some(booking) = Booking::of(&course)? {
+        check_timetable_db(&mut tx, &booking).await?;
+    }
 
-    let description = if let Some(description) = update_course.description {
-        Some(description)
-    }else {
-        current_course_row.description
-    };
+    let course = save_course_db(&mut tx, course, changed_by).await?;
+    tx.commit().await?;
 
-    let format = if let Some(format) = update_course.format {
-        Some(format)
-    }else {
-        current_course_row.format
-    };
+    Ok(course)
+}
 
-    let structure = if let Some(structure) = update_course.structure {
-        Some(structure)
-    }else {
-        current_course_row.structure
-    };
+pub async fn patch_course_db(
+    pool: &MySqlPool, teacher_id: i32, id: i32, patch: &MergePatch, changed_by: i32, if_match: &IfMatch
+) -> Result<Course, MyError> {
+    let mut tx = pool.begin().await?;
+    let current = lock_course_detail_db(&mut tx, teacher_id, id).await?;
+    if_match.check(current.version)?;
 
-    let duration = if let Some(duration) = update_course.duration {
-        Some(duration)
-    }else {
-        current_course_row.duration
-    };
+    let course = current.patched(patch)?;
+    if let Some(booking) = Booking::of(&course)? {
+        check_timetable_db(&mut tx, &booking).await?;
+    }
 
-    let price = if let Some(price) = update_course.price {
-        Some(price)
-    }else {
-        current_course_row.price
-    };
+    let course = save_course_db(&mut tx, course, changed_by).await?;
+    tx.commit().await?;
 
-    let language = if let Some(language) = update_course.language {
-        Some(language)
-    }else {
-        current_course_row.language
-    };
+    Ok(course)
+}
 
-    let level = if let Some(level) = update_course.level {
-        Some(level)
-    }else {
-        current_course_row.level
-    };
-
-    let _ = sqlx::query_as!(
-        Course,
-        "UPDATE course SET name = ?, description = ?, format = ?, structure = ?, duration = ?, \
-        price = ?, language = ?, level = ?",
-        name,
-        description,
-        format,
-        structure,
-        duration,
-        price,
-        language,
-        level,
-    ).execute(pool).await?;
+//stores changed fields of an existing course, the same way for updates, patches and restores,
+//unless another save got in after the course was read
+pub async fn save_course_db(
+    tx: &mut Transaction<'_, MySql>, course: Course, changed_by: i32
+) -> Result<Course, MyError> {
+    let updated = sqlx::query!(
+        "UPDATE course SET name = ?, starts_at = ?, ends_at = ?, room = ?, capacity = ?, description = ?, \
+        format = ?, structure = ?, duration = ?, price = ?, currency = ?, language = ?, level = ?, \
+        version = version + 1 WHERE teacher_id = ? and id = ? and version = ? and deleted_at IS NULL",
+        course.name,
+        course.starts_at,
+        course.ends_at,
+        course.room,
+        course.capacity,
+        course.description,
+        course.format,
+        course.structure,
+        course.duration,
+        course.price.map(|price| price.amount),
+        course.price.map(|price| price.currency),
+        course.language,
+        course.level,
+        course.teacher_id,
+        course.id,
+        course.version,
+    ).execute(&mut *tx).await?;
+    if updated.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The course has changed since it was read".into()));
+    }
 
-    Ok(Course{
-        teacher_id,
-        id,
-        name,
-        time: current_course_row.time,
-        description,
-        format,
-        structure,
-        duration,
-        price,
-        language,
-        level,
-    })
+    let course = Course { version: course.version + 1, ..course };
+    record_revision_db(tx, &course, changed_by).await?;
+    assign_slug_db(tx, &course).await?;
+    //a raised capacity lets the waitlist in
+    fill_seats_db(tx, course.id, course.capacity).await?;
+
+    Ok(course)
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+use sqlx::MySqlPool;
+use crate::dbaccess::course::get_course_detail_db;
+use crate::dbaccess::student::get_student_details_db;
+use crate::errors::{MyError, Resource};
+use crate::models::enrollment::{CreateEnrollment, Enrollment};
+
+pub async fn post_new_enrollment_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, new_enrollment: CreateEnrollment
+) -> Result<Enrollment, MyError> {
+    //both ends of the enrollment have to exist
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+    let student = get_student_details_db(pool, new_enrollment.student_id).await?;
+
+    //the check and the insert see the same rows, uq_enrollment catches what slips past
+    let mut tx = pool.begin().await?;
+    let existing = sqlx::query!(
+        "SELECT id FROM enrollment WHERE student_id = ? and course_id = ? FOR UPDATE",
+        student.id,
+        course.id,
+    ).fetch_optional(&mut tx).await?;
+
+    if existing.is_some() {
+        return Err(MyError::Conflict("Student already enrolled in this course".into()));
+    }
+
+    let post_row = sqlx::query!(
+        "INSERT INTO enrollment (student_id, course_id) VALUE (?, ?)",
+        student.id,
+        course.id,
+    ).execute(&mut tx).await?;
+
+    let row = sqlx::query_as!(
+        Enrollment,
+        "SELECT * FROM enrollment WHERE id = ?",
+        post_row.last_insert_id()
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(row)
+}
+
+pub async fn get_enrollments_for_course_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<Enrollment>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        Enrollment,
+        "SELECT * FROM enrollment WHERE course_id = ? ORDER BY enrolled_at",
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn get_enrollments_for_student_db(
+    pool: &MySqlPool, student_id: i32
+) -> Result<Vec<Enrollment>, MyError> {
+    let student = get_student_details_db(pool, student_id).await?;
+
+    let rows = sqlx::query_as!(
+        Enrollment,
+        "SELECT e.* FROM enrollment e JOIN course c ON c.id = e.course_id \
+        WHERE e.student_id = ? AND c.deleted_at IS NULL ORDER BY e.enrolled_at",
+        student.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn delete_enrollment_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, student_id: i32
+) -> Result<String, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let row = sqlx::query!(
+        "DELETE FROM enrollment WHERE course_id = ? and student_id = ?",
+        course.id,
+        student_id,
+    ).execute(pool).await?;
+
+    if row.rows_affected() == 0 {
+        return Err(MyError::NotFound(Resource::Enrollment, "Enrollment not found".into()));
+    }
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
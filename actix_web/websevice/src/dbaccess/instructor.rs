@@ -0,0 +1,82 @@
+use sqlx::MySqlPool;
+use crate::dbaccess::course::get_course_detail_db;
+use crate::dbaccess::teacher::get_teacher_details_db;
+use crate::errors::{MyError, Resource};
+use crate::models::instructor::{AddInstructor, CourseInstructor, InstructorRow};
+use chrono::Utc;
+
+pub async fn get_instructors_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<CourseInstructor>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+    let lead = get_teacher_details_db(pool, teacher_id).await?;
+
+    //teachers in the trash are left out
+    let rows = sqlx::query_as::<_, InstructorRow>(
+        "SELECT i.course_id, i.teacher_id, t.name, i.role, i.added_at \
+        FROM course_instructor i JOIN teacher t ON t.id = i.teacher_id \
+        WHERE i.course_id = ? AND t.deleted_at IS NULL \
+        ORDER BY i.added_at, i.teacher_id"
+    )
+        .bind(course.id)
+        .fetch_all(pool).await?;
+
+    let mut instructors = vec![CourseInstructor::lead(&course, &lead)];
+    for row in rows {
+        instructors.push(row.try_into()?);
+    }
+    Ok(instructors)
+}
+
+pub async fn add_instructor_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, new_instructor: AddInstructor
+) -> Result<CourseInstructor, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+    //the foreign key does not see teachers in the trash
+    let teacher = match get_teacher_details_db(pool, new_instructor.teacher_id).await {
+        Ok(teacher) => teacher,
+        Err(MyError::NotFound(..)) | Err(MyError::Gone(..)) => {
+            return Err(MyError::InvalidReference("A referenced record does not exist".into()))
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "INSERT INTO course_instructor (course_id, teacher_id, role, added_at) VALUES (?, ?, ?, ?)",
+        course.id,
+        teacher.id,
+        new_instructor.role.as_str(),
+        Utc::now(),
+    ).execute(&mut tx).await?;
+
+    //added_at as the database stored it
+    let row = sqlx::query_as::<_, InstructorRow>(
+        "SELECT i.course_id, i.teacher_id, t.name, i.role, i.added_at \
+        FROM course_instructor i JOIN teacher t ON t.id = i.teacher_id \
+        WHERE i.course_id = ? AND i.teacher_id = ?"
+    )
+        .bind(course.id)
+        .bind(teacher.id)
+        .fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    row.try_into()
+}
+
+pub async fn remove_instructor_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, instructor_id: i32
+) -> Result<String, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let row = sqlx::query!(
+        "DELETE FROM course_instructor WHERE course_id = ? AND teacher_id = ?",
+        course.id,
+        instructor_id,
+    ).execute(pool).await?;
+
+    match row.rows_affected() {
+        0 => Err(MyError::NotFound(Resource::Instructor, "Instructor not found".into())),
+        deleted => Ok(format!("Deleted {} record", deleted)),
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use sqlx::MySqlPool;
+use crate::dbaccess::course::{get_course_detail_db, missing_course_db};
+use crate::errors::MyError;
+use crate::models::course::{Course, CourseRow};
+use crate::models::lifecycle::{StatusChange, Transition};
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Language};
+
+pub async fn change_course_status_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, transition: Transition, changed_by: i32
+) -> Result<Course, MyError> {
+    let now = Utc::now();
+    let mut tx = pool.begin().await?;
+
+    //locked so two requests cannot both move the course on from the same status
+    let course = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE teacher_id = ? and id = ? and deleted_at IS NULL FOR UPDATE"#,
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut tx).await?;
+    let course: Course = match course {
+        Some(row) => row.into(),
+        None => return Err(missing_course_db(pool, teacher_id, course_id).await),
+    };
+    let (status, published_at) = transition.apply(&course, now)?;
+
+    sqlx::query!(
+        "UPDATE course SET status = ?, published_at = ?, version = version + 1 WHERE id = ?",
+        status,
+        published_at,
+        course.id,
+    ).execute(&mut tx).await?;
+    sqlx::query!(
+        "INSERT INTO course_status_change (course_id, from_status, to_status, changed_by, changed_at) \
+        VALUES (?, ?, ?, ?, ?)",
+        course.id,
+        course.status,
+        status,
+        changed_by,
+        now,
+    ).execute(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(Course { status, published_at, version: course.version + 1, ..course })
+}
+
+pub async fn get_status_changes_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<StatusChange>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let changes = sqlx::query_as!(
+        StatusChange,
+        r#"SELECT id, course_id, from_status as "from_status: CourseStatus",
+        to_status as "to_status: CourseStatus", changed_by,
+        changed_at as "changed_at: DateTime<Utc>"
+        FROM course_status_change WHERE course_id = ? ORDER BY id"#,
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(changes)
+}
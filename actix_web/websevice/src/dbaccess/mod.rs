@@ -1,2 +1,47 @@
+use sqlx::mysql::{MySql, MySqlArguments};
+use sqlx::query::{QueryAs, QueryScalar};
+
 pub mod course;
-pub mod teacher;
\ No newline at end of file
+pub mod enrollment;
+pub mod instructor;
+pub mod lifecycle;
+pub mod revision;
+pub mod seat;
+pub mod session;
+pub mod slug;
+pub mod student;
+pub mod syllabus;
+pub mod teacher;
+pub mod transfer;
+pub mod trash;
+pub mod user;
+
+pub use crate::repository::{status_condition, SqlArg};
+
+pub fn bind_args<'q, O>(
+    mut query: QueryAs<'q, MySql, O, MySqlArguments>, args: &'q [SqlArg]
+) -> QueryAs<'q, MySql, O, MySqlArguments> {
+    for arg in args {
+        query = match arg {
+            SqlArg::Int(value) => query.bind(*value),
+            SqlArg::BigInt(value) => query.bind(*value),
+            SqlArg::Str(value) => query.bind(value.as_str()),
+            SqlArg::Time(value) => query.bind(*value),
+        };
+    }
+    query
+}
+
+pub fn bind_scalar_args<'q, O>(
+    mut query: QueryScalar<'q, MySql, O, MySqlArguments>, args: &'q [SqlArg]
+) -> QueryScalar<'q, MySql, O, MySqlArguments> {
+    for arg in args {
+        query = match arg {
+            SqlArg::Int(value) => query.bind(*value),
+            SqlArg::BigInt(value) => query.bind(*value),
+            SqlArg::Str(value) => query.bind(value.as_str()),
+            SqlArg::Time(value) => query.bind(*value),
+        };
+    }
+    query
+}
\ No newline at end of file
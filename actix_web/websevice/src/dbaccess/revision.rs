@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlPool, Transaction};
+use crate::dbaccess::course::{check_timetable_db, get_course_detail_db, lock_course_detail_db, save_course_db};
+use crate::errors::{MyError, Resource};
+use crate::models::course::Course;
+use crate::models::meta::{CourseFormat, CourseLevel, Currency, Language};
+use crate::models::revision::{CourseRevision, RevisionRow};
+use crate::models::timetable::Booking;
+
+//keeps the course as just saved under the next revision number,
+//the course row is written first in the same transaction so no other save takes the number
+pub async fn record_revision_db(
+    tx: &mut Transaction<'_, MySql>, course: &Course, changed_by: i32
+) -> Result<(), MyError> {
+    let revision = sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(revision), 0) + 1 as "revision!: i32" FROM course_revision WHERE course_id = ?"#,
+        course.id
+    ).fetch_one(&mut *tx).await?;
+
+    sqlx::query!(
+        r#"INSERT INTO course_revision (course_id, revision, name, starts_at, ends_at, room, capacity,
+        description, format, structure, duration, price, currency, language, level, changed_by, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        course.id,
+        revision,
+        course.name,
+        course.starts_at,
+        course.ends_at,
+        course.room,
+        course.capacity,
+        course.description,
+        course.format,
+        course.structure,
+        course.duration,
+        course.price.map(|price| price.amount),
+        course.price.map(|price| price.currency),
+        course.language,
+        course.level,
+        changed_by,
+        Utc::now(),
+    ).execute(&mut *tx).await?;
+
+    Ok(())
+}
+
+pub async fn get_revisions_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<CourseRevision>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        RevisionRow,
+        r#"SELECT id, course_id, revision, name, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel", changed_by,
+        created_at as "created_at: DateTime<Utc>"
+        FROM course_revision WHERE course_id = ? ORDER BY revision"#,
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(CourseRevision::from).collect())
+}
+
+pub async fn get_revision_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, revision: i32
+) -> Result<CourseRevision, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let row = sqlx::query_as!(
+        RevisionRow,
+        r#"SELECT id, course_id, revision, name, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel", changed_by,
+        created_at as "created_at: DateTime<Utc>"
+        FROM course_revision WHERE course_id = ? AND revision = ?"#,
+        course.id,
+        revision,
+    ).fetch_optional(pool).await?;
+
+    row.map(CourseRevision::from)
+        .ok_or_else(|| MyError::NotFound(Resource::Revision, "Revision not found".into()))
+}
+
+pub async fn restore_revision_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, revision: i32, changed_by: i32
+) -> Result<Course, MyError> {
+    let mut tx = pool.begin().await?;
+    let current = lock_course_detail_db(&mut tx, teacher_id, course_id).await?;
+    let course = get_revision_db(pool, teacher_id, course_id, revision).await?.restore_onto(current);
+    if let Some(booking) = Booking::of(&course)? {
+        check_timetable_db(&mut tx, &booking).await?;
+    }
+
+    let course = save_course_db(&mut tx, course, changed_by).await?;
+    tx.commit().await?;
+
+    Ok(course)
+}
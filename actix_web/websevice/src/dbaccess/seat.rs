@@ -0,0 +1,137 @@
+use sqlx::{MySql, MySqlPool, Transaction};
+use crate::dbaccess::course::get_course_detail_db;
+use crate::errors::{MyError, Resource};
+use crate::models::seat::{
+    open_seats, seat_status, with_positions, CreateReservation, Reservation, ReservationRow,
+};
+
+//the course's capacity, its row stays locked until the transaction ends
+//so seat counts read after it stay true
+async fn lock_course_db(
+    tx: &mut Transaction<'_, MySql>, teacher_id: i32, course_id: i32
+) -> Result<(i32, Option<i32>), MyError> {
+    let row = sqlx::query!(
+        "SELECT id, capacity FROM course WHERE teacher_id = ? and id = ? and deleted_at IS NULL FOR UPDATE",
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut *tx).await?;
+    if let Some(course) = row {
+        return Ok((course.id, course.capacity));
+    }
+
+    //not there or in the trash
+    let trashed = sqlx::query_scalar!(
+        "SELECT id FROM course WHERE teacher_id = ? and id = ?",
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut *tx).await?;
+    match trashed {
+        Some(_) => Err(MyError::Gone(Resource::Course, "Course was deleted".into())),
+        None => Err(MyError::NotFound(Resource::Course, "Course ID not found".into())),
+    }
+}
+
+async fn count_reserved_db(tx: &mut Transaction<'_, MySql>, course_id: i32) -> Result<i64, MyError> {
+    let reserved = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM seat_reservation WHERE course_id = ? AND status = 'reserved'",
+        course_id
+    ).fetch_one(&mut *tx).await?;
+
+    Ok(reserved)
+}
+
+//moves the head of the waitlist into any free seats
+pub async fn fill_seats_db(
+    tx: &mut Transaction<'_, MySql>, course_id: i32, capacity: Option<i32>
+) -> Result<(), MyError> {
+    let reserved = count_reserved_db(tx, course_id).await?;
+
+    sqlx::query!(
+        "UPDATE seat_reservation SET status = 'reserved' \
+        WHERE course_id = ? AND status = 'waitlisted' ORDER BY id LIMIT ?",
+        course_id,
+        open_seats(capacity, reserved),
+    ).execute(&mut *tx).await?;
+
+    Ok(())
+}
+
+pub async fn get_reservations_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<Reservation>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        ReservationRow,
+        "SELECT * FROM seat_reservation WHERE course_id = ? ORDER BY id",
+        course.id
+    ).fetch_all(pool).await?;
+
+    let mut reservations = with_positions(rows)?;
+    reservations.sort_by_key(|r| (r.waitlist_position, r.id));
+    Ok(reservations)
+}
+
+pub async fn get_reservation_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, student_id: i32
+) -> Result<Reservation, MyError> {
+    //the waitlist position depends on the reservations made before this one
+    get_reservations_db(pool, teacher_id, course_id).await?
+        .into_iter()
+        .find(|r| r.student_id == student_id)
+        .ok_or_else(|| MyError::NotFound(Resource::Reservation, "Reservation not found".into()))
+}
+
+pub async fn reserve_seat_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, new_reservation: CreateReservation
+) -> Result<Reservation, MyError> {
+    let mut tx = pool.begin().await?;
+    let (course_id, capacity) = lock_course_db(&mut tx, teacher_id, course_id).await?;
+
+    let student = sqlx::query!("SELECT id FROM student WHERE id = ?", new_reservation.student_id)
+        .fetch_optional(&mut tx).await?
+        .ok_or_else(|| MyError::NotFound(Resource::Student, "Student id not found".into()))?;
+
+    let existing = sqlx::query!(
+        "SELECT id FROM seat_reservation WHERE course_id = ? and student_id = ?",
+        course_id,
+        student.id,
+    ).fetch_optional(&mut tx).await?;
+
+    if existing.is_some() {
+        return Err(MyError::Conflict("Student already has a seat or a waitlist place in this course".into()));
+    }
+
+    let reserved = count_reserved_db(&mut tx, course_id).await?;
+    sqlx::query!(
+        "INSERT INTO seat_reservation (course_id, student_id, status) VALUE (?, ?, ?)",
+        course_id,
+        student.id,
+        seat_status(capacity, reserved).as_str(),
+    ).execute(&mut tx).await?;
+    tx.commit().await?;
+
+    get_reservation_db(pool, teacher_id, course_id, student.id).await
+}
+
+pub async fn release_seat_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, student_id: i32
+) -> Result<String, MyError> {
+    let mut tx = pool.begin().await?;
+    let (course_id, capacity) = lock_course_db(&mut tx, teacher_id, course_id).await?;
+
+    let row = sqlx::query!(
+        "DELETE FROM seat_reservation WHERE course_id = ? and student_id = ?",
+        course_id,
+        student_id,
+    ).execute(&mut tx).await?;
+
+    if row.rows_affected() == 0 {
+        return Err(MyError::NotFound(Resource::Reservation, "Reservation not found".into()));
+    }
+
+    fill_seats_db(&mut tx, course_id, capacity).await?;
+    tx.commit().await?;
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
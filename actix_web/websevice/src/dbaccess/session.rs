@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use sqlx::MySqlPool;
+use crate::dbaccess::course::get_course_detail_db;
+use crate::dbaccess::teacher::get_teacher_details_db;
+use crate::errors::{MyError, Resource};
+use crate::models::meta::Frequency;
+use crate::models::session::{CourseSession, CreateSession, UpdateSession};
+
+async fn get_session_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, session_id: i32
+) -> Result<CourseSession, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let row = sqlx::query_as!(
+        CourseSession,
+        r#"SELECT id, course_id, starts_at as "starts_at: DateTime<Utc>", ends_at as "ends_at: DateTime<Utc>",
+        timezone, location, meeting_url, frequency as "frequency: Frequency", occurrences,
+        repeat_until as "repeat_until: DateTime<Utc>"
+        FROM course_session WHERE course_id = ? and id = ?"#,
+        course.id,
+        session_id,
+    ).fetch_optional(pool).await?;
+
+    row.ok_or_else(|| MyError::NotFound(Resource::Session, "Session not found".into()))
+}
+
+pub async fn get_sessions_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<CourseSession>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        CourseSession,
+        r#"SELECT id, course_id, starts_at as "starts_at: DateTime<Utc>", ends_at as "ends_at: DateTime<Utc>",
+        timezone, location, meeting_url, frequency as "frequency: Frequency", occurrences,
+        repeat_until as "repeat_until: DateTime<Utc>"
+        FROM course_session WHERE course_id = ? ORDER BY starts_at, id"#,
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn get_sessions_for_teacher_db(
+    pool: &MySqlPool, teacher_id: i32
+) -> Result<Vec<CourseSession>, MyError> {
+    let teacher = get_teacher_details_db(pool, teacher_id).await?;
+
+    let rows = sqlx::query_as!(
+        CourseSession,
+        r#"SELECT s.id, s.course_id, s.starts_at as "starts_at: DateTime<Utc>", s.ends_at as "ends_at: DateTime<Utc>",
+        s.timezone, s.location, s.meeting_url, s.frequency as "frequency: Frequency", s.occurrences,
+        s.repeat_until as "repeat_until: DateTime<Utc>"
+        FROM course_session s JOIN course c ON c.id = s.course_id
+        WHERE c.teacher_id = ? AND c.deleted_at IS NULL ORDER BY s.starts_at, s.id"#,
+        teacher.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn post_new_session_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, new_session: CreateSession
+) -> Result<CourseSession, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let post_row = sqlx::query!(
+        "INSERT INTO course_session (course_id, starts_at, ends_at, timezone, location, meeting_url, \
+        frequency, occurrences, repeat_until) VALUE (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        course.id,
+        new_session.starts_at,
+        new_session.ends_at,
+        new_session.timezone,
+        new_session.location,
+        new_session.meeting_url,
+        new_session.frequency,
+        new_session.occurrences,
+        new_session.repeat_until,
+    ).execute(pool).await?;
+
+    get_session_db(pool, teacher_id, course_id, post_row.last_insert_id() as i32).await
+}
+
+pub async fn update_session_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, session_id: i32, update_session: UpdateSession
+) -> Result<CourseSession, MyError> {
+    let current = get_session_db(pool, teacher_id, course_id, session_id).await?;
+    let session = update_session.apply_to(current)?;
+
+    sqlx::query!(
+        "UPDATE course_session SET starts_at = ?, ends_at = ?, timezone = ?, location = ?, \
+        meeting_url = ?, frequency = ?, occurrences = ?, repeat_until = ? WHERE id = ?",
+        session.starts_at,
+        session.ends_at,
+        session.timezone,
+        session.location,
+        session.meeting_url,
+        session.frequency,
+        session.occurrences,
+        session.repeat_until,
+        session.id,
+    ).execute(pool).await?;
+
+    Ok(session)
+}
+
+pub async fn delete_session_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, session_id: i32
+) -> Result<String, MyError> {
+    let session = get_session_db(pool, teacher_id, course_id, session_id).await?;
+
+    let row = sqlx::query!("DELETE FROM course_session WHERE id = ?", session.id)
+        .execute(pool).await?;
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
@@ -0,0 +1,66 @@
+use sqlx::{MySql, MySqlPool, Transaction};
+use crate::dbaccess::course::get_course_detail_db;
+use crate::errors::{MyError, Resource};
+use crate::models::course::Course;
+use crate::models::slug::{next_slug, taken_pattern, SlugLookup};
+
+//gives the course the slug next_slug picks for its name, the slugs it had before stay for redirects
+pub async fn assign_slug_db(tx: &mut Transaction<'_, MySql>, course: &Course) -> Result<(), MyError> {
+    let own = sqlx::query!(
+        r#"SELECT slug, current as "current: bool" FROM course_slug WHERE course_id = ?"#,
+        course.id,
+    ).fetch_all(&mut *tx).await?;
+    let (base, pattern) = taken_pattern(&course.name);
+    let taken = sqlx::query_scalar!(
+        "SELECT slug FROM course_slug WHERE slug = ? OR slug LIKE ?",
+        base,
+        pattern,
+    ).fetch_all(&mut *tx).await?;
+
+    let current = own.iter().find(|row| row.current).map(|row| row.slug.as_str());
+    let own: Vec<String> = own.iter().map(|row| row.slug.clone()).collect();
+    let slug = match next_slug(&course.name, current, &own, &taken) {
+        Some(slug) => slug,
+        None => return Ok(()),
+    };
+
+    sqlx::query!("UPDATE course_slug SET current = FALSE WHERE course_id = ?", course.id)
+        .execute(&mut *tx).await?;
+    if own.contains(&slug) {
+        sqlx::query!("UPDATE course_slug SET current = TRUE WHERE slug = ?", slug)
+            .execute(&mut *tx).await?;
+    } else {
+        sqlx::query!("INSERT INTO course_slug (slug, course_id) VALUES (?, ?)", slug, course.id)
+            .execute(&mut *tx).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_course_by_id_db(pool: &MySqlPool, course_id: i32) -> Result<Course, MyError> {
+    //the teacher of a course in the trash too, so the detail answers 410 for it
+    let teacher_id = sqlx::query_scalar!("SELECT teacher_id FROM course WHERE id = ?", course_id)
+        .fetch_optional(pool).await?
+        .ok_or_else(|| MyError::NotFound(Resource::Course, "Course ID not found".into()))?;
+
+    get_course_detail_db(pool, teacher_id, course_id).await
+}
+
+pub async fn get_course_slug_db(pool: &MySqlPool, course_id: i32) -> Result<Option<String>, MyError> {
+    let slug = sqlx::query_scalar!(
+        "SELECT slug FROM course_slug WHERE course_id = ? AND current",
+        course_id,
+    ).fetch_optional(pool).await?;
+
+    Ok(slug)
+}
+
+pub async fn find_course_slug_db(pool: &MySqlPool, slug: &str) -> Result<Option<SlugLookup>, MyError> {
+    let row = sqlx::query!(
+        "SELECT s.course_id, c.slug FROM course_slug s \
+        JOIN course_slug c ON c.course_id = s.course_id AND c.current WHERE s.slug = ?",
+        slug,
+    ).fetch_optional(pool).await?;
+
+    Ok(row.map(|row| SlugLookup::of(slug, row.course_id, row.slug)))
+}
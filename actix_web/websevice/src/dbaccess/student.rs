@@ -0,0 +1,76 @@
+use sqlx::MySqlPool;
+use crate::errors::{MyError, Resource};
+use crate::models::student::{CreateStudent, Student, UpdateStudent};
+
+pub async fn get_all_students_db(pool: &MySqlPool) -> Result<Vec<Student>, MyError> {
+    let rows = sqlx::query_as!(Student, "SELECT id, name, email, profile FROM student")
+        .fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn get_student_details_db(pool: &MySqlPool, student_id: i32) -> Result<Student, MyError> {
+    let row = sqlx::query_as!(
+        Student,
+        "SELECT id, name, email, profile FROM student WHERE id = ?",
+        student_id
+    )
+        .fetch_optional(pool)
+        .await?;
+
+    if let Some(student) = row {
+        Ok(student)
+    }else {
+        Err(MyError::NotFound(Resource::Student, "Student id not found".into()))
+    }
+}
+
+pub async fn post_new_student_db(pool: &MySqlPool, new_student: CreateStudent) -> Result<Student, MyError> {
+    //last_insert_id belongs to the connection, the read back has to use the same one
+    let mut tx = pool.begin().await?;
+    let post_row = sqlx::query!(
+        "INSERT INTO student (name, email, profile) VALUE (?, ?, ?)",
+        new_student.name,
+        new_student.email,
+        new_student.profile,
+    ).execute(&mut tx).await?;
+
+    let row = sqlx::query_as!(
+        Student,
+        "SELECT id, name, email, profile FROM student WHERE id = ?",
+        post_row.last_insert_id()
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(row)
+}
+
+pub async fn update_student_details_db(
+    pool: &MySqlPool, student_id: i32, update_student: UpdateStudent
+) -> Result<Student, MyError> {
+    let current = get_student_details_db(pool, student_id).await?;
+    let temp = update_student.apply_to(current);
+
+    let _update_row = sqlx::query!(
+        "UPDATE student SET name = ?, email = ?, profile = ? WHERE id = ?",
+        temp.name, temp.email, temp.profile, temp.id)
+        .execute(pool).await?;
+
+    Ok(temp)
+}
+
+pub async fn delete_student_db(pool: &MySqlPool, student_id: i32) -> Result<String, MyError> {
+    //a student's enrollments go with the student, both or neither
+    let mut tx = pool.begin().await?;
+    let _ = sqlx::query!("DELETE FROM enrollment WHERE student_id = ?", student_id)
+        .execute(&mut tx).await?;
+
+    let row = sqlx::query!("DELETE FROM student WHERE id = ?", student_id)
+        .execute(&mut tx).await?;
+    if row.rows_affected() == 0 {
+        return Err(MyError::NotFound(Resource::Student, "Student id not found".into()));
+    }
+    tx.commit().await?;
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
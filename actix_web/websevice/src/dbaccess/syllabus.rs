@@ -0,0 +1,234 @@
+use sqlx::MySqlPool;
+use crate::dbaccess::course::get_course_detail_db;
+use crate::errors::{MyError, Resource};
+use crate::models::syllabus::{
+    attachments_json, CourseModule, CreateLesson, CreateModule, Lesson, LessonRow, Reorder,
+    UpdateLesson, UpdateModule,
+};
+
+//the module, as long as it belongs to the teacher's course
+async fn get_module_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32
+) -> Result<CourseModule, MyError> {
+    let row = sqlx::query_as!(
+        CourseModule,
+        "SELECT m.id, m.course_id, m.title, m.summary, m.position FROM course_module m \
+        JOIN course c ON c.id = m.course_id \
+        WHERE c.teacher_id = ? and m.course_id = ? and m.id = ? and c.deleted_at IS NULL",
+        teacher_id,
+        course_id,
+        module_id,
+    ).fetch_optional(pool).await?;
+
+    row.ok_or_else(|| MyError::NotFound(Resource::Module, "Module not found".into()))
+}
+
+async fn get_lesson_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+) -> Result<Lesson, MyError> {
+    let module = get_module_db(pool, teacher_id, course_id, module_id).await?;
+
+    let row = sqlx::query_as!(
+        LessonRow,
+        "SELECT * FROM lesson WHERE module_id = ? and id = ?",
+        module.id,
+        lesson_id,
+    ).fetch_optional(pool).await?;
+
+    row.map(Lesson::from)
+        .ok_or_else(|| MyError::NotFound(Resource::Lesson, "Lesson not found".into()))
+}
+
+pub async fn get_modules_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<CourseModule>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        CourseModule,
+        "SELECT * FROM course_module WHERE course_id = ? ORDER BY position, id",
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+pub async fn post_new_module_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, new_module: CreateModule
+) -> Result<CourseModule, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let mut tx = pool.begin().await?;
+    //new modules go last
+    let post_row = sqlx::query!(
+        "INSERT INTO course_module (course_id, title, summary, position) \
+        SELECT ?, ?, ?, COALESCE(MAX(position), 0) + 1 FROM course_module WHERE course_id = ?",
+        course.id,
+        new_module.title,
+        new_module.summary,
+        course.id,
+    ).execute(&mut tx).await?;
+
+    let row = sqlx::query_as!(
+        CourseModule,
+        "SELECT * FROM course_module WHERE id = ?",
+        post_row.last_insert_id()
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(row)
+}
+
+pub async fn update_module_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, update_module: UpdateModule
+) -> Result<CourseModule, MyError> {
+    let current = get_module_db(pool, teacher_id, course_id, module_id).await?;
+    let module = update_module.apply_to(current);
+
+    sqlx::query!(
+        "UPDATE course_module SET title = ?, summary = ? WHERE id = ?",
+        module.title,
+        module.summary,
+        module.id,
+    ).execute(pool).await?;
+
+    Ok(module)
+}
+
+pub async fn delete_module_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32
+) -> Result<String, MyError> {
+    let module = get_module_db(pool, teacher_id, course_id, module_id).await?;
+
+    //its lessons go with it, ON DELETE CASCADE
+    let row = sqlx::query!("DELETE FROM course_module WHERE id = ?", module.id)
+        .execute(pool).await?;
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
+
+pub async fn reorder_modules_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, reorder: Reorder
+) -> Result<Vec<CourseModule>, MyError> {
+    let current = get_modules_db(pool, teacher_id, course_id).await?;
+    reorder.check(&current.iter().map(|m| m.id).collect::<Vec<_>>())?;
+
+    let mut tx = pool.begin().await?;
+    for (position, module_id) in reorder.ids.iter().enumerate() {
+        sqlx::query!(
+            "UPDATE course_module SET position = ? WHERE id = ?",
+            position as i32 + 1,
+            module_id,
+        ).execute(&mut tx).await?;
+    }
+    tx.commit().await?;
+
+    get_modules_db(pool, teacher_id, course_id).await
+}
+
+pub async fn get_lessons_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32
+) -> Result<Vec<Lesson>, MyError> {
+    let module = get_module_db(pool, teacher_id, course_id, module_id).await?;
+
+    let rows = sqlx::query_as!(
+        LessonRow,
+        "SELECT * FROM lesson WHERE module_id = ? ORDER BY position, id",
+        module.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(Lesson::from).collect())
+}
+
+pub async fn get_lessons_for_course_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Vec<Lesson>, MyError> {
+    let course = get_course_detail_db(pool, teacher_id, course_id).await?;
+
+    let rows = sqlx::query_as!(
+        LessonRow,
+        "SELECT l.id, l.module_id, l.title, l.body, l.duration_minutes, l.attachments, l.position \
+        FROM lesson l JOIN course_module m ON m.id = l.module_id \
+        WHERE m.course_id = ? ORDER BY m.position, m.id, l.position, l.id",
+        course.id
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(Lesson::from).collect())
+}
+
+pub async fn post_new_lesson_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, new_lesson: CreateLesson
+) -> Result<Lesson, MyError> {
+    let module = get_module_db(pool, teacher_id, course_id, module_id).await?;
+
+    let mut tx = pool.begin().await?;
+    //new lessons go last in their module
+    let post_row = sqlx::query!(
+        "INSERT INTO lesson (module_id, title, body, duration_minutes, attachments, position) \
+        SELECT ?, ?, ?, ?, ?, COALESCE(MAX(position), 0) + 1 FROM lesson WHERE module_id = ?",
+        module.id,
+        new_lesson.title,
+        new_lesson.body,
+        new_lesson.duration_minutes,
+        attachments_json(&new_lesson.attachments),
+        module.id,
+    ).execute(&mut tx).await?;
+
+    let row = sqlx::query_as!(
+        LessonRow,
+        "SELECT * FROM lesson WHERE id = ?",
+        post_row.last_insert_id()
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(row.into())
+}
+
+pub async fn update_lesson_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32,
+    update_lesson: UpdateLesson
+) -> Result<Lesson, MyError> {
+    let current = get_lesson_db(pool, teacher_id, course_id, module_id, lesson_id).await?;
+    let lesson = update_lesson.apply_to(current);
+
+    sqlx::query!(
+        "UPDATE lesson SET title = ?, body = ?, duration_minutes = ?, attachments = ? WHERE id = ?",
+        lesson.title,
+        lesson.body,
+        lesson.duration_minutes,
+        attachments_json(&lesson.attachments),
+        lesson.id,
+    ).execute(pool).await?;
+
+    Ok(lesson)
+}
+
+pub async fn delete_lesson_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+) -> Result<String, MyError> {
+    let lesson = get_lesson_db(pool, teacher_id, course_id, module_id, lesson_id).await?;
+
+    let row = sqlx::query!("DELETE FROM lesson WHERE id = ?", lesson.id)
+        .execute(pool).await?;
+
+    Ok(format!("Deleted {} record", row.rows_affected()))
+}
+
+pub async fn reorder_lessons_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, module_id: i32, reorder: Reorder
+) -> Result<Vec<Lesson>, MyError> {
+    let current = get_lessons_db(pool, teacher_id, course_id, module_id).await?;
+    reorder.check(&current.iter().map(|l| l.id).collect::<Vec<_>>())?;
+
+    let mut tx = pool.begin().await?;
+    for (position, lesson_id) in reorder.ids.iter().enumerate() {
+        sqlx::query!(
+            "UPDATE lesson SET position = ? WHERE id = ?",
+            position as i32 + 1,
+            lesson_id,
+        ).execute(&mut tx).await?;
+    }
+    tx.commit().await?;
+
+    get_lessons_db(pool, teacher_id, course_id, module_id).await
+}
@@ -1,65 +1,89 @@
 use sqlx::MySqlPool;
-use crate::errors::MyError;
-use crate::models::teacher::{CreateTeacher, Teacher, UpdateTeacher};
+use crate::errors::{MyError, Resource};
+use crate::dbaccess::{bind_args, bind_scalar_args, SqlArg};
+use crate::dbaccess::transfer::{get_live_courses_db, save_transfer_db, transferred_course_db};
+use crate::models::etag::IfMatch;
+use crate::models::pagination::contains_pattern;
+use crate::models::patch::MergePatch;
+use crate::models::teacher::{CreateTeacher, Teacher, TeacherQuery, UpdateTeacher};
+use crate::models::transfer::TeacherDeletion;
+use crate::models::trash::TrashItem;
+use chrono::Utc;
 
-pub async fn get_all_teachers_db(pool: &MySqlPool) ->Result<Vec<Teacher>, MyError> {
-    let rows = sqlx::query!("SELECT * FROM teacher")
-        .fetch_all(pool).await?;
-
-    let teachers: Vec<Teacher> = rows.iter().map(|r| Teacher {
-        id: r.id,
-        name: r.name.clone(),
-        picture_url: r.picture_url.clone(),
-        profile: r.profile.clone(),
-    }).collect();
+pub async fn get_all_teachers_db(
+    pool: &MySqlPool, query: &TeacherQuery
+) -> Result<(Vec<Teacher>, i64), MyError> {
+    let (page, per_page) = query.page_bounds();
+    let order_by = query.order_by()?;
 
-    match teachers.len() {
-        0 => Err(MyError::NotFound("No teachers found".into())),
-        _ => Ok(teachers),
+    let mut filters = String::from("WHERE deleted_at IS NULL");
+    let mut args = vec![];
+    if let Some(name) = &query.name {
+        filters.push_str(" AND name LIKE ? ESCAPE '!'");
+        args.push(SqlArg::Str(contains_pattern(name)));
     }
+
+    let count_sql = format!("SELECT COUNT(*) FROM teacher {}", filters);
+    let rows_sql = format!("SELECT * FROM teacher {} {} LIMIT ? OFFSET ?", filters, order_by);
+
+    let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+        .fetch_one(pool).await?;
+
+    let teachers = bind_args(sqlx::query_as::<_, Teacher>(&rows_sql), &args)
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .fetch_all(pool).await?;
+
+    Ok((teachers, total))
 }
 
 pub async fn get_teacher_details_db(pool: &MySqlPool, teacher_id: i32) -> Result<Teacher, MyError> {
     let row = sqlx::query!("SELECT * FROM teacher WHERE id = ?", teacher_id)
-        .fetch_one(pool)
-        .await
-        .map(|r| Teacher {
-            id: r.id,
-            name: r.name.clone(),
-            picture_url: r.picture_url.clone(),
-            profile: r.profile.clone(),
-        })
-        .map_err(|_| MyError::NotFound("Teacher id not found".into()))?;
-
-    Ok(row)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| MyError::NotFound(Resource::Teacher, "Teacher id not found".into()))?;
+    if row.deleted_at.is_some() {
+        return Err(MyError::Gone(Resource::Teacher, "Teacher was deleted".into()));
+    }
+
+    Ok(Teacher {
+        id: row.id,
+        name: row.name,
+        picture_url: row.picture_url,
+        profile: row.profile,
+        version: row.version,
+    })
 }
 
 pub async fn post_new_teacher_db(pool: &MySqlPool, new_teacher: CreateTeacher) -> Result<Teacher, MyError> {
+    let mut tx = pool.begin().await?;
     let post_row = sqlx::query!(
         "INSERT INTO teacher (name, picture_url, profile)\
         VALUE (?, ?, ?)",
         new_teacher.name,
         new_teacher.picture_url,
         new_teacher.profile,
-    ).execute(pool).await?;
+    ).execute(&mut tx).await?;
 
     let row = sqlx::query!(
         "SELECT * FROM teacher WHERE id = ?", post_row.last_insert_id()
-    ).fetch_one(pool).await?;
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
 
     Ok(Teacher{
         id: row.id,
         name: row.name,
         picture_url: row.picture_url,
         profile: row.profile,
+        version: row.version,
     })
 }
 
 pub async fn update_teacher_details_db(
-    pool: &MySqlPool, teacher_id: i32, update_teacher: UpdateTeacher
+    pool: &MySqlPool, teacher_id: i32, update_teacher: UpdateTeacher, if_match: &IfMatch
 ) -> Result<Teacher, MyError> {
-    let row = sqlx::query_as!(Teacher, "SELECT * FROM teacher WHERE id = ?", teacher_id)
-        .fetch_one(pool).await.map_err(|_|MyError::NotFound("Teacher id not found".into()))?;
+    let row = get_teacher_details_db(pool, teacher_id).await?;
+    if_match.check(row.version)?;
 
     let temp = Teacher {
         id: row.id,
@@ -78,27 +102,85 @@ pub async fn update_teacher_details_db(
         }else {
             row.profile.clone()
         },
+        version: row.version,
     };
 
-    let _update_row = sqlx::query!(
-        "UPDATE teacher SET name = ?, picture_url = ?, profile = ? WHERE id = ?",
-        temp.name, temp.picture_url, temp.profile, temp.id)
-        .execute(pool).await.map_err(|_| MyError::DBError("Update teacher failed".into()))?;
+    save_teacher_db(pool, temp).await
+}
+
+pub async fn patch_teacher_details_db(
+    pool: &MySqlPool, teacher_id: i32, patch: &MergePatch, if_match: &IfMatch
+) -> Result<Teacher, MyError> {
+    let current = get_teacher_details_db(pool, teacher_id).await?;
+    if_match.check(current.version)?;
 
-    let teacher_row = sqlx::query!("SELECT * FROM teacher where id = ?", row.id)
-        .fetch_one(pool).await.map_err(|_|MyError::NotFound("Updated teacher not found".into()))?;
+    save_teacher_db(pool, current.patched(patch)?).await
+}
 
-    Ok(Teacher{
-        id: teacher_row.id,
-        name: teacher_row.name.clone(),
-        picture_url: teacher_row.picture_url.clone(),
-        profile: teacher_row.profile.clone(),
-    })
+//only applies if nobody saved in between the read and this write
+async fn save_teacher_db(pool: &MySqlPool, teacher: Teacher) -> Result<Teacher, MyError> {
+    let update_row = sqlx::query!(
+        "UPDATE teacher SET name = ?, picture_url = ?, profile = ?, version = version + 1 \
+        WHERE id = ? AND version = ? AND deleted_at IS NULL",
+        teacher.name, teacher.picture_url, teacher.profile, teacher.id, teacher.version)
+        .execute(pool).await?;
+    if update_row.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The teacher has changed since it was read".into()));
+    }
+
+    Ok(Teacher { version: teacher.version + 1, ..teacher })
 }
 
-pub async fn delete_teacher_db(pool: &MySqlPool, teacher_id: i32) -> Result<String, MyError> {
-    let row = sqlx::query!("DELETE FROM teacher WHERE id = ?", teacher_id)
-        .execute(pool).await.map_err(|_|MyError::DBError("Unable to delete teacher".into()))?;
+//moves the teacher to the trash, the row stays until purge_trash_db
+pub async fn delete_teacher_db(
+    pool: &MySqlPool, teacher_id: i32, deletion: TeacherDeletion, if_match: &IfMatch
+) -> Result<TrashItem, MyError> {
+    let current = get_teacher_details_db(pool, teacher_id).await?;
+    if_match.check(current.version)?;
+
+    //checked before anything is written, the checks and writes below go in one transaction
+    let courses = get_live_courses_db(pool, teacher_id).await?;
+    let mut tx = pool.begin().await?;
+    let mut transfers = vec![];
+    match deletion {
+        TeacherDeletion::Refuse if !courses.is_empty() => {
+            return Err(MyError::Conflict("Unable to delete teacher, it still has courses".into()));
+        }
+        TeacherDeletion::Reassign(to_teacher_id) => {
+            for course in courses {
+                transfers.push(transferred_course_db(pool, &mut tx, course, to_teacher_id).await?);
+            }
+        }
+        TeacherDeletion::Refuse | TeacherDeletion::Cascade => {}
+    }
+
+    let deleted_at = Utc::now();
+    for course in &transfers {
+        save_transfer_db(&mut tx, teacher_id, course).await?;
+    }
+    if deletion == TeacherDeletion::Cascade {
+        sqlx::query!(
+            "UPDATE course SET deleted_at = ?, version = version + 1 WHERE teacher_id = ? AND deleted_at IS NULL",
+            deleted_at, teacher_id
+        ).execute(&mut tx).await?;
+    }
+    //a course added in the meantime keeps the teacher
+    let remaining = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM course WHERE teacher_id = ? AND deleted_at IS NULL", teacher_id
+    ).fetch_one(&mut tx).await?;
+    if remaining > 0 {
+        return Err(MyError::Conflict("Unable to delete teacher, it still has courses".into()));
+    }
+
+    let row = sqlx::query!(
+        "UPDATE teacher SET deleted_at = ?, version = version + 1 \
+        WHERE id = ? AND version = ? AND deleted_at IS NULL",
+        deleted_at, teacher_id, current.version
+    ).execute(&mut tx).await?;
+    if row.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The teacher has changed since it was read".into()));
+    }
+    tx.commit().await?;
 
-    Ok(format!("Delete {:?} record", row))
-}
\ No newline at end of file
+    Ok(TrashItem::teacher(&Teacher { version: current.version + 1, ..current }, deleted_at))
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlPool, Transaction};
+use crate::dbaccess::course::{check_timetable_db, lock_course_detail_db};
+use crate::dbaccess::teacher::get_teacher_details_db;
+use crate::errors::MyError;
+use crate::models::course::{Course, CourseRow};
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Language};
+use crate::models::timetable::Booking;
+
+//the teacher's courses outside the trash
+pub async fn get_live_courses_db(pool: &MySqlPool, teacher_id: i32) -> Result<Vec<Course>, MyError> {
+    let rows = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE teacher_id = ? AND deleted_at IS NULL
+        ORDER BY id"#,
+        teacher_id,
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(Course::from).collect())
+}
+
+//the course as it will be once moved, the new teacher has to be free at the course's time
+pub async fn transferred_course_db(
+    pool: &MySqlPool, tx: &mut Transaction<'_, MySql>, course: Course, to_teacher_id: i32
+) -> Result<Course, MyError> {
+    match get_teacher_details_db(pool, to_teacher_id).await {
+        Ok(_) => {}
+        Err(MyError::NotFound(..)) | Err(MyError::Gone(..)) => {
+            return Err(MyError::InvalidReference("The teacher to transfer to does not exist".into()))
+        }
+        Err(err) => return Err(err),
+    }
+
+    let course = Course { teacher_id: to_teacher_id, version: course.version + 1, ..course };
+    if let Some(booking) = Booking::of(&course)? {
+        check_timetable_db(tx, &booking).await?;
+    }
+    Ok(course)
+}
+
+//saves a course from transferred_course_db unless it changed since it was read
+pub async fn save_transfer_db(
+    tx: &mut Transaction<'_, MySql>, from_teacher_id: i32, course: &Course
+) -> Result<(), MyError> {
+    let row = sqlx::query!(
+        "UPDATE course SET teacher_id = ?, version = version + 1 \
+        WHERE id = ? AND teacher_id = ? AND version = ? AND deleted_at IS NULL",
+        course.teacher_id,
+        course.id,
+        from_teacher_id,
+        course.version - 1,
+    ).execute(&mut *tx).await?;
+    if row.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The course has changed since it was read".into()));
+    }
+
+    sqlx::query!(
+        "INSERT INTO course_transfer (course_id, from_teacher_id, to_teacher_id) VALUES (?, ?, ?)",
+        course.id,
+        from_teacher_id,
+        course.teacher_id,
+    ).execute(&mut *tx).await?;
+    //a co-teacher who gets the course leads it from now on
+    sqlx::query!(
+        "DELETE FROM course_instructor WHERE course_id = ? AND teacher_id = ?",
+        course.id,
+        course.teacher_id,
+    ).execute(&mut *tx).await?;
+
+    Ok(())
+}
+
+pub async fn transfer_course_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32, to_teacher_id: i32
+) -> Result<Course, MyError> {
+    let mut tx = pool.begin().await?;
+    let current = lock_course_detail_db(&mut tx, teacher_id, course_id).await?;
+    let course = transferred_course_db(pool, &mut tx, current, to_teacher_id).await?;
+    save_transfer_db(&mut tx, teacher_id, &course).await?;
+    tx.commit().await?;
+
+    Ok(course)
+}
+
+pub async fn find_transferred_course_db(
+    pool: &MySqlPool, teacher_id: i32, course_id: i32
+) -> Result<Option<i32>, MyError> {
+    let current = sqlx::query_scalar!(
+        "SELECT c.teacher_id FROM course_transfer t JOIN course c ON c.id = t.course_id \
+        WHERE t.course_id = ? AND t.from_teacher_id = ? AND c.teacher_id <> ? LIMIT 1",
+        course_id,
+        teacher_id,
+        teacher_id,
+    ).fetch_optional(pool).await?;
+
+    Ok(current)
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use sqlx::MySqlPool;
+use crate::dbaccess::course::{check_timetable_db, get_course_detail_db};
+use crate::dbaccess::teacher::get_teacher_details_db;
+use crate::errors::MyError;
+use crate::models::course::{Course, CourseRow};
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Language};
+use crate::models::teacher::Teacher;
+use crate::models::timetable::Booking;
+use crate::models::trash::{TrashItem, TrashRow};
+
+pub async fn get_trash_db(pool: &MySqlPool) -> Result<Vec<TrashItem>, MyError> {
+    let rows = sqlx::query_as::<_, TrashRow>(
+        "SELECT id, NULL AS teacher_id, name, deleted_at FROM teacher WHERE deleted_at IS NOT NULL \
+        UNION ALL \
+        SELECT id, teacher_id, name, deleted_at FROM course WHERE deleted_at IS NOT NULL \
+        ORDER BY deleted_at DESC, id DESC"
+    ).fetch_all(pool).await?;
+
+    Ok(rows.into_iter().map(TrashItem::from).collect())
+}
+
+pub async fn restore_teacher_db(pool: &MySqlPool, teacher_id: i32) -> Result<Teacher, MyError> {
+    let row = sqlx::query!(
+        "UPDATE teacher SET deleted_at = NULL, version = version + 1 WHERE id = ? AND deleted_at IS NOT NULL",
+        teacher_id
+    ).execute(pool).await?;
+    if row.rows_affected() == 0 {
+        //404 when it does not exist at all
+        get_teacher_details_db(pool, teacher_id).await?;
+        return Err(MyError::Conflict("Teacher is not deleted".into()));
+    }
+
+    get_teacher_details_db(pool, teacher_id).await
+}
+
+pub async fn restore_course_db(pool: &MySqlPool, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+    //the row stays locked from the check to the update
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course
+        WHERE teacher_id = ? and id = ? and deleted_at IS NOT NULL
+        FOR UPDATE"#,
+        teacher_id,
+        course_id,
+    ).fetch_optional(&mut tx).await?;
+    let course: Course = match row {
+        Some(row) => row.into(),
+        None => {
+            //404 when it does not exist at all
+            get_course_detail_db(pool, teacher_id, course_id).await?;
+            return Err(MyError::Conflict("Course is not deleted".into()));
+        }
+    };
+
+    match get_teacher_details_db(pool, teacher_id).await {
+        Ok(_) => {}
+        Err(MyError::Gone(..)) => {
+            return Err(MyError::Conflict("The course's teacher is deleted, restore the teacher first".into()))
+        }
+        Err(err) => return Err(err),
+    }
+    //the slot may have been taken while the course was in the trash
+    if let Some(booking) = Booking::of(&course)? {
+        check_timetable_db(&mut tx, &booking).await?;
+    }
+
+    let row = sqlx::query!(
+        "UPDATE course SET deleted_at = NULL, version = version + 1 \
+        WHERE id = ? and version = ? and deleted_at IS NOT NULL",
+        course.id,
+        course.version,
+    ).execute(&mut tx).await?;
+    if row.rows_affected() == 0 {
+        return Err(MyError::PreconditionFailed("The course has changed since it was read".into()));
+    }
+
+    let row = sqlx::query_as!(
+        CourseRow,
+        r#"SELECT teacher_id, id, name, time, starts_at as "starts_at: DateTime<Utc>",
+        ends_at as "ends_at: DateTime<Utc>", room, capacity, status as "status: CourseStatus",
+        published_at as "published_at: DateTime<Utc>", version, description, format as "format: CourseFormat",
+        structure, duration, price, currency as "currency: Currency",
+        language as "language: Language", level as "level: CourseLevel"
+        FROM course WHERE id = ?"#,
+        course.id,
+    ).fetch_one(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(row.into())
+}
+
+//courses go first so the teachers they kept in the trash can go in the same run
+pub async fn purge_trash_db(pool: &MySqlPool, before: DateTime<Utc>) -> Result<u64, MyError> {
+    let mut tx = pool.begin().await?;
+    let courses = sqlx::query!("DELETE FROM course WHERE deleted_at < ?", before)
+        .execute(&mut tx).await?;
+    let teachers = sqlx::query!(
+        "DELETE FROM teacher WHERE deleted_at < ? \
+        AND NOT EXISTS (SELECT 1 FROM course c WHERE c.teacher_id = teacher.id)",
+        before
+    ).execute(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(courses.rows_affected() + teachers.rows_affected())
+}
@@ -0,0 +1,59 @@
+use sqlx::MySqlPool;
+use crate::errors::{MyError, Resource};
+use crate::models::user::{CreateUser, Role, User};
+
+pub async fn get_user_by_username_db(pool: &MySqlPool, username: &str) -> Result<User, MyError> {
+    let row = sqlx::query!(
+        "SELECT id, username, password_hash, role, teacher_id, student_id FROM app_user WHERE username = ?",
+        username
+    ).fetch_optional(pool).await?;
+
+    let row = row.ok_or_else(|| MyError::NotFound(Resource::User, "User not found".into()))?;
+
+    Ok(User {
+        id: row.id,
+        username: row.username,
+        password_hash: row.password_hash,
+        role: row.role.parse().map_err(MyError::DBError)?,
+        teacher_id: row.teacher_id,
+        student_id: row.student_id,
+    })
+}
+
+//`password_hash` is already hashed by the caller, never store the plain password
+pub async fn post_new_user_db(
+    pool: &MySqlPool, new_user: CreateUser, password_hash: String
+) -> Result<User, MyError> {
+    let existing = sqlx::query!("SELECT id FROM app_user WHERE username = ?", new_user.username)
+        .fetch_optional(pool).await?;
+    if existing.is_some() {
+        return Err(MyError::Conflict("Username already taken".into()));
+    }
+
+    let post_row = sqlx::query!(
+        "INSERT INTO app_user (username, password_hash, role, teacher_id, student_id) VALUE (?, ?, ?, ?, ?)",
+        new_user.username,
+        password_hash,
+        new_user.role.as_str(),
+        new_user.teacher_id,
+        new_user.student_id,
+    ).execute(pool).await?;
+
+    Ok(User {
+        id: post_row.last_insert_id() as i32,
+        username: new_user.username,
+        password_hash,
+        role: new_user.role,
+        teacher_id: new_user.teacher_id,
+        student_id: new_user.student_id,
+    })
+}
+
+pub async fn count_admins_db(pool: &MySqlPool) -> Result<i64, MyError> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM app_user WHERE role = ?",
+        Role::Admin.as_str()
+    ).fetch_one(pool).await?;
+
+    Ok(count)
+}
@@ -1,6 +1,9 @@
 use actix_web::{error, Error, http::StatusCode, HttpResponse};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
-use sqlx::error::Error as SQLxError;
+use sqlx::error::{DatabaseError, Error as SQLxError};
+use sqlx::migrate::MigrateError;
+use utoipa::Component;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use actix_web::body::BoxBody;
@@ -9,58 +12,252 @@ use actix_web::body::BoxBody;
 pub enum MyError {
     DBError(String),
     ActixError(String),
-    NotFound(String),
-    InvalidInput(String)
+    NotFound(Resource, String),
+    //a record that was deleted, it stays in the trash until purged
+    Gone(Resource, String),
+    //a body, query string or path that cannot be parsed at all
+    InvalidInput(String),
+    Unauthorized(String),
+    Forbidden(String),
+    //duplicate value, or a record that is still referenced
+    Conflict(String),
+    //a referenced record does not exist
+    InvalidReference(String),
+    //well formed input that breaks the rules of one or more fields
+    Validation(Vec<FieldError>),
+    //a course booked over other courses of the same teacher or room
+    ScheduleConflict(Vec<ScheduleConflict>),
+    //If-Match names a version that is no longer the stored one
+    PreconditionFailed(String),
+    //a write that has to say which version it was made against
+    PreconditionRequired(String),
 }
 
-#[derive(Debug, Serialize)]
+//what a NotFound was looking for, gives each its own error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Resource {
+    Teacher,
+    Course,
+    Student,
+    Enrollment,
+    User,
+    Module,
+    Lesson,
+    Session,
+    Reservation,
+    Revision,
+    Instructor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Component)]
+pub struct FieldError {
+    #[component(example = "sort")]
+    pub field: String,
+    #[component(example = "Unknown sort 'rating', expected name, time or price")]
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+//a course in the way of the one being saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Component)]
+pub struct ScheduleConflict {
+    pub course_id: i32,
+    pub teacher_id: i32,
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub room: Option<String>,
+    pub reason: ConflictReason,
+}
+
+//what the two courses share
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    Teacher,
+    Room,
+}
+
+//body of every error response, an RFC 7807 application/problem+json document
+#[derive(Debug, Serialize, Component)]
 pub struct MyErrorResponse {
-    error_msg: String,
+    #[serde(rename = "type")]
+    #[component(example = "about:blank")]
+    problem_type: String,
+    #[component(example = "Not Found")]
+    title: String,
+    #[component(example = 404)]
+    status: u16,
+    #[component(example = "Course ID not found")]
+    detail: String,
+    //stable, machine readable, clients should branch on this rather than detail
+    #[component(example = "course_not_found")]
+    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[component(example = "0b4a3c38-4a0b-4d5e-9f0c-3f8d2a1c6e77")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<ScheduleConflict>,
 }
 
 impl MyError {
-    fn error_response(&self) -> String {
+    pub fn code(&self) -> &'static str {
         match self {
-            MyError::DBError(msg) => {
-                println!("Database error occurred: {:?}", msg);
-                "Database error".into()
-            },
-            MyError::ActixError(msg) => {
-                println!("Server error occurred: {:?}", msg);
-                "Internal server error".into()
-            },
-            MyError::NotFound(msg) => {
-                println!("Not found error occurred: {:?}", msg);
-                msg.into()
+            MyError::DBError(_) => "database_error",
+            MyError::ActixError(_) => "internal_error",
+            MyError::NotFound(Resource::Teacher, _) => "teacher_not_found",
+            MyError::NotFound(Resource::Course, _) => "course_not_found",
+            MyError::NotFound(Resource::Student, _) => "student_not_found",
+            MyError::NotFound(Resource::Enrollment, _) => "enrollment_not_found",
+            MyError::NotFound(Resource::User, _) => "user_not_found",
+            MyError::NotFound(Resource::Module, _) => "module_not_found",
+            MyError::NotFound(Resource::Lesson, _) => "lesson_not_found",
+            MyError::NotFound(Resource::Session, _) => "session_not_found",
+            MyError::NotFound(Resource::Reservation, _) => "reservation_not_found",
+            MyError::NotFound(Resource::Revision, _) => "revision_not_found",
+            MyError::NotFound(Resource::Instructor, _) => "instructor_not_found",
+            MyError::Gone(Resource::Teacher, _) => "teacher_deleted",
+            MyError::Gone(Resource::Course, _) => "course_deleted",
+            MyError::Gone(..) => "deleted",
+            MyError::InvalidInput(_) => "invalid_input",
+            MyError::Unauthorized(_) => "unauthorized",
+            MyError::Forbidden(_) => "forbidden",
+            MyError::Conflict(_) => "conflict",
+            MyError::InvalidReference(_) => "invalid_reference",
+            MyError::Validation(_) => "validation_failed",
+            MyError::ScheduleConflict(_) => "schedule_conflict",
+            MyError::PreconditionFailed(_) => "precondition_failed",
+            MyError::PreconditionRequired(_) => "precondition_required",
+        }
+    }
+
+    //the message shown to the client, internal details only go to the log
+    fn detail(&self) -> String {
+        match self {
+            MyError::DBError(_) => "Database error".into(),
+            MyError::ActixError(_) => "Internal server error".into(),
+            MyError::Validation(_) => "Some fields are invalid".into(),
+            MyError::ScheduleConflict(_) => "The course overlaps other courses of the teacher or room".into(),
+            MyError::NotFound(_, msg)
+            | MyError::Gone(_, msg)
+            | MyError::InvalidInput(msg)
+            | MyError::Unauthorized(msg)
+            | MyError::Forbidden(msg)
+            | MyError::Conflict(msg)
+            | MyError::InvalidReference(msg)
+            | MyError::PreconditionFailed(msg)
+            | MyError::PreconditionRequired(msg) => msg.into(),
+        }
+    }
+
+    fn log(&self) {
+        match self {
+            MyError::DBError(msg) => println!("Database error occurred: {:?}", msg),
+            MyError::ActixError(msg) => println!("Server error occurred: {:?}", msg),
+            MyError::NotFound(_, msg) => println!("Not found error occurred: {:?}", msg),
+            MyError::Gone(_, msg) => println!("Deleted record requested: {:?}", msg),
+            MyError::InvalidInput(msg) => println!("Invalid input occurred: {:?}", msg),
+            MyError::Unauthorized(msg) => println!("Unauthorized request: {:?}", msg),
+            MyError::Forbidden(msg) => println!("Forbidden request: {:?}", msg),
+            MyError::Conflict(msg) => println!("Conflict occurred: {:?}", msg),
+            MyError::InvalidReference(msg) => println!("Invalid reference occurred: {:?}", msg),
+            MyError::Validation(errors) => println!("Validation failed: {:?}", errors),
+            MyError::ScheduleConflict(conflicts) => println!("Schedule conflict occurred: {:?}", conflicts),
+            MyError::PreconditionFailed(msg) => println!("Precondition failed: {:?}", msg),
+            MyError::PreconditionRequired(msg) => println!("Precondition required: {:?}", msg),
+        }
+    }
+
+    pub fn problem(&self, request_id: Option<String>) -> MyErrorResponse {
+        let status = error::ResponseError::status_code(self);
+        MyErrorResponse {
+            problem_type: "about:blank".into(),
+            title: status.canonical_reason().unwrap_or_default().into(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code().into(),
+            request_id,
+            errors: match self {
+                MyError::Validation(errors) => errors.clone(),
+                _ => Vec::new(),
             },
-            MyError::InvalidInput(msg) => {
-                println!("Invalid input occurred: {:?}", msg);
-                msg.into()
+            conflicts: match self {
+                MyError::ScheduleConflict(conflicts) => conflicts.clone(),
+                _ => Vec::new(),
             },
         }
     }
+
+    pub fn problem_response(&self, request_id: Option<String>) -> HttpResponse<BoxBody> {
+        HttpResponse::build(error::ResponseError::status_code(self))
+            .content_type("application/problem+json")
+            .json(self.problem(request_id))
+    }
 }
 
 impl error::ResponseError for MyError {
     fn status_code(&self) -> StatusCode {
         match self {
             MyError::DBError(_) | MyError::ActixError(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            MyError::NotFound(_) => StatusCode::NOT_FOUND,
+            MyError::NotFound(..) => StatusCode::NOT_FOUND,
+            MyError::Gone(..) => StatusCode::GONE,
             MyError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            MyError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            MyError::Forbidden(_) => StatusCode::FORBIDDEN,
+            MyError::Conflict(_) | MyError::ScheduleConflict(_) => StatusCode::CONFLICT,
+            MyError::InvalidReference(_) | MyError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            MyError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            MyError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
         }
     }
 
+    //the request id is filled in by request_id::assign, which renders the body again
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        HttpResponse::build(self.status_code()).json(MyErrorResponse {
-            error_msg: self.error_response()
-        })
+        self.log();
+        self.problem_response(None)
     }
 }
 
 
 impl Display for MyError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self)
+        match self {
+            MyError::DBError(msg)
+            | MyError::ActixError(msg)
+            | MyError::NotFound(_, msg)
+            | MyError::Gone(_, msg)
+            | MyError::InvalidInput(msg)
+            | MyError::Unauthorized(msg)
+            | MyError::Forbidden(msg)
+            | MyError::Conflict(msg)
+            | MyError::InvalidReference(msg)
+            | MyError::PreconditionFailed(msg)
+            | MyError::PreconditionRequired(msg) => write!(f, "{}: {}", self.code(), msg),
+            MyError::Validation(errors) => {
+                write!(f, "{}:", self.code())?;
+                for e in errors {
+                    write!(f, " {}: {};", e.field, e.message)?;
+                }
+                Ok(())
+            }
+            MyError::ScheduleConflict(conflicts) => {
+                write!(f, "{}:", self.code())?;
+                for c in conflicts {
+                    write!(f, " course {} ({:?});", c.course_id, c.reason)?;
+                }
+                Ok(())
+            }
+        }
     }
 }
 
@@ -71,9 +268,141 @@ impl From<Error> for MyError {
     }
 }
 
-//convert sqlx error to MyError
+//convert sqlx error to MyError, constraint violations get their own variants
 impl From<SQLxError> for MyError {
     fn from(err: SQLxError) -> Self {
+        if let SQLxError::Database(db_err) = &err {
+            match constraint_violation(db_err.as_ref()) {
+                Some(Violation::Unique) => {
+                    return MyError::Conflict("A record with the same value already exists".into())
+                }
+                Some(Violation::StillReferenced) => {
+                    return MyError::Conflict("The record is still referenced by other records".into())
+                }
+                Some(Violation::MissingReference) => {
+                    return MyError::InvalidReference("A referenced record does not exist".into())
+                }
+                None => {}
+            }
+        }
         MyError::DBError(err.to_string())
     }
-}
\ No newline at end of file
+}
+
+//convert sqlx migration error to MyError
+impl From<MigrateError> for MyError {
+    fn from(err: MigrateError) -> Self {
+        MyError::DBError(err.to_string())
+    }
+}
+
+enum Violation {
+    Unique,
+    //deleting a row other rows point at, sqlite cannot report it
+    #[cfg_attr(not(any(feature = "mysql", feature = "postgres")), allow(dead_code))]
+    StillReferenced,
+    //inserting or updating a row that points at nothing
+    MissingReference,
+}
+
+fn constraint_violation(db_err: &dyn DatabaseError) -> Option<Violation> {
+    match db_err.code().as_deref() {
+        //postgres sqlstate
+        Some("23505") => Some(Violation::Unique),
+        Some("23503") => postgres_violation(db_err),
+        //sqlite extended result codes, it does not say which side of the foreign key failed
+        Some("2067") | Some("1555") => Some(Violation::Unique),
+        Some("787") => Some(Violation::MissingReference),
+        //mysql reports 23000 for both kinds, the error number tells them apart
+        Some("23000") => mysql_violation(db_err),
+        _ => None,
+    }
+}
+
+//the message only names the table written to, the detail says which side of the key is missing:
+//"Key (id)=(1) is still referenced from table ..." or "Key (teacher_id)=(9) is not present in table ..."
+#[cfg(feature = "postgres")]
+fn postgres_violation(db_err: &dyn DatabaseError) -> Option<Violation> {
+    use sqlx::postgres::PgDatabaseError;
+
+    match db_err.try_downcast_ref::<PgDatabaseError>()?.detail() {
+        Some(detail) if detail.contains("is still referenced") => Some(Violation::StillReferenced),
+        _ => Some(Violation::MissingReference),
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+fn postgres_violation(_db_err: &dyn DatabaseError) -> Option<Violation> {
+    None
+}
+
+#[cfg(feature = "mysql")]
+fn mysql_violation(db_err: &dyn DatabaseError) -> Option<Violation> {
+    use sqlx::mysql::MySqlDatabaseError;
+
+    match db_err.try_downcast_ref::<MySqlDatabaseError>()?.number() {
+        1062 => Some(Violation::Unique),
+        1451 => Some(Violation::StillReferenced),
+        1452 => Some(Violation::MissingReference),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "mysql"))]
+fn mysql_violation(_db_err: &dyn DatabaseError) -> Option<Violation> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn not_found_is_a_problem_document() {
+        let err = MyError::NotFound(Resource::Teacher, "Teacher id not found".into());
+        let resp = err.problem_response(Some("req-1".into()));
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "teacher_not_found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[test]
+    fn validation_lists_the_fields() {
+        let err = MyError::Validation(vec![FieldError::new("sort", "Unknown sort")]);
+        let problem = err.problem(None);
+
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.errors, vec![FieldError::new("sort", "Unknown sort")]);
+        assert_eq!(err.to_string(), "validation_failed: sort: Unknown sort;");
+    }
+
+    #[cfg(feature = "postgres")]
+    #[actix_rt::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn postgres_foreign_key_errors_tell_the_two_sides_apart() {
+        use sqlx::{Connection, PgConnection};
+
+        let db_url = std::env::var("TEST_DATABASE_URL").unwrap();
+        if !db_url.starts_with("postgres") {
+            return;
+        }
+        let mut conn = PgConnection::connect(&db_url).await.unwrap();
+        sqlx::query("CREATE TEMP TABLE fk_parent (id INT PRIMARY KEY)").execute(&mut conn).await.unwrap();
+        sqlx::query("CREATE TEMP TABLE fk_child (parent_id INT REFERENCES fk_parent (id))")
+            .execute(&mut conn).await.unwrap();
+
+        let err = sqlx::query("INSERT INTO fk_child VALUES (1)").execute(&mut conn).await.unwrap_err();
+        assert!(matches!(MyError::from(err), MyError::InvalidReference(_)));
+
+        sqlx::query("INSERT INTO fk_parent VALUES (1)").execute(&mut conn).await.unwrap();
+        sqlx::query("INSERT INTO fk_child VALUES (1)").execute(&mut conn).await.unwrap();
+        let err = sqlx::query("DELETE FROM fk_parent").execute(&mut conn).await.unwrap_err();
+        assert!(matches!(MyError::from(err), MyError::Conflict(_)));
+    }
+}
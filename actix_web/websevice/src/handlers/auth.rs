@@ -0,0 +1,64 @@
+use actix_web::{HttpResponse, web};
+use crate::auth::{hash_password, issue_token, verify_password, AuthUser, TOKEN_LIFETIME};
+use crate::errors::MyError;
+use crate::models::user::{CreateUser, LoginRequest, TokenResponse};
+use crate::state::AppState;
+
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Access token for the user", body = TokenResponse),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn login(
+    app_state: web::Data<AppState>, credentials: web::Json<LoginRequest>
+) -> Result<HttpResponse, MyError> {
+    //same answer for unknown user and wrong password
+    let user = app_state.db.get_user_by_username(&credentials.username)
+        .await
+        .map_err(|_| MyError::Unauthorized("Invalid username or password".into()))?;
+
+    if !verify_password(&credentials.password, &user.password_hash) {
+        return Err(MyError::Unauthorized("Invalid username or password".into()));
+    }
+
+    let token = issue_token(&app_state.jwt_secret, &user)?;
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        access_token: token,
+        token_type: "Bearer".into(),
+        expires_in: TOKEN_LIFETIME,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/users",
+    tag = "auth",
+    request_body = CreateUser,
+    responses(
+        (status = 200, description = "The created user", body = User),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 409, description = "Duplicate or still referenced record", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_user(
+    app_state: web::Data<AppState>, user: AuthUser, new_user: web::Json<CreateUser>
+) -> Result<HttpResponse, MyError> {
+    user.require_admin()?;
+
+    let new_user = CreateUser::from(new_user);
+    let password_hash = hash_password(&new_user.password)?;
+    app_state.db.post_new_user(new_user, password_hash)
+        .await
+        .map(|user| HttpResponse::Ok().json(user))
+}
@@ -1,174 +1,680 @@
+use crate::auth::AuthUser;
 use crate::state::AppState;
-use crate::dbaccess::course::*;
-use crate::errors::MyError;
-use crate::models::course::{CreateCourse, UpdateCourse};
-use actix_web::{web, HttpResponse};
-
+use crate::errors::{FieldError, MyError, Resource};
+use crate::models::course::{Course, CourseDetailQuery, CourseQuery, CreateCourse, UpdateCourse};
+use crate::models::etag::{etag, tagged, IfMatch};
+use crate::models::instructor::{CourseInstructor, InstructorRole};
+use crate::models::meta::Currency;
+use crate::models::pagination::Page;
+use crate::models::patch::MergePatch;
+use crate::models::search::{CourseSearchQuery, CourseSearchResult};
+use crate::models::slug::SlugLookup;
+use crate::models::syllabus::{outline, CourseDetail};
+use crate::models::timetable::interval_errors;
+use crate::models::validation::validated;
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde_json::Value;
+//the path macros call into_params on every web::Query argument
+use utoipa::IntoParams;
+
+//instructors who may change a course, deleting it is left to the lead
+const EDITORS: [InstructorRole; 2] = [InstructorRole::Lead, InstructorRole::CoTeacher];
+//instructors who see the course before it is published
+const ANY_INSTRUCTOR: [InstructorRole; 3] = [InstructorRole::Lead, InstructorRole::CoTeacher, InstructorRole::Ta];
+
+#[utoipa::path(
+    post,
+    path = "/courses/",
+    tag = "course",
+    request_body = CreateCourse,
+    responses(
+        (status = 200, description = "The created course", body = Course),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 409, description = "Overlaps other courses of the teacher or room", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules or an unknown teacher_id, each listed in errors", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn post_new_course(
     new_course: web::Json<CreateCourse>,
     app_state: web::Data<AppState>,
+    user: AuthUser,
 ) -> Result<HttpResponse, MyError> {
     println!("Received new course");
+    user.require_teacher(new_course.teacher_id)?;
+
+    //an unknown teacher is reported together with the other invalid fields
+    let missing_teacher = match app_state.db.get_teacher_details(new_course.teacher_id).await {
+        Ok(_) => None,
+        Err(MyError::NotFound(..)) | Err(MyError::Gone(..)) => {
+            Some(FieldError::new("teacher_id", "Teacher does not exist"))
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut extra = interval_errors(new_course.starts_at, new_course.ends_at);
+    extra.extend(missing_teacher);
 
-    post_new_course_db(&app_state.db, new_course.try_into()?)
+    app_state.db.post_new_course(validated(new_course.into_inner(), extra)?, user.user_id)
         .await
         .map(|course| HttpResponse::Ok().json(course))
 }
 
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("page" = Option<u32>, query, description = "Page number, starting at 1"),
+        ("per_page" = Option<u32>, query, description = "Items per page, 20 by default and 100 at most"),
+        ("sort" = Option<String>, query, description = "name, time or price"),
+        ("order" = Option<String>, query, description = "asc or desc"),
+        ("language" = Option<String>, query, description = "Only courses in this language"),
+        ("level" = Option<String>, query, description = "Only courses of this level"),
+        ("min_price" = Option<i64>, query, description = "Lowest price, in minor units"),
+        ("max_price" = Option<i64>, query, description = "Highest price, in minor units"),
+        ("currency" = Option<String>, query, description = "Only courses priced in this currency"),
+        ("display_currency" = Option<String>, query, description = "Add each price converted to this currency"),
+        ("status" = Option<String>, query, description = "Only courses in this status, for the teacher's own listing"),
+    ),
+    responses(
+        (status = 200, description = "Page of the teacher's courses, only published ones unless asked by the teacher", body = CoursePage),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
 pub async fn get_courses_for_teacher(
     app_state: web::Data<AppState>,
     path: web::Path<i32>,
+    query: web::Query<CourseQuery>,
+    req: HttpRequest,
+    user: Option<AuthUser>,
 ) -> Result<HttpResponse,MyError> {
     println!("Searching courses...");
 
     let teacher_id = path.into_inner();
-    get_course_for_teacher_db(&app_state.db, teacher_id)
-            .await.map(|courses|HttpResponse::Ok().json(courses))
+    let mut query = query.into_inner();
+    //teachers see their drafts, everyone else only what is published
+    query.include_unpublished = is_owner(&user, teacher_id);
+    let (page, per_page) = query.page_bounds();
+    app_state.db.get_course_for_teacher(teacher_id, &query)
+            .await.map(|(courses, total)|HttpResponse::Ok().json(Page::new(
+                in_currency(courses, &app_state, query.display_currency),
+                total, page, per_page, |p| query.link(req.path(), p)
+            )))
 }
 
+#[utoipa::path(
+    get,
+    path = "/courses/search",
+    tag = "course",
+    params(
+        ("q" = String, query, description = "Search terms"),
+        ("page" = Option<u32>, query, description = "Page number, starting at 1"),
+        ("per_page" = Option<u32>, query, description = "Items per page, 20 by default and 100 at most"),
+        ("language" = Option<String>, query, description = "Only courses in this language"),
+        ("level" = Option<String>, query, description = "Only courses of this level"),
+        ("format" = Option<String>, query, description = "Only courses of this format"),
+        ("display_currency" = Option<String>, query, description = "Add each price converted to this currency"),
+    ),
+    responses(
+        (status = 200, description = "Matching courses, best match first, with facet counts", body = CourseSearchPage),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn search_courses(
+    app_state: web::Data<AppState>,
+    query: web::Query<CourseSearchQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, MyError> {
+    let query = query.into_inner();
+    let (page, per_page) = query.page_bounds();
+    app_state.db.search_courses(&query)
+        .await
+        .map(|(courses, total, facets)| HttpResponse::Ok().json(CourseSearchResult {
+            page: Page::new(
+                in_currency(courses, &app_state, query.display_currency),
+                total, page, per_page, |p| query.link(req.path(), p)
+            ),
+            facets,
+        }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead or of another of its instructors"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("display_currency" = Option<String>, query, description = "Add the price converted to this currency"),
+        ("include" = Option<String>, query, description = "outline, to embed the modules and their lessons"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "The course, with `outline` when asked for, unpublished ones only for its instructors", body = Course),
+        (status = 304, description = "The copy named in If-None-Match is still current"),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
 pub async fn get_course_detail(
     app_state: web::Data<AppState>,
     path: web::Path<(i32, i32)>,
+    query: web::Query<CourseDetailQuery>,
+    user: Option<AuthUser>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, MyError> {
     println!("Getting course's detail");
 
     let (teacher_id, course_id) = path.into_inner();
-    get_course_detail_db(&app_state.db, teacher_id, course_id)
-        .await
-        .map(|course_detail|HttpResponse::Ok().json(course_detail))
+    query.wants_outline()?;
+    let (course, _) = instructed_course(&app_state, teacher_id, course_id).await?;
+
+    course_detail(&app_state, course, &query, &user, &req).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/by-id/{course_id}",
+    tag = "course",
+    params(
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("display_currency" = Option<String>, query, description = "Add the price converted to this currency"),
+        ("include" = Option<String>, query, description = "outline, to embed the modules and their lessons"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "The course whichever teacher it belongs to, unpublished ones only for its instructors", body = Course),
+        (status = 304, description = "The copy named in If-None-Match is still current"),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_course_by_id(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    query: web::Query<CourseDetailQuery>,
+    user: Option<AuthUser>,
+    req: HttpRequest,
+) -> Result<HttpResponse, MyError> {
+    query.wants_outline()?;
+    let course = app_state.db.get_course_by_id(path.into_inner()).await?;
+
+    course_detail(&app_state, course, &query, &user, &req).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/c/{slug}",
+    tag = "course",
+    params(
+        ("slug" = String, path, description = "Slug of the course, current or from before a rename"),
+        ("display_currency" = Option<String>, query, description = "Add the price converted to this currency"),
+        ("include" = Option<String>, query, description = "outline, to embed the modules and their lessons"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "The course, unpublished ones only for its instructors", body = Course),
+        (status = 304, description = "The copy named in If-None-Match is still current"),
+        (status = 308, description = "An old slug, Location has the current one"),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_course_by_slug(
+    app_state: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<CourseDetailQuery>,
+    user: Option<AuthUser>,
+    req: HttpRequest,
+) -> Result<HttpResponse, MyError> {
+    query.wants_outline()?;
+    let course_id = match app_state.db.find_course_slug(&path).await? {
+        Some(SlugLookup::Course(course_id)) => course_id,
+        Some(SlugLookup::Moved(current)) => {
+            let location = match req.query_string() {
+                "" => format!("/c/{}", current),
+                query => format!("/c/{}?{}", current, query),
+            };
+            return Ok(HttpResponse::PermanentRedirect().insert_header((header::LOCATION, location)).finish());
+        }
+        None => return Err(MyError::NotFound(Resource::Course, "Course not found".into())),
+    };
+    let course = app_state.db.get_course_by_id(course_id).await?;
+
+    course_detail(&app_state, course, &query, &user, &req).await
+}
+
+//the answer of every course lookup, by path, id or slug
+async fn course_detail(
+    app_state: &AppState,
+    course: Course,
+    query: &CourseDetailQuery,
+    user: &Option<AuthUser>,
+    req: &HttpRequest,
+) -> Result<HttpResponse, MyError> {
+    let course = course.in_currency(&app_state.exchange_rates, query.display_currency);
+    if !is_visible(app_state, &course, user).await? {
+        return Err(MyError::NotFound(Resource::Course, "Course ID not found".into()));
+    }
+    let slug = app_state.db.get_course_slug(course.id).await?;
+
+    //the version only covers the course itself, so a body with the outline is sent untagged
+    if !query.wants_outline()? {
+        let version = course.version;
+        return Ok(tagged(req, version, &CourseDetail { course, slug, outline: None }));
+    }
+
+    let modules = app_state.db.get_modules(course.teacher_id, course.id).await?;
+    let lessons = app_state.db.get_lessons_for_course(course.teacher_id, course.id).await?;
+    Ok(HttpResponse::Ok().json(CourseDetail { course, slug, outline: Some(outline(modules, lessons)) }))
+}
+
+//an unpublished course does not exist as far as the public is concerned, only for its instructors
+pub async fn is_visible(app_state: &AppState, course: &Course, user: &Option<AuthUser>) -> Result<bool, MyError> {
+    let user = match user {
+        _ if course.is_live(Utc::now()) => return Ok(true),
+        Some(user) => user,
+        None => return Ok(false),
+    };
+    let instructors = app_state.db.get_instructors(course.teacher_id, course.id).await?;
+    Ok(user.require_instructor(&instructors, &ANY_INSTRUCTOR).is_ok())
+}
+
+//the course behind a public read of its outline, sessions or calendar
+pub async fn visible_course(
+    app_state: &AppState, teacher_id: i32, course_id: i32, user: &Option<AuthUser>
+) -> Result<Course, MyError> {
+    let course = app_state.db.get_course_detail(teacher_id, course_id).await?;
+    if !is_visible(app_state, &course, user).await? {
+        return Err(MyError::NotFound(Resource::Course, "Course ID not found".into()));
+    }
+    Ok(course)
+}
+
+//the course at /courses/{teacher_id}/{course_id} and its instructors, teacher_id being the lead or any
+//other instructor; the repository keys courses by their lead, so what follows goes by course.teacher_id
+async fn instructed_course(
+    app_state: &AppState, teacher_id: i32, course_id: i32
+) -> Result<(Course, Vec<CourseInstructor>), MyError> {
+    let course = match app_state.db.get_course_detail(teacher_id, course_id).await {
+        Err(MyError::NotFound(Resource::Course, msg)) => app_state.db.get_course_by_id(course_id)
+            .await
+            .map_err(|_| MyError::NotFound(Resource::Course, msg))?,
+        course => course?,
+    };
+    let instructors = app_state.db.get_instructors(course.teacher_id, course.id).await?;
+    if !instructors.iter().any(|instructor| instructor.teacher_id == teacher_id) {
+        return Err(MyError::NotFound(Resource::Course, "Course ID not found".into()));
+    }
+    Ok((course, instructors))
+}
+
+//whether the caller is the teacher the courses belong to, or an admin
+fn is_owner(user: &Option<AuthUser>, teacher_id: i32) -> bool {
+    user.as_ref().is_some_and(|user| user.require_teacher(teacher_id).is_ok())
+}
+
+//adds display_price to every course when the caller asked for a display currency
+fn in_currency(courses: Vec<Course>, app_state: &AppState, currency: Option<Currency>) -> Vec<Course> {
+    courses
+        .into_iter()
+        .map(|course| course.in_currency(&app_state.exchange_rates, currency))
+        .collect()
 }
 
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead or of another of its instructors"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("If-Match" = String, header, description = "ETag the deletion was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The deleted course, restorable until purged", body = TrashItem),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not the lead of the course, nor an admin", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 410, description = "Already deleted", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn delete_course(
     app_state: web::Data<AppState>,
     path: web::Path<(i32, i32)>,
+    user: AuthUser,
+    if_match: IfMatch,
 ) -> Result<HttpResponse, MyError> {
     let (teacher_id, course_id) = path.into_inner();
+    let (course, instructors) = instructed_course(&app_state, teacher_id, course_id).await?;
+    user.require_instructor(&instructors, &[InstructorRole::Lead])?;
 
-    delete_course_db(&app_state.db, teacher_id, course_id)
+    app_state.db.delete_course(course.teacher_id, course.id, &if_match)
         .await.map(|resp|HttpResponse::Ok().json(resp))
 }
 
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}",
+    tag = "course",
+    request_body(content = UpdateCourse,
+        description = "The whole course, optional fields left out or set to null are cleared"),
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead or of another of its instructors"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("If-Match" = String, header, description = "ETag the update was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The updated course, its new version in the ETag header", body = Course),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not the lead or a co-teacher of the course, nor an admin", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Overlaps other courses of the teacher or room", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules, each listed in errors", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn update_course_detail(
     app_state: web::Data<AppState>,
     path: web::Path<(i32, i32)>,
     update_course: web::Json<UpdateCourse>,
+    user: AuthUser,
+    if_match: IfMatch,
 ) -> Result<HttpResponse, MyError> {
     let (teacher_id, course_id) = path.into_inner();
+    let (course, instructors) = instructed_course(&app_state, teacher_id, course_id).await?;
+    user.require_instructor(&instructors, &EDITORS)?;
 
-    update_course_db(&app_state.db, teacher_id, course_id, update_course.into())
-        .await.map(|course|HttpResponse::Ok().json(course))
+    app_state.db.update_course(course.teacher_id, course.id, update_course.try_into()?, user.user_id, &if_match)
+        .await.map(|course|HttpResponse::Ok().insert_header((header::ETAG, etag(course.version))).json(course))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/courses/{teacher_id}/{course_id}",
+    tag = "course",
+    request_body(content = UpdateCourse, content_type = "application/merge-patch+json",
+        description = "RFC 7396 merge patch, fields left out are kept and fields set to null are cleared"),
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead or of another of its instructors"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("If-Match" = String, header, description = "ETag the patch was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The patched course, its new version in the ETag header", body = Course),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not the lead or a co-teacher of the course, nor an admin", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Overlaps other courses of the teacher or room", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules, each listed in errors, or a field a patch cannot change", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn patch_course_detail(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    patch: web::Json<Value>,
+    user: AuthUser,
+    if_match: IfMatch,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    let (course, instructors) = instructed_course(&app_state, teacher_id, course_id).await?;
+    user.require_instructor(&instructors, &EDITORS)?;
+
+    let patch = MergePatch(patch.into_inner());
+    app_state.db.patch_course(course.teacher_id, course.id, &patch, user.user_id, &if_match)
+        .await.map(|course|HttpResponse::Ok().insert_header((header::ETAG, etag(course.version))).json(course))
 }
 
 #[cfg(test)]
 mod tests {
-    use dotenv::dotenv;
-    use std::env;
-    use sqlx::mysql::MySqlPoolOptions;
     use actix_web::web;
-    use crate::AppState;
-    use std::sync::Mutex;
     use actix_web::http::StatusCode;
+    use crate::models::instructor::AddInstructor;
+    use crate::models::meta::{CourseLevel, Language};
+    use crate::models::money::Money;
+    use crate::test_support::*;
+    use chrono::{Duration, TimeZone};
     use super::*;
 
+    fn no_headers() -> HttpRequest {
+        actix_web::test::TestRequest::default().to_http_request()
+    }
+
     #[actix_rt::test]
     async fn post_course_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state: web::Data<AppState> = web::Data::new(AppState {
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
 
         let new_course = web::Json(CreateCourse {
-            teacher_id: 1,
+            teacher_id: t.id,
             name: "post new course from test function".into(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
             description: None,
             format: None,
             structure: None,
             duration: None,
             price: None,
-            language: Some("Chinese".to_string()),
-            level: Some("Medium".to_string()),
+            language: Some(Language::Zh),
+            level: Some(CourseLevel::Intermediate),
         });
 
-        let resp = post_new_course(new_course, app_state).await.unwrap();
+        let resp = post_new_course(new_course, app_state, teacher(t.id)).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
     #[actix_rt::test]
-    async fn get_all_courses_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state: web::Data<AppState> = web::Data::new(AppState {
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
+    async fn post_course_with_invalid_fields_fails() {
+        let app_state = memory_state();
+
+        let new_course = web::Json(CreateCourse {
+            teacher_id: 42,
+            name: "".into(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            description: None,
+            format: None,
+            structure: None,
+            duration: None,
+            price: Some(Money::new(-1, Currency::Cny)),
+            language: None,
+            level: None,
         });
 
+        let resp = post_new_course(new_course, app_state, teacher(42)).await;
+
+        match resp {
+            Err(MyError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(fields, vec!["name", "price", "teacher_id"]);
+            }
+            other => panic!("expected a validation error, got {:?}", other.map(|r| r.status())),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn get_all_courses_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        seed_course(&app_state, t.id, "Rust").await;
+
+        let teacher_id: web::Path<i32>  = web::Path::from(t.id);
+        let query = web::Query(CourseQuery::default());
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let resp = get_courses_for_teacher(app_state, teacher_id, query, req, None).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn get_courses_with_unknown_sort_fails() {
+        let app_state = memory_state();
+
         let teacher_id: web::Path<i32>  = web::Path::from(1);
-        let resp = get_courses_for_teacher(app_state, teacher_id).await.unwrap();
+        let query = web::Query(CourseQuery {
+            sort: Some("teacher_id; DROP TABLE course".into()),
+            ..Default::default()
+        });
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let resp = get_courses_for_teacher(app_state, teacher_id, query, req, None).await;
+
+        assert!(matches!(resp, Err(MyError::Validation(_))));
+    }
+
+    #[actix_rt::test]
+    async fn search_courses_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        seed_course(&app_state, t.id, "Rust").await;
+
+        let query = web::Query(CourseSearchQuery {
+            q: "rust".into(),
+            ..Default::default()
+        });
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let resp = search_courses(app_state, query, req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
     #[actix_rt::test]
     async fn get_course_detail_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state: web::Data<AppState> = web::Data::new(AppState {
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
 
-        let params: web::Path<(i32, i32)> = web::Path::from((1, 2));
-        let resp = get_course_detail(app_state, params).await.unwrap();
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = get_course_detail(app_state, params, web::Query(CourseDetailQuery::default()), None, no_headers()).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn get_course_detail_failure() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, 21));
+        let resp = get_course_detail(app_state, params, web::Query(CourseDetailQuery::default()), None, no_headers()).await;
+
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Course, _))));
+    }
 
+    #[actix_rt::test]
+    async fn draft_course_hidden_from_the_public() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = get_course_detail(app_state.clone(), params, web::Query(CourseDetailQuery::default()), None, no_headers()).await;
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Course, _))));
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let query = web::Query(CourseDetailQuery::default());
+        let resp = get_course_detail(app_state, params, query, Some(teacher(t.id)), no_headers()).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[actix_rt::test]
+    async fn co_teachers_reach_the_course_through_their_own_path() {
+        let app_state = memory_state();
+        let lead = seed_teacher(&app_state).await;
+        let co = seed_teacher(&app_state).await;
+        let ta = seed_teacher(&app_state).await;
+        let other = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, lead.id, "Rust").await;
+        for (teacher_id, role) in [(co.id, InstructorRole::CoTeacher), (ta.id, InstructorRole::Ta)] {
+            let instructor = AddInstructor { teacher_id, role };
+            app_state.db.add_instructor(lead.id, c.id, instructor).await.unwrap();
+        }
+        let detail = |teacher_id: i32, user: AuthUser| {
+            let query = web::Query(CourseDetailQuery::default());
+            get_course_detail(app_state.clone(), web::Path::from((teacher_id, c.id)), query, Some(user), no_headers())
+        };
+        let update = |teacher_id: i32, user: AuthUser| {
+            let body = web::Json(UpdateCourse { name: "Go".into(), ..UpdateCourse::from(&c) });
+            update_course_detail(app_state.clone(), web::Path::from((teacher_id, c.id)), body, user, IfMatch::Any)
+        };
+
+        assert_eq!(detail(co.id, teacher(co.id)).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(detail(ta.id, teacher(ta.id)).await.unwrap().status(), StatusCode::OK);
+        assert!(matches!(detail(other.id, teacher(other.id)).await, Err(MyError::NotFound(Resource::Course, _))));
+
+        assert!(matches!(update(ta.id, teacher(ta.id)).await, Err(MyError::Forbidden(_))));
+        assert_eq!(update(co.id, teacher(co.id)).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(update(lead.id, teacher(co.id)).await.unwrap().status(), StatusCode::OK);
+        let course = app_state.db.get_course_detail(lead.id, c.id).await.unwrap();
+        assert_eq!((course.name.as_str(), course.teacher_id), ("Go", lead.id));
+
+        //deleting the course stays with its lead
+        let resp = delete_course(app_state.clone(), web::Path::from((co.id, c.id)), teacher(co.id), IfMatch::Any).await;
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+        let resp = delete_course(app_state, web::Path::from((co.id, c.id)), teacher(lead.id), IfMatch::Any).await;
+        assert_eq!(resp.unwrap().status(), StatusCode::OK);
+    }
+
     #[actix_rt::test]
     async fn delete_course_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state: web::Data<AppState> = web::Data::new(AppState {
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
 
-        let params: web::Path<(i32, i32)> = web::Path::from((1, 1));
-        let resp = delete_course(app_state, params).await.unwrap();
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = delete_course(app_state, params, teacher(t.id), IfMatch::Any).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
     #[actix_rt::test]
     async fn update_course_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state: web::Data<AppState> = web::Data::new(AppState {
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
 
-        let params: web::Path<(i32, i32)> = web::Path::from((1, 2));
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
 
         let update_course = web::Json(UpdateCourse {
             name: "Update Course".to_string(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
             description: Some("Update information".to_string()),
             format: None,
             structure: None,
@@ -178,8 +684,151 @@ mod tests {
             level: None,
         });
 
-        let resp = update_course_detail(app_state, params, update_course).await.unwrap();
+        let resp = update_course_detail(app_state.clone(), params, update_course, teacher(t.id), IfMatch::Any).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
+        //PUT replaces the course, what the body leaves out is cleared
+        let course = app_state.db.get_course_detail(t.id, c.id).await.unwrap();
+        assert_eq!(course.description.as_deref(), Some("Update information"));
+        assert_eq!((course.price, course.language, course.level), (None, None, None));
     }
-}
\ No newline at end of file
+
+    #[actix_rt::test]
+    async fn update_course_from_stale_copy_fails() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let update_course = || web::Json(UpdateCourse {
+            name: "Update Course".to_string(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            description: None,
+            format: None,
+            structure: None,
+            duration: None,
+            price: None,
+            language: None,
+            level: None,
+        });
+        let stale = IfMatch::parse(&etag(c.version));
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        update_course_detail(app_state.clone(), params, update_course(), teacher(t.id), stale.clone()).await.unwrap();
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = update_course_detail(app_state, params, update_course(), teacher(t.id), stale).await;
+
+        assert!(matches!(resp, Err(MyError::PreconditionFailed(_))));
+    }
+
+    #[actix_rt::test]
+    async fn patch_with_null_clears_only_that_field() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let patch = web::Json(serde_json::json!({"description": null, "capacity": 30}));
+        patch_course_detail(app_state.clone(), params, patch, teacher(t.id), IfMatch::Any).await.unwrap();
+
+        let course = app_state.db.get_course_detail(t.id, c.id).await.unwrap();
+        assert_eq!(course.description, None);
+        assert_eq!(course.capacity, Some(30));
+        assert_eq!(course.name, "Rust");
+        assert_eq!(course.language, c.language);
+    }
+
+    #[actix_rt::test]
+    async fn patch_of_status_or_required_name_refused() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let patch = web::Json(serde_json::json!({"status": "archived"}));
+        let resp = patch_course_detail(app_state.clone(), params, patch, teacher(t.id), IfMatch::Any).await;
+        assert!(matches!(resp, Err(MyError::Validation(ref errors)) if errors[0].field == "status"));
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let patch = web::Json(serde_json::json!({"name": null}));
+        let resp = patch_course_detail(app_state, params, patch, teacher(t.id), IfMatch::Any).await;
+        assert!(matches!(resp, Err(MyError::InvalidInput(_))));
+    }
+
+    #[actix_rt::test]
+    async fn get_course_detail_not_modified() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let req = actix_web::test::TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag(c.version)))
+            .to_http_request();
+        let resp = get_course_detail(app_state, params, web::Query(CourseDetailQuery::default()), None, req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[actix_rt::test]
+    async fn update_other_teachers_course_forbidden() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+
+        let update_course = web::Json(UpdateCourse {
+            name: "Not my course".to_string(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            description: None,
+            format: None,
+            structure: None,
+            duration: None,
+            price: None,
+            language: None,
+            level: None,
+        });
+
+        let resp = update_course_detail(app_state, params, update_course, teacher(t.id + 100), IfMatch::Any).await;
+
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+
+    #[actix_rt::test]
+    async fn post_course_over_the_teachers_other_course_conflicts() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let starts_at = Utc.with_ymd_and_hms(2022, 3, 1, 9, 0, 0).unwrap();
+
+        let mut conflicts = vec![];
+        for (name, hours) in [("Rust", 0), ("Go", 1)] {
+            let new_course = web::Json(CreateCourse {
+                teacher_id: t.id,
+                name: name.into(),
+                starts_at: Some(starts_at + Duration::hours(hours)),
+                ends_at: Some(starts_at + Duration::hours(hours + 2)),
+                room: None,
+                capacity: None,
+                description: None,
+                format: None,
+                structure: None,
+                duration: None,
+                price: None,
+                language: None,
+                level: None,
+            });
+            if let Err(MyError::ScheduleConflict(found)) = post_new_course(new_course, app_state.clone(), teacher(t.id)).await {
+                conflicts = found;
+            }
+        }
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].name, "Rust");
+    }
+}
@@ -0,0 +1,178 @@
+use actix_web::{HttpResponse, web};
+use crate::errors::MyError;
+use crate::models::enrollment::CreateEnrollment;
+use crate::auth::AuthUser;
+use crate::state::AppState;
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/enrollments",
+    tag = "course",
+    request_body = CreateEnrollment,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The created enrollment", body = Enrollment),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Duplicate or still referenced record", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_enrollment(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    new_enrollment: web::Json<CreateEnrollment>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    //students enroll themselves, the course's teacher can enroll anyone
+    user.require_student(new_enrollment.student_id)
+        .or_else(|_| user.require_teacher(teacher_id))?;
+
+    app_state.db.post_new_enrollment(teacher_id, course_id, new_enrollment.into())
+        .await
+        .map(|enrollment| HttpResponse::Ok().json(enrollment))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/enrollments",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Enrollments of the course", body = [Enrollment]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_enrollments_for_course(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.get_enrollments_for_course(teacher_id, course_id)
+        .await
+        .map(|enrollments| HttpResponse::Ok().json(enrollments))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/{student_id}/enrollments",
+    tag = "student",
+    params(
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "Enrollments of the student", body = [Enrollment]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_enrollments_for_student(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let student_id = path.into_inner();
+    user.require_student(student_id)?;
+
+    app_state.db.get_enrollments_for_student(student_id)
+        .await
+        .map(|enrollments| HttpResponse::Ok().json(enrollments))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/enrollments/{student_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_enrollment(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, student_id) = path.into_inner();
+    user.require_student(student_id)
+        .or_else(|_| user.require_teacher(teacher_id))?;
+
+    app_state.db.delete_enrollment(teacher_id, course_id, student_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use crate::test_support::*;
+
+    #[actix_rt::test]
+    async fn post_new_enrollment_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        let s = seed_student(&app_state, "li.lei@example.com").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let enrollment = web::Json(CreateEnrollment{ student_id: s.id });
+
+        let resp = post_new_enrollment(app_state, params, enrollment, student(s.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+
+    #[actix_rt::test]
+    async fn get_enrollments_for_course_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+
+        let resp = get_enrollments_for_course(app_state, params, teacher(t.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+
+    #[actix_rt::test]
+    async fn get_enrollments_for_student_success() {
+        let app_state = memory_state();
+        let s = seed_student(&app_state, "li.lei@example.com").await;
+        let student_id = web::Path::from(s.id);
+
+        let resp = get_enrollments_for_student(app_state, student_id, student(s.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+}
@@ -1,6 +1,14 @@
 use actix_web::{HttpResponse, web};
 use crate::state::AppState;
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "general",
+    responses(
+        (status = 200, description = "Health message with the visit count", body = String),
+    ),
+)]
 pub async fn health_check_handler(app_state: web::Data<AppState>) -> HttpResponse {
     let health_check_response = &app_state.health_check_response;
     let mut visit_count = app_state.visit_count.lock().unwrap();
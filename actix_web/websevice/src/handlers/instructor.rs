@@ -0,0 +1,180 @@
+use crate::auth::AuthUser;
+use crate::state::AppState;
+use crate::errors::MyError;
+use crate::models::instructor::AddInstructor;
+use actix_web::{web, HttpResponse};
+
+//the teacher_id in these paths is always the course's lead, who manages the others
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/instructors",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The lead, then co-teachers and assistants in the order they were added", body = [CourseInstructor]),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_instructors(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+
+    app_state.db.get_instructors(teacher_id, course_id)
+        .await
+        .map(|instructors| HttpResponse::Ok().json(instructors))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/instructors",
+    tag = "course",
+    request_body = AddInstructor,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The added instructor", body = CourseInstructor),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The teacher is on the course already", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 422, description = "Role lead, or the teacher does not exist", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn add_instructor(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    new_instructor: web::Json<AddInstructor>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+    new_instructor.check(teacher_id)?;
+
+    app_state.db.add_instructor(teacher_id, course_id, new_instructor.into_inner())
+        .await
+        .map(|instructor| HttpResponse::Ok().json(instructor))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/instructors/{instructor_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the course's lead teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("instructor_id" = i32, path, description = "Teacher id of the co-teacher or assistant"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The lead cannot be removed", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn remove_instructor(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, instructor_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+    if instructor_id == teacher_id {
+        return Err(MyError::Conflict("The lead cannot be removed, transfer the course instead".into()));
+    }
+
+    app_state.db.remove_instructor(teacher_id, course_id, instructor_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::course::CourseQuery;
+    use crate::models::instructor::InstructorRole;
+    use crate::test_support::*;
+    use actix_web::http::StatusCode;
+
+    #[actix_rt::test]
+    async fn co_teachers_see_the_course_in_their_listing() {
+        let app_state = memory_state();
+        let lead = seed_teacher(&app_state).await;
+        let ta = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, lead.id, "Rust").await;
+
+        let params = web::Path::from((lead.id, c.id));
+        let body = web::Json(AddInstructor { teacher_id: ta.id, role: InstructorRole::Ta });
+        let resp = add_instructor(app_state.clone(), params, body, teacher(lead.id)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let (courses, total) = app_state.db.get_course_for_teacher(ta.id, &CourseQuery::default()).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!((courses[0].id, courses[0].teacher_id), (c.id, lead.id));
+
+        let instructors = app_state.db.get_instructors(lead.id, c.id).await.unwrap();
+        let roles: Vec<(i32, InstructorRole)> = instructors.iter().map(|i| (i.teacher_id, i.role)).collect();
+        assert_eq!(roles, vec![(lead.id, InstructorRole::Lead), (ta.id, InstructorRole::Ta)]);
+    }
+
+    #[actix_rt::test]
+    async fn instructors_are_added_once_and_removed() {
+        let app_state = memory_state();
+        let lead = seed_teacher(&app_state).await;
+        let co = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, lead.id, "Rust").await;
+        let add = |teacher_id| {
+            let body = web::Json(AddInstructor { teacher_id, role: InstructorRole::CoTeacher });
+            add_instructor(app_state.clone(), web::Path::from((lead.id, c.id)), body, teacher(lead.id))
+        };
+
+        add(co.id).await.unwrap();
+        assert!(matches!(add(co.id).await, Err(MyError::Conflict(_))));
+        assert!(matches!(add(lead.id).await, Err(MyError::Conflict(_))));
+        assert!(matches!(add(co.id + 100).await, Err(MyError::InvalidReference(_))));
+
+        let resp = remove_instructor(app_state.clone(), web::Path::from((lead.id, c.id, lead.id)), teacher(lead.id)).await;
+        assert!(matches!(resp, Err(MyError::Conflict(_))));
+        let resp = remove_instructor(app_state.clone(), web::Path::from((lead.id, c.id, co.id)), teacher(co.id)).await;
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+
+        remove_instructor(app_state.clone(), web::Path::from((lead.id, c.id, co.id)), teacher(lead.id)).await.unwrap();
+        let resp = remove_instructor(app_state.clone(), web::Path::from((lead.id, c.id, co.id)), teacher(lead.id)).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+        assert_eq!(app_state.db.get_instructors(lead.id, c.id).await.unwrap().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn co_teacher_given_the_course_becomes_its_lead() {
+        let app_state = memory_state();
+        let lead = seed_teacher(&app_state).await;
+        let co = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, lead.id, "Rust").await;
+        let body = AddInstructor { teacher_id: co.id, role: InstructorRole::CoTeacher };
+        app_state.db.add_instructor(lead.id, c.id, body).await.unwrap();
+
+        app_state.db.transfer_course(lead.id, c.id, co.id).await.unwrap();
+
+        let instructors = app_state.db.get_instructors(co.id, c.id).await.unwrap();
+        let roles: Vec<(i32, InstructorRole)> = instructors.iter().map(|i| (i.teacher_id, i.role)).collect();
+        assert_eq!(roles, vec![(co.id, InstructorRole::Lead)]);
+        assert!(app_state.db.get_teacher_details(lead.id).await.is_ok());
+    }
+}
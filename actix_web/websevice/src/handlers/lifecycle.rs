@@ -0,0 +1,156 @@
+use actix_web::{HttpResponse, web};
+use crate::errors::MyError;
+use crate::models::lifecycle::{PublishCourse, Transition};
+use crate::auth::AuthUser;
+use crate::state::AppState;
+
+//courses only change status here, so every change is recorded with who made it
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/publish",
+    tag = "course",
+    request_body = PublishCourse,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The course, published or scheduled to be", body = Course),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The course is already published or archived", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn publish_course(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    body: web::Bytes,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+    let transition = Transition::Publish { at: PublishCourse::from_body(&body)?.publish_at };
+
+    app_state.db.change_course_status(teacher_id, course_id, transition, user.user_id)
+        .await
+        .map(|course| HttpResponse::Ok().json(course))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/archive",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The archived course", body = Course),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The course is not published", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn archive_course(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.change_course_status(teacher_id, course_id, Transition::Archive, user.user_id)
+        .await
+        .map(|course| HttpResponse::Ok().json(course))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/status-changes",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Status changes of the course, oldest first", body = [StatusChange]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_status_changes(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.get_status_changes(teacher_id, course_id)
+        .await
+        .map(|changes| HttpResponse::Ok().json(changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use crate::models::meta::CourseStatus;
+    use crate::test_support::*;
+
+    #[actix_rt::test]
+    async fn publish_then_archive_is_recorded() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = publish_course(app_state.clone(), params, web::Bytes::new(), teacher(t.id)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        archive_course(app_state.clone(), params, teacher(t.id)).await.unwrap();
+
+        let changes = app_state.db.get_status_changes(t.id, c.id).await.unwrap();
+        let moves: Vec<(CourseStatus, CourseStatus)> = changes.iter().map(|c| (c.from_status, c.to_status)).collect();
+        assert_eq!(moves, vec![
+            (CourseStatus::Draft, CourseStatus::Published),
+            (CourseStatus::Published, CourseStatus::Archived),
+        ]);
+        assert_eq!(changes[0].changed_by, Some(teacher(t.id).user_id));
+    }
+
+    #[actix_rt::test]
+    async fn archive_draft_conflicts() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = archive_course(app_state, params, teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::Conflict(_))));
+    }
+
+    #[actix_rt::test]
+    async fn publish_other_teachers_course_forbidden() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = publish_course(app_state, params, web::Bytes::new(), teacher(t.id + 100)).await;
+
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+}
@@ -0,0 +1,76 @@
+use actix_web::HttpResponse;
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Frequency, Language};
+
+//the accepted values of the course vocabularies, for building dropdowns
+
+#[utoipa::path(
+    get,
+    path = "/meta/levels",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Course levels", body = [MetaOption]),
+    ),
+)]
+pub async fn get_levels() -> HttpResponse {
+    HttpResponse::Ok().json(CourseLevel::options())
+}
+
+#[utoipa::path(
+    get,
+    path = "/meta/formats",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Course formats", body = [MetaOption]),
+    ),
+)]
+pub async fn get_formats() -> HttpResponse {
+    HttpResponse::Ok().json(CourseFormat::options())
+}
+
+#[utoipa::path(
+    get,
+    path = "/meta/languages",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Languages of instruction, ISO 639-1 codes", body = [MetaOption]),
+    ),
+)]
+pub async fn get_languages() -> HttpResponse {
+    HttpResponse::Ok().json(Language::options())
+}
+
+#[utoipa::path(
+    get,
+    path = "/meta/currencies",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Currencies of course prices, ISO 4217 codes", body = [MetaOption]),
+    ),
+)]
+pub async fn get_currencies() -> HttpResponse {
+    HttpResponse::Ok().json(Currency::options())
+}
+
+#[utoipa::path(
+    get,
+    path = "/meta/frequencies",
+    tag = "meta",
+    responses(
+        (status = 200, description = "How often a course session repeats", body = [MetaOption]),
+    ),
+)]
+pub async fn get_frequencies() -> HttpResponse {
+    HttpResponse::Ok().json(Frequency::options())
+}
+
+#[utoipa::path(
+    get,
+    path = "/meta/statuses",
+    tag = "meta",
+    responses(
+        (status = 200, description = "Lifecycle statuses of a course", body = [MetaOption]),
+    ),
+)]
+pub async fn get_statuses() -> HttpResponse {
+    HttpResponse::Ok().json(CourseStatus::options())
+}
@@ -1,3 +1,15 @@
+pub mod auth;
 pub mod course;
+pub mod enrollment;
 pub mod general;
-pub mod teacher;
\ No newline at end of file
+pub mod instructor;
+pub mod lifecycle;
+pub mod meta;
+pub mod revision;
+pub mod seat;
+pub mod session;
+pub mod student;
+pub mod syllabus;
+pub mod teacher;
+pub mod transfer;
+pub mod trash;
\ No newline at end of file
@@ -0,0 +1,225 @@
+use actix_web::{HttpResponse, web};
+use crate::errors::MyError;
+use crate::models::revision::{RevisionDiff, RevisionDiffQuery};
+use crate::auth::AuthUser;
+use crate::state::AppState;
+//the path macros call into_params on every web::Query argument
+use utoipa::IntoParams;
+
+//every save of a course is kept, so an edit gone wrong can be looked up and undone
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/revisions",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Revisions of the course, oldest first", body = [CourseRevision]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_revisions(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.get_revisions(teacher_id, course_id)
+        .await
+        .map(|revisions| HttpResponse::Ok().json(revisions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/revisions/diff",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("from" = i32, query, description = "Revision to compare from"),
+        ("to" = i32, query, description = "Revision to compare to"),
+    ),
+    responses(
+        (status = 200, description = "The fields that differ between the two revisions", body = RevisionDiff),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_revision_diff(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    query: web::Query<RevisionDiffQuery>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    let from = app_state.db.get_revision(teacher_id, course_id, query.from).await?;
+    let to = app_state.db.get_revision(teacher_id, course_id, query.to).await?;
+
+    Ok(HttpResponse::Ok().json(RevisionDiff::between(&from, &to)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/revisions/{revision}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("revision" = i32, path, description = "Revision number, 1 for the course as created"),
+    ),
+    responses(
+        (status = 200, description = "The revision", body = CourseRevision),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_revision(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, revision) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.get_revision(teacher_id, course_id, revision)
+        .await
+        .map(|revision| HttpResponse::Ok().json(revision))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/revisions/{revision}/restore",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("revision" = i32, path, description = "Revision to go back to"),
+    ),
+    responses(
+        (status = 200, description = "The course as restored, saved as a new revision", body = Course),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Overlaps other courses of the teacher or room", body = MyErrorResponse),
+        (status = 412, description = "Saved by someone else while restoring", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn restore_revision(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, revision) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.restore_revision(teacher_id, course_id, revision, user.user_id)
+        .await
+        .map(|course| HttpResponse::Ok().json(course))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use crate::errors::Resource;
+    use crate::models::course::UpdateCourse;
+    use crate::models::etag::IfMatch;
+    use crate::test_support::*;
+
+    fn wipe_description() -> UpdateCourse {
+        UpdateCourse {
+            name: "Rust".into(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            description: Some("".into()),
+            format: None,
+            structure: None,
+            duration: None,
+            price: None,
+            language: None,
+            level: None,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn every_save_is_a_revision() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        app_state.db.update_course(t.id, c.id, wipe_description(), teacher(t.id).user_id, &IfMatch::Any).await.unwrap();
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = get_revisions(app_state.clone(), params, teacher(t.id)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let revisions = app_state.db.get_revisions(t.id, c.id).await.unwrap();
+        let numbers: Vec<i32> = revisions.iter().map(|r| r.revision).collect();
+        assert_eq!(numbers, vec![1, 2]);
+        assert_eq!(revisions[0].description.as_deref(), Some("All about Rust"));
+        assert_eq!(revisions[1].description.as_deref(), Some(""));
+    }
+
+    #[actix_rt::test]
+    async fn restore_brings_a_wiped_description_back() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        app_state.db.update_course(t.id, c.id, wipe_description(), teacher(t.id).user_id, &IfMatch::Any).await.unwrap();
+
+        let params: web::Path<(i32, i32, i32)> = web::Path::from((t.id, c.id, 1));
+        restore_revision(app_state.clone(), params, teacher(t.id)).await.unwrap();
+
+        let course = app_state.db.get_course_detail(t.id, c.id).await.unwrap();
+        assert_eq!(course.description.as_deref(), Some("All about Rust"));
+        //the restore is a revision of its own, the wiped one stays in the history
+        let revisions = app_state.db.get_revisions(t.id, c.id).await.unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[2].description.as_deref(), Some("All about Rust"));
+    }
+
+    #[actix_rt::test]
+    async fn unknown_revision_not_found() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32, i32)> = web::Path::from((t.id, c.id, 7));
+        let resp = restore_revision(app_state, params, teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Revision, _))));
+    }
+
+    #[actix_rt::test]
+    async fn other_teachers_revisions_forbidden() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = get_revisions(app_state, params, teacher(t.id + 100)).await;
+
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+}
@@ -0,0 +1,217 @@
+use actix_web::{HttpResponse, web};
+use crate::errors::MyError;
+use crate::models::seat::CreateReservation;
+use crate::auth::AuthUser;
+use crate::state::AppState;
+
+//students hold and release their own seats, the course's teacher manages all of them
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/seats",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Seat holders, then the waitlist in order", body = [Reservation]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_reservations(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.get_reservations(teacher_id, course_id)
+        .await
+        .map(|reservations| HttpResponse::Ok().json(reservations))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/seats/{student_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "The student's reservation and waitlist position", body = Reservation),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_reservation(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, student_id) = path.into_inner();
+    user.require_student(student_id)
+        .or_else(|_| user.require_teacher(teacher_id))?;
+
+    app_state.db.get_reservation(teacher_id, course_id, student_id)
+        .await
+        .map(|reservation| HttpResponse::Ok().json(reservation))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/seats",
+    tag = "course",
+    request_body = CreateReservation,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "A seat, or a place on the waitlist when the course is full", body = Reservation),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The student already has a reservation", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn reserve_seat(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    new_reservation: web::Json<CreateReservation>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_student(new_reservation.student_id)
+        .or_else(|_| user.require_teacher(teacher_id))?;
+
+    app_state.db.reserve_seat(teacher_id, course_id, new_reservation.into())
+        .await
+        .map(|reservation| HttpResponse::Ok().json(reservation))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/seats/{student_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message, the freed seat goes to the head of the waitlist", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn release_seat(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, student_id) = path.into_inner();
+    user.require_student(student_id)
+        .or_else(|_| user.require_teacher(teacher_id))?;
+
+    app_state.db.release_seat(teacher_id, course_id, student_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use crate::errors::Resource;
+    use crate::models::course::UpdateCourse;
+    use crate::models::etag::IfMatch;
+    use crate::models::seat::SeatStatus;
+    use crate::test_support::*;
+
+    async fn limit_seats(app_state: &web::Data<AppState>, teacher_id: i32, course_id: i32, capacity: i32) {
+        let course = app_state.db.get_course_detail(teacher_id, course_id).await.unwrap();
+        let update = UpdateCourse { capacity: Some(capacity), ..UpdateCourse::from(&course) };
+        app_state.db.update_course(teacher_id, course_id, update, teacher(teacher_id).user_id, &IfMatch::Any).await.unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn full_course_puts_students_on_the_waitlist() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        limit_seats(&app_state, t.id, c.id, 1).await;
+
+        let mut reservations = vec![];
+        for email in ["a@example.com", "b@example.com", "c@example.com"] {
+            let s = seed_student(&app_state, email).await;
+            reservations.push(app_state.db.reserve_seat(t.id, c.id, CreateReservation { student_id: s.id }).await.unwrap());
+        }
+
+        assert_eq!(reservations[0].status, SeatStatus::Reserved);
+        assert_eq!(reservations[2].status, SeatStatus::Waitlisted);
+        assert_eq!(reservations[2].waitlist_position, Some(2));
+    }
+
+    #[actix_rt::test]
+    async fn released_seat_goes_to_the_head_of_the_waitlist() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        limit_seats(&app_state, t.id, c.id, 1).await;
+        let first = seed_student(&app_state, "a@example.com").await;
+        let second = seed_student(&app_state, "b@example.com").await;
+        for s in [&first, &second] {
+            app_state.db.reserve_seat(t.id, c.id, CreateReservation { student_id: s.id }).await.unwrap();
+        }
+
+        let params: web::Path<(i32, i32, i32)> = web::Path::from((t.id, c.id, first.id));
+        let resp = release_seat(app_state.clone(), params, student(first.id)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let promoted = app_state.db.get_reservation(t.id, c.id, second.id).await.unwrap();
+        assert_eq!(promoted.status, SeatStatus::Reserved);
+        assert_eq!(promoted.waitlist_position, None);
+    }
+
+    #[actix_rt::test]
+    async fn release_someone_elses_seat_forbidden() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        let s = seed_student(&app_state, "a@example.com").await;
+
+        let params: web::Path<(i32, i32, i32)> = web::Path::from((t.id, c.id, s.id));
+        let resp = release_seat(app_state, params, student(s.id + 100)).await;
+
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+
+    #[actix_rt::test]
+    async fn release_without_reservation_not_found() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32, i32)> = web::Path::from((t.id, c.id, 42));
+        let resp = release_seat(app_state, params, teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Reservation, _))));
+    }
+}
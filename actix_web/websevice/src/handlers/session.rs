@@ -0,0 +1,317 @@
+use actix_web::{HttpResponse, web};
+use chrono::Utc;
+use crate::errors::MyError;
+use crate::models::calendar::{to_ics, CalendarEvent};
+use crate::models::session::{CreateSession, UpdateSession};
+use crate::auth::AuthUser;
+use crate::handlers::course::{is_visible, visible_course};
+use crate::state::AppState;
+use std::collections::BTreeMap;
+
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/sessions",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Sessions of the course, earliest first", body = [CourseSession]),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_sessions(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: Option<AuthUser>,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    visible_course(&app_state, teacher_id, course_id, &user).await?;
+
+    app_state.db.get_sessions(teacher_id, course_id)
+        .await
+        .map(|sessions| HttpResponse::Ok().json(sessions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/sessions",
+    tag = "course",
+    request_body = CreateSession,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The created session", body = CourseSession),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_session(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    new_session: web::Json<CreateSession>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.post_new_session(teacher_id, course_id, new_session.try_into()?)
+        .await
+        .map(|session| HttpResponse::Ok().json(session))
+}
+
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}/sessions/{session_id}",
+    tag = "course",
+    request_body = UpdateSession,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("session_id" = i32, path, description = "Id of the session"),
+    ),
+    responses(
+        (status = 200, description = "The updated session", body = CourseSession),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_session(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    update_session: web::Json<UpdateSession>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, session_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.update_session(teacher_id, course_id, session_id, update_session.try_into()?)
+        .await
+        .map(|session| HttpResponse::Ok().json(session))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/sessions/{session_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("session_id" = i32, path, description = "Id of the session"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_session(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, session_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.delete_session(teacher_id, course_id, session_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/calendar.ics",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "iCalendar feed of the course's sessions", content_type = "text/calendar", body = String),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_course_calendar(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: Option<AuthUser>,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    let course = visible_course(&app_state, teacher_id, course_id, &user).await?;
+    let sessions = app_state.db.get_sessions(teacher_id, course_id).await?;
+
+    let events: Vec<CalendarEvent> = sessions
+        .iter()
+        .map(|session| CalendarEvent { title: &course.name, session })
+        .collect();
+
+    Ok(HttpResponse::Ok()
+        .content_type(CALENDAR_CONTENT_TYPE)
+        .body(to_ics(&course.name, &events, Utc::now())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/teacher/{teacher_id}/calendar.ics",
+    tag = "teacher",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+    ),
+    responses(
+        (status = 200, description = "iCalendar feed of the sessions of the teacher's published courses, drafts too for the teacher", content_type = "text/calendar", body = String),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_teacher_calendar(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    user: Option<AuthUser>,
+) -> Result<HttpResponse, MyError> {
+    let teacher_id = path.into_inner();
+    let teacher = app_state.db.get_teacher_details(teacher_id).await?;
+    let sessions = app_state.db.get_sessions_for_teacher(teacher_id).await?;
+
+    //each event is titled with its course's name, courses the caller may not see are left out
+    let mut course_names = BTreeMap::new();
+    for session in &sessions {
+        if !course_names.contains_key(&session.course_id) {
+            let course = app_state.db.get_course_detail(teacher_id, session.course_id).await?;
+            let name = is_visible(&app_state, &course, &user).await?.then_some(course.name);
+            course_names.insert(course.id, name);
+        }
+    }
+
+    let events: Vec<CalendarEvent> = sessions
+        .iter()
+        .filter_map(|session| {
+            let title = course_names[&session.course_id].as_ref()?;
+            Some(CalendarEvent { title, session })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok()
+        .content_type(CALENDAR_CONTENT_TYPE)
+        .body(to_ics(&teacher.name, &events, Utc::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use chrono::TimeZone;
+    use crate::models::meta::Frequency;
+    use crate::test_support::*;
+
+    fn weekly_session() -> CreateSession {
+        CreateSession {
+            starts_at: Utc.with_ymd_and_hms(2022, 3, 1, 11, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2022, 3, 1, 12, 0, 0).unwrap(),
+            timezone: "Asia/Shanghai".into(),
+            location: None,
+            meeting_url: Some("https://meet.example.com/rust".into()),
+            frequency: Some(Frequency::Weekly),
+            occurrences: Some(8),
+            repeat_until: None,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn post_new_session_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = post_new_session(app_state, params, web::Json(weekly_session()), teacher(t.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn post_session_ending_before_start_fails() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let mut session = weekly_session();
+        session.ends_at = session.starts_at;
+        session.timezone = "Mars/Olympus".into();
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = post_new_session(app_state, params, web::Json(session), teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::Validation(errors)) if errors.len() == 2));
+    }
+
+    #[actix_rt::test]
+    async fn teacher_calendar_lists_sessions_of_all_courses() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        for name in ["Rust", "Go"] {
+            let c = seed_course(&app_state, t.id, name).await;
+            app_state.db.post_new_session(t.id, c.id, weekly_session()).await.unwrap();
+        }
+
+        let resp = get_teacher_calendar(app_state, web::Path::from(t.id), None).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let ics = std::str::from_utf8(&body).unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("SUMMARY:Go\r\n"));
+    }
+
+    #[actix_rt::test]
+    async fn sessions_of_a_draft_are_not_found_for_the_public() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+        app_state.db.post_new_session(t.id, c.id, weekly_session()).await.unwrap();
+
+        let resp = get_sessions(app_state.clone(), web::Path::from((t.id, c.id)), None).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+        let resp = get_course_calendar(app_state.clone(), web::Path::from((t.id, c.id)), None).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+
+        let resp = get_sessions(app_state, web::Path::from((t.id, c.id)), Some(teacher(t.id))).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn teacher_calendar_leaves_drafts_to_the_teacher() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let live = seed_course(&app_state, t.id, "Rust").await;
+        let draft = seed_draft_course(&app_state, t.id, "Go").await;
+        for c in [&live, &draft] {
+            app_state.db.post_new_session(t.id, c.id, weekly_session()).await.unwrap();
+        }
+
+        for (user, expected) in [(None, 1), (Some(teacher(t.id + 1)), 1), (Some(teacher(t.id)), 2)] {
+            let resp = get_teacher_calendar(app_state.clone(), web::Path::from(t.id), user).await.unwrap();
+            let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+            let ics = std::str::from_utf8(&body).unwrap();
+            assert_eq!(ics.matches("BEGIN:VEVENT").count(), expected);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use actix_web::{HttpResponse, web};
+use actix_web::web::Path;
+use crate::errors::MyError;
+use crate::models::student::{CreateStudent, UpdateStudent};
+use crate::auth::AuthUser;
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/students/",
+    tag = "student",
+    responses(
+        (status = 200, description = "All students, empty when there are none", body = [Student]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_all_students(
+    app_state: web::Data<AppState>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    user.require_staff()?;
+    app_state.db.get_all_students()
+        .await
+        .map(|students| HttpResponse::Ok().json(students))
+}
+
+#[utoipa::path(
+    get,
+    path = "/students/{student_id}",
+    tag = "student",
+    params(
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "The student", body = Student),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_student_details(
+    app_state: web::Data<AppState>, path: Path<i32>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    let student_id = path.into_inner();
+    user.require_staff().or_else(|_| user.require_student(student_id))?;
+
+    app_state.db.get_student_details(student_id)
+        .await
+        .map(|student| HttpResponse::Ok().json(student))
+}
+
+#[utoipa::path(
+    post,
+    path = "/students/",
+    tag = "student",
+    request_body = CreateStudent,
+    responses(
+        (status = 200, description = "The created student", body = Student),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 409, description = "Duplicate or still referenced record", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_student(
+    app_state: web::Data<AppState>, student: web::Json<CreateStudent>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    user.require_admin()?;
+    app_state.db.post_new_student(CreateStudent::from(student))
+        .await
+        .map(|student| HttpResponse::Ok().json(student))
+}
+
+#[utoipa::path(
+    put,
+    path = "/students/{student_id}",
+    tag = "student",
+    request_body = UpdateStudent,
+    params(
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "The updated student", body = Student),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Duplicate or still referenced record", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_student_details(
+    app_state: web::Data<AppState>,
+    path: Path<i32>,
+    update_student: web::Json<UpdateStudent>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let student_id = path.into_inner();
+    user.require_student(student_id)?;
+
+    app_state.db.update_student_details(student_id, UpdateStudent::from(update_student))
+        .await
+        .map(|student| HttpResponse::Ok().json(student))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/students/{student_id}",
+    tag = "student",
+    params(
+        ("student_id" = i32, path, description = "Id of the student"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_student(
+    app_state: web::Data<AppState>, path: Path<i32>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    user.require_admin()?;
+    app_state.db.delete_student(path.into_inner())
+        .await
+        .map(|result| HttpResponse::Ok().json(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use crate::errors::Resource;
+    use crate::test_support::*;
+
+    #[actix_rt::test]
+    async fn post_new_student_success() {
+        let app_state = memory_state();
+        let student = web::Json(CreateStudent{
+            name: "Li Lei".to_string(),
+            email: "li.lei@example.com".to_string(),
+            profile: None,
+        });
+
+        let resp = post_new_student(app_state, student, admin()).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+
+    #[actix_rt::test]
+    async fn get_all_students_success() {
+        let app_state = memory_state();
+        seed_student(&app_state, "li.lei@example.com").await;
+
+        let resp = get_all_students(app_state, admin()).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+
+    #[actix_rt::test]
+    async fn no_students_is_an_empty_list() {
+        let app_state = memory_state();
+
+        let resp = get_all_students(app_state, admin()).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"[]");
+    }
+
+    #[actix_rt::test]
+    async fn update_student_details_success() {
+        let app_state = memory_state();
+        let s = seed_student(&app_state, "li.lei@example.com").await;
+        let update = web::Json(UpdateStudent{
+            name: None,
+            email: None,
+            profile: Some(Some("Second year".to_string())),
+        });
+        let student_id = web::Path::from(s.id);
+
+        let resp = update_student_details(app_state, student_id, update, student(s.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK)
+    }
+
+    #[actix_rt::test]
+    async fn delete_unknown_student_not_found() {
+        let app_state = memory_state();
+        let s = seed_student(&app_state, "li.lei@example.com").await;
+
+        let resp = delete_student(app_state.clone(), web::Path::from(s.id), admin()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = delete_student(app_state, web::Path::from(s.id), admin()).await;
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Student, _))));
+    }
+}
@@ -0,0 +1,423 @@
+use actix_web::{HttpResponse, web};
+use crate::errors::MyError;
+use crate::models::syllabus::{CreateLesson, CreateModule, Reorder, UpdateLesson, UpdateModule};
+use crate::auth::AuthUser;
+use crate::handlers::course::visible_course;
+use crate::state::AppState;
+
+//the outline is public like the course itself, changing it is up to the course's teacher
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/modules",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Modules of the course in order", body = [CourseModule]),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_modules(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    user: Option<AuthUser>,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    visible_course(&app_state, teacher_id, course_id, &user).await?;
+
+    app_state.db.get_modules(teacher_id, course_id)
+        .await
+        .map(|modules| HttpResponse::Ok().json(modules))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/modules",
+    tag = "course",
+    request_body = CreateModule,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The created module, placed last", body = CourseModule),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_module(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    new_module: web::Json<CreateModule>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.post_new_module(teacher_id, course_id, new_module.try_into()?)
+        .await
+        .map(|module| HttpResponse::Ok().json(module))
+}
+
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}/modules/order",
+    tag = "course",
+    request_body = Reorder,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "Modules of the course in their new order", body = [CourseModule]),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "The ids are not exactly the course's modules", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn reorder_modules(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    reorder: web::Json<Reorder>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.reorder_modules(teacher_id, course_id, reorder.into_inner())
+        .await
+        .map(|modules| HttpResponse::Ok().json(modules))
+}
+
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}",
+    tag = "course",
+    request_body = UpdateModule,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+    ),
+    responses(
+        (status = 200, description = "The updated module", body = CourseModule),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_module(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    update_module: web::Json<UpdateModule>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.update_module(teacher_id, course_id, module_id, update_module.try_into()?)
+        .await
+        .map(|module| HttpResponse::Ok().json(module))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message, the module's lessons are deleted too", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_module(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.delete_module(teacher_id, course_id, module_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[utoipa::path(
+    get,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}/lessons",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+    ),
+    responses(
+        (status = 200, description = "Lessons of the module in order", body = [Lesson]),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_lessons(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    user: Option<AuthUser>,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id) = path.into_inner();
+    visible_course(&app_state, teacher_id, course_id, &user).await?;
+
+    app_state.db.get_lessons(teacher_id, course_id, module_id)
+        .await
+        .map(|lessons| HttpResponse::Ok().json(lessons))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}/lessons",
+    tag = "course",
+    request_body = CreateLesson,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+    ),
+    responses(
+        (status = 200, description = "The created lesson, placed last in its module", body = Lesson),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn post_new_lesson(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    new_lesson: web::Json<CreateLesson>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.post_new_lesson(teacher_id, course_id, module_id, new_lesson.try_into()?)
+        .await
+        .map(|lesson| HttpResponse::Ok().json(lesson))
+}
+
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}/lessons/order",
+    tag = "course",
+    request_body = Reorder,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+    ),
+    responses(
+        (status = 200, description = "Lessons of the module in their new order", body = [Lesson]),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "The ids are not exactly the module's lessons", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn reorder_lessons(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32)>,
+    reorder: web::Json<Reorder>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.reorder_lessons(teacher_id, course_id, module_id, reorder.into_inner())
+        .await
+        .map(|lessons| HttpResponse::Ok().json(lessons))
+}
+
+#[utoipa::path(
+    put,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+    tag = "course",
+    request_body = UpdateLesson,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+        ("lesson_id" = i32, path, description = "Id of the lesson"),
+    ),
+    responses(
+        (status = 200, description = "The updated lesson", body = Lesson),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_lesson(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32, i32)>,
+    update_lesson: web::Json<UpdateLesson>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id, lesson_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.update_lesson(teacher_id, course_id, module_id, lesson_id, update_lesson.try_into()?)
+        .await
+        .map(|lesson| HttpResponse::Ok().json(lesson))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/courses/{teacher_id}/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+    tag = "course",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+        ("module_id" = i32, path, description = "Id of the module"),
+        ("lesson_id" = i32, path, description = "Id of the lesson"),
+    ),
+    responses(
+        (status = 200, description = "Deletion message", body = String),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_lesson(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32, i32, i32)>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id, module_id, lesson_id) = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.delete_lesson(teacher_id, course_id, module_id, lesson_id)
+        .await
+        .map(|resp| HttpResponse::Ok().json(resp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use crate::errors::Resource;
+    use crate::test_support::*;
+
+    fn new_module(title: &str) -> web::Json<CreateModule> {
+        web::Json(CreateModule { title: title.into(), summary: None })
+    }
+
+    #[actix_rt::test]
+    async fn post_new_module_success() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = post_new_module(app_state, params, new_module("Basics"), teacher(t.id)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn post_module_for_other_teacher_forbidden() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let resp = post_new_module(app_state, params, new_module("Basics"), teacher(t.id + 100)).await;
+
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+
+    #[actix_rt::test]
+    async fn reorder_modules_with_missing_id_fails() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        let m1 = app_state.db.post_new_module(t.id, c.id, new_module("One").into_inner()).await.unwrap();
+        app_state.db.post_new_module(t.id, c.id, new_module("Two").into_inner()).await.unwrap();
+
+        let params: web::Path<(i32, i32)> = web::Path::from((t.id, c.id));
+        let reorder = web::Json(Reorder { ids: vec![m1.id] });
+        let resp = reorder_modules(app_state, params, reorder, teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::Validation(_))));
+    }
+
+    #[actix_rt::test]
+    async fn lesson_of_another_module_not_found() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        let m1 = app_state.db.post_new_module(t.id, c.id, new_module("One").into_inner()).await.unwrap();
+        let m2 = app_state.db.post_new_module(t.id, c.id, new_module("Two").into_inner()).await.unwrap();
+        let lesson = CreateLesson {
+            title: "Ownership".into(),
+            body: None,
+            duration_minutes: Some(30),
+            attachments: vec![],
+        };
+        let l = app_state.db.post_new_lesson(t.id, c.id, m1.id, lesson).await.unwrap();
+
+        let params: web::Path<(i32, i32, i32, i32)> = web::Path::from((t.id, c.id, m2.id, l.id));
+        let resp = delete_lesson(app_state, params, teacher(t.id)).await;
+
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Lesson, _))));
+    }
+
+    #[actix_rt::test]
+    async fn outline_of_a_draft_is_not_found_for_the_public() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_draft_course(&app_state, t.id, "Rust").await;
+        let m = app_state.db.post_new_module(t.id, c.id, new_module("One").into_inner()).await.unwrap();
+
+        let resp = get_modules(app_state.clone(), web::Path::from((t.id, c.id)), None).await;
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Course, _))));
+        let resp = get_lessons(app_state.clone(), web::Path::from((t.id, c.id, m.id)), None).await;
+        assert!(matches!(resp, Err(MyError::NotFound(Resource::Course, _))));
+
+        let resp = get_modules(app_state, web::Path::from((t.id, c.id)), Some(teacher(t.id))).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
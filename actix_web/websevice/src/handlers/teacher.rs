@@ -1,48 +1,237 @@
-use actix_web::{HttpResponse, web};
+use actix_web::{HttpRequest, HttpResponse, web};
+use actix_web::http::header;
 use actix_web::web::Path;
-use crate::dbaccess::teacher::*;
 use crate::errors::MyError;
-use crate::models::teacher::{CreateTeacher, UpdateTeacher};
+use crate::models::etag::{etag, tagged, IfMatch};
+use crate::models::pagination::Page;
+use crate::models::patch::MergePatch;
+use crate::models::teacher::{CreateTeacher, TeacherQuery, UpdateTeacher};
+use crate::models::timetable::{Availability, AvailabilityQuery};
+use crate::models::transfer::DeleteTeacherQuery;
+use crate::auth::AuthUser;
 use crate::state::AppState;
+use serde_json::Value;
+//the path macros call into_params on every web::Query argument
+use utoipa::IntoParams;
 
+#[utoipa::path(
+    get,
+    path = "/teacher/",
+    tag = "teacher",
+    params(
+        ("page" = Option<u32>, query, description = "Page number, starting at 1"),
+        ("per_page" = Option<u32>, query, description = "Items per page, 20 by default and 100 at most"),
+        ("sort" = Option<String>, query, description = "id or name"),
+        ("order" = Option<String>, query, description = "asc or desc"),
+        ("name" = Option<String>, query, description = "Only teachers whose name contains this"),
+    ),
+    responses(
+        (status = 200, description = "Page of teachers, empty when none match", body = TeacherPage),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 422, description = "Invalid field values or reference", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
 pub async fn get_all_teachers(
-    app_state: web::Data<AppState>
+    app_state: web::Data<AppState>,
+    query: web::Query<TeacherQuery>,
+    req: HttpRequest,
 ) ->Result<HttpResponse, MyError> {
-    get_all_teachers_db(&app_state.db)
+    let query = query.into_inner();
+    let (page, per_page) = query.page_bounds();
+    app_state.db.get_all_teachers(&query)
         .await
-        .map(|teachers| HttpResponse::Ok().json(teachers))
+        .map(|(teachers, total)| HttpResponse::Ok().json(Page::new(
+            teachers, total, page, per_page, |p| query.link(req.path(), p)
+        )))
 }
 
+#[utoipa::path(
+    get,
+    path = "/teacher/{teacher_id}",
+    tag = "teacher",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("If-None-Match" = Option<String>, header, description = "ETag of the copy the client has"),
+    ),
+    responses(
+        (status = 200, description = "The teacher, its version in the ETag header", body = Teacher),
+        (status = 304, description = "The copy named in If-None-Match is still current"),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
 pub async fn get_teacher_details(
-    app_state: web::Data<AppState>, path: Path<i32>
+    app_state: web::Data<AppState>, path: Path<i32>, req: HttpRequest
 ) -> Result<HttpResponse, MyError> {
-    get_teacher_details_db(&app_state.db, path.into_inner())
+    app_state.db.get_teacher_details(path.into_inner())
         .await
-        .map(|teacher| HttpResponse::Ok().json(teacher))
+        .map(|teacher| tagged(&req, teacher.version, &teacher))
 }
 
+#[utoipa::path(
+    get,
+    path = "/teacher/{teacher_id}/availability",
+    tag = "teacher",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("from" = String, query, description = "Start of the window, RFC 3339"),
+        ("to" = String, query, description = "End of the window, RFC 3339, at most 366 days after from"),
+    ),
+    responses(
+        (status = 200, description = "Busy and free blocks of the teacher within the window", body = Availability),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 422, description = "Invalid window", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+)]
+pub async fn get_teacher_availability(
+    app_state: web::Data<AppState>, path: Path<i32>, query: web::Query<AvailabilityQuery>
+) -> Result<HttpResponse, MyError> {
+    let teacher = app_state.db.get_teacher_details(path.into_inner()).await?;
+    let (from, to) = query.window()?;
+
+    let courses = app_state.db.get_courses_between(teacher.id, from, to).await?;
+    Ok(HttpResponse::Ok().json(Availability::new(teacher.id, from, to, &courses)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/teacher/",
+    tag = "teacher",
+    request_body = CreateTeacher,
+    responses(
+        (status = 200, description = "The created teacher", body = Teacher),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules, each listed in errors", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn post_new_teacher(
-    app_state: web::Data<AppState>, teacher: web::Json<CreateTeacher>
+    app_state: web::Data<AppState>, teacher: web::Json<CreateTeacher>, user: AuthUser
 ) -> Result<HttpResponse, MyError> {
-    post_new_teacher_db(&app_state.db, CreateTeacher::from(teacher))
+    user.require_admin()?;
+    app_state.db.post_new_teacher(CreateTeacher::try_from(teacher)?)
         .await
         .map(|teacher| HttpResponse::Ok().json(teacher))
 }
 
+#[utoipa::path(
+    put,
+    path = "/teacher/{teacher_id}",
+    tag = "teacher",
+    request_body = UpdateTeacher,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("If-Match" = String, header, description = "ETag the update was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The updated teacher, its new version in the ETag header", body = Teacher),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules, each listed in errors", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn update_teacher_details(
     app_state: web::Data<AppState>,
     path: Path<i32>,
     update_teacher: web::Json<UpdateTeacher>,
+    user: AuthUser,
+    if_match: IfMatch,
 ) -> Result<HttpResponse, MyError> {
-    update_teacher_details_db(&app_state.db, path.into_inner(), UpdateTeacher::from(update_teacher))
+    let teacher_id = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    app_state.db.update_teacher_details(teacher_id, UpdateTeacher::try_from(update_teacher)?, &if_match)
         .await
-        .map(|teacher|HttpResponse::Ok().json(teacher))
+        .map(|teacher|HttpResponse::Ok().insert_header((header::ETAG, etag(teacher.version))).json(teacher))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/teacher/{teacher_id}",
+    tag = "teacher",
+    request_body(content = UpdateTeacher, content_type = "application/merge-patch+json",
+        description = "RFC 7396 merge patch, fields left out are kept"),
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("If-Match" = String, header, description = "ETag the patch was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The patched teacher, its new version in the ETag header", body = Teacher),
+        (status = 400, description = "Malformed JSON body", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 422, description = "Fields that break their rules, each listed in errors, or a field a patch cannot change", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn patch_teacher_details(
+    app_state: web::Data<AppState>,
+    path: Path<i32>,
+    patch: web::Json<Value>,
+    user: AuthUser,
+    if_match: IfMatch,
+) -> Result<HttpResponse, MyError> {
+    let teacher_id = path.into_inner();
+    user.require_teacher(teacher_id)?;
+
+    let patch = MergePatch(patch.into_inner());
+    app_state.db.patch_teacher_details(teacher_id, &patch, &if_match)
+        .await
+        .map(|teacher|HttpResponse::Ok().insert_header((header::ETAG, etag(teacher.version))).json(teacher))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/teacher/{teacher_id}",
+    tag = "teacher",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("courses" = Option<String>, query, description = "refuse (default) while the teacher has courses, cascade to delete them too, or reassign them"),
+        ("reassign_to" = Option<i32>, query, description = "Teacher getting the courses, with courses=reassign"),
+        ("If-Match" = String, header, description = "ETag the deletion was based on, or *"),
+    ),
+    responses(
+        (status = 200, description = "The deleted teacher, restorable until purged", body = TrashItem),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "The teacher still has courses, or the new teacher is busy at their times", body = MyErrorResponse),
+        (status = 410, description = "Already deleted", body = MyErrorResponse),
+        (status = 412, description = "Changed since the ETag in If-Match was read", body = MyErrorResponse),
+        (status = 422, description = "Invalid reassign_to", body = MyErrorResponse),
+        (status = 428, description = "Missing If-Match", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn delete_teacher(
-    app_state: web::Data<AppState>, path: Path<i32>
+    app_state: web::Data<AppState>,
+    path: Path<i32>,
+    query: web::Query<DeleteTeacherQuery>,
+    user: AuthUser,
+    if_match: IfMatch,
 ) -> Result<HttpResponse, MyError> {
-    delete_teacher_db(&app_state.db, path.into_inner())
+    user.require_admin()?;
+    let teacher_id = path.into_inner();
+    let deletion = query.deletion(teacher_id)?;
+    app_state.db.delete_teacher(teacher_id, deletion, &if_match)
         .await.
         map(|result| HttpResponse::Ok().json(result))
 }
@@ -51,107 +240,182 @@ pub async fn delete_teacher(
 mod  tests {
     use super::*;
     use actix_web::http::StatusCode;
-    use dotenv::dotenv;
-    use std::env;
-    use std::sync::Mutex;
-    use sqlx::mysql::MySqlPoolOptions;
+    use crate::models::transfer::OnCourses;
+    use crate::test_support::*;
 
-    #[ignore]
     #[actix_rt::test]
     async fn post_new_teacher_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state = web::Data::new(AppState{
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
         let teacher = web::Json(CreateTeacher{
             name: "Han Siyuan".to_string(),
             picture_url: "https://onederive.com/Haydn.Kong".to_string(),
             profile: "rich".to_string(),
         });
 
-        let resp = post_new_teacher(app_state, teacher).await.unwrap();
+        let resp = post_new_teacher(app_state, teacher, admin()).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK)
     }
 
     #[actix_rt::test]
-    async fn get_all_teachers_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state = web::Data::new(AppState{
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
+    async fn post_new_teacher_with_bad_picture_url_fails() {
+        let app_state = memory_state();
+        let teacher = web::Json(CreateTeacher{
+            name: "Han Siyuan".to_string(),
+            picture_url: "not a url".to_string(),
+            profile: "rich".to_string(),
         });
 
-        let resp = get_all_teachers(app_state).await.unwrap();
+        let resp = post_new_teacher(app_state, teacher, admin()).await;
+
+        assert!(matches!(resp, Err(MyError::Validation(ref errors)) if errors[0].field == "picture_url"));
+    }
+
+    #[actix_rt::test]
+    async fn get_all_teachers_success() {
+        let app_state = memory_state();
+        seed_teacher(&app_state).await;
+
+        let query = web::Query(TeacherQuery::default());
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let resp = get_all_teachers(app_state, query, req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK)
     }
 
+    #[actix_rt::test]
+    async fn teachers_matching_nothing_give_an_empty_page() {
+        let app_state = memory_state();
+        seed_teacher(&app_state).await;
+
+        let query = TeacherQuery { name: Some("100%".into()), ..TeacherQuery::default() };
+        let (teachers, total) = app_state.db.get_all_teachers(&query).await.unwrap();
+        assert_eq!((teachers.len(), total), (0, 0));
+
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        let resp = get_all_teachers(app_state, web::Query(query), req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
     #[actix_rt::test]
     async fn get_teacher_detail_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state = web::Data::new(AppState{
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
-        let teacher_id = web::Path::from(3);
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let teacher_id = web::Path::from(t.id);
+        let req = actix_web::test::TestRequest::default().to_http_request();
 
-        let resp = get_teacher_details(app_state, teacher_id).await.unwrap();
+        let resp = get_teacher_details(app_state, teacher_id, req).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), "\"1\"");
+    }
+
+    #[actix_rt::test]
+    async fn get_teacher_detail_not_modified() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let req = actix_web::test::TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"1\""))
+            .to_http_request();
+
+        let resp = get_teacher_details(app_state, web::Path::from(t.id), req).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
     }
 
     #[actix_rt::test]
     async fn update_teacher_details_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state = web::Data::new(AppState{
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
         let teacher = web::Json(UpdateTeacher{
             name: Some("Haydn Kong".to_string()),
             picture_url: Some("https://onederive.com/Haydn.Kong".to_string()),
             profile: Some("rich".to_string()),
         });
-        let teacher_id = web::Path::from(2);
+        let teacher_id = web::Path::from(t.id);
 
-        let resp = update_teacher_details(app_state, teacher_id, teacher).await.unwrap();
+        let resp = update_teacher_details(app_state, teacher_id, teacher, admin(), IfMatch::parse("\"1\"")).await.unwrap();
 
-        assert_eq!(resp.status(), StatusCode::OK)
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), "\"2\"");
+    }
+
+    #[actix_rt::test]
+    async fn update_teacher_from_stale_copy_fails() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let teacher = || web::Json(UpdateTeacher{ name: Some("Haydn Kong".to_string()), picture_url: None, profile: None });
+        update_teacher_details(app_state.clone(), web::Path::from(t.id), teacher(), admin(), IfMatch::Any).await.unwrap();
+
+        let resp = update_teacher_details(app_state, web::Path::from(t.id), teacher(), admin(), IfMatch::parse("\"1\"")).await;
+
+        assert!(matches!(resp, Err(MyError::PreconditionFailed(_))));
+    }
+
+    #[actix_rt::test]
+    async fn patch_teacher_keeps_fields_left_out() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let patch = web::Json(serde_json::json!({"profile": "richer"}));
+
+        let resp = patch_teacher_details(app_state.clone(), web::Path::from(t.id), patch, admin(), IfMatch::Any).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let teacher = app_state.db.get_teacher_details(t.id).await.unwrap();
+        assert_eq!((teacher.name, teacher.profile), (t.name, "richer".to_string()));
     }
 
     #[actix_rt::test]
     async fn delete_teacher_success() {
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-        let db_pool = MySqlPoolOptions::new().connect(&db_url).await.unwrap();
-
-        let app_state = web::Data::new(AppState{
-            health_check_response: "".to_string(),
-            visit_count: Mutex::new(0),
-            db: db_pool,
-        });
-        let teacher_id = web::Path::from(6);
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let teacher_id = web::Path::from(t.id);
 
-        let resp = delete_teacher(app_state, teacher_id).await.unwrap();
+        let query = web::Query(DeleteTeacherQuery::default());
+        let resp = delete_teacher(app_state, teacher_id, query, admin(), IfMatch::Any).await.unwrap();
 
         assert_eq!(resp.status(), StatusCode::OK)
     }
-}
\ No newline at end of file
+
+    #[actix_rt::test]
+    async fn courses_are_refused_cascaded_or_reassigned() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let other = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        let delete = |courses, reassign_to| {
+            let query = web::Query(DeleteTeacherQuery { courses, reassign_to });
+            delete_teacher(app_state.clone(), web::Path::from(t.id), query, admin(), IfMatch::Any)
+        };
+
+        let resp = delete(None, None).await;
+        assert!(matches!(resp, Err(MyError::Conflict(_))));
+        let resp = delete(Some(OnCourses::Reassign), Some(other.id + 100)).await;
+        assert!(matches!(resp, Err(MyError::InvalidReference(_))));
+        assert_eq!(app_state.db.get_course_detail(t.id, c.id).await.unwrap().version, c.version);
+
+        delete(Some(OnCourses::Reassign), Some(other.id)).await.unwrap();
+        assert!(app_state.db.get_course_detail(other.id, c.id).await.is_ok());
+        let resp = app_state.db.get_teacher_details(t.id).await;
+        assert!(matches!(resp, Err(MyError::Gone(..))));
+
+        let query = web::Query(DeleteTeacherQuery { courses: Some(OnCourses::Cascade), reassign_to: None });
+        delete_teacher(app_state.clone(), web::Path::from(other.id), query, admin(), IfMatch::Any).await.unwrap();
+        let resp = app_state.db.get_course_detail(other.id, c.id).await;
+        assert!(matches!(resp, Err(MyError::Gone(..))));
+        assert_eq!(app_state.db.get_trash().await.unwrap().len(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn availability_window_must_be_ordered() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let query = web::Query::<AvailabilityQuery>::from_query(
+            "from=2022-03-02T00:00:00Z&to=2022-03-01T00:00:00Z"
+        ).unwrap();
+
+        let resp = get_teacher_availability(app_state, web::Path::from(t.id), query).await;
+
+        assert!(matches!(resp, Err(MyError::Validation(ref errors)) if errors[0].field == "to"));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::auth::AuthUser;
+use crate::state::AppState;
+use crate::errors::{MyError, Resource};
+use crate::models::etag::etag;
+use crate::models::transfer::{course_path, moved_path, TransferCourse};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::{web, Error, HttpResponse};
+use std::future::Future;
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/transfer",
+    tag = "course",
+    request_body = TransferCourse,
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the course"),
+    ),
+    responses(
+        (status = 200, description = "The course under its new teacher, its new path in the Location header", body = Course),
+        (status = 400, description = "Invalid input", body = MyErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found", body = MyErrorResponse),
+        (status = 409, description = "Overlaps other courses of the new teacher", body = MyErrorResponse),
+        (status = 410, description = "The course is deleted", body = MyErrorResponse),
+        (status = 412, description = "Changed while it was being transferred", body = MyErrorResponse),
+        (status = 422, description = "Same teacher, or the new teacher does not exist", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn transfer_course(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    transfer: web::Json<TransferCourse>,
+    user: AuthUser,
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    //the current teacher gives the course away, the new one does not have to agree
+    user.require_teacher(teacher_id)?;
+    transfer.check(teacher_id)?;
+
+    app_state.db.transfer_course(teacher_id, course_id, transfer.teacher_id)
+        .await
+        .map(|course| HttpResponse::Ok()
+            .insert_header((header::LOCATION, format!("/courses/{}/{}", course.teacher_id, course.id)))
+            .insert_header((header::ETAG, etag(course.version)))
+            .json(course))
+}
+
+//middleware for the /courses scope: a path whose course was transferred away from the teacher in it
+//answers 308 with the same path under the course's current teacher
+pub fn follow_transfers<S>(req: ServiceRequest, srv: &S) -> impl Future<Output = Result<ServiceResponse, Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+{
+    let fut = srv.call(req);
+    async move {
+        let res = fut.await?;
+
+        let course_missing = matches!(
+            res.response().error().and_then(|err| err.as_error::<MyError>()),
+            Some(MyError::NotFound(Resource::Course, _))
+        );
+        let (teacher_id, course_id) = match course_path(res.request().path()) {
+            Some(ids) if course_missing => ids,
+            _ => return Ok(res),
+        };
+        let app_state = match res.request().app_data::<web::Data<AppState>>() {
+            Some(app_state) => app_state.clone(),
+            None => return Ok(res),
+        };
+
+        //a failed lookup keeps the 404
+        let location = match app_state.db.find_transferred_course(teacher_id, course_id).await {
+            Ok(Some(current)) => moved_path(res.request().path(), res.request().query_string(), current),
+            _ => None,
+        };
+        match location {
+            Some(location) => {
+                let redirect = HttpResponse::PermanentRedirect()
+                    .insert_header((header::LOCATION, location))
+                    .finish();
+                Ok(res.into_response(redirect))
+            }
+            None => Ok(res),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use actix_web::http::StatusCode;
+
+    #[actix_rt::test]
+    async fn transferred_course_moves_to_the_new_teacher() {
+        let app_state = memory_state();
+        let from = seed_teacher(&app_state).await;
+        let to = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, from.id, "Rust").await;
+
+        let params = web::Path::from((from.id, c.id));
+        let body = web::Json(TransferCourse { teacher_id: to.id });
+        let resp = transfer_course(app_state.clone(), params, body, teacher(from.id)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap().to_str().unwrap(),
+            format!("/courses/{}/{}", to.id, c.id)
+        );
+
+        let course = app_state.db.get_course_detail(to.id, c.id).await.unwrap();
+        assert_eq!(course.version, c.version + 1);
+        let resp = app_state.db.get_course_detail(from.id, c.id).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+        assert_eq!(app_state.db.find_transferred_course(from.id, c.id).await.unwrap(), Some(to.id));
+        assert_eq!(app_state.db.find_transferred_course(to.id, c.id).await.unwrap(), None);
+    }
+
+    #[actix_rt::test]
+    async fn transfer_needs_another_existing_teacher() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+
+        let params = web::Path::from((t.id, c.id));
+        let body = web::Json(TransferCourse { teacher_id: t.id });
+        let resp = transfer_course(app_state.clone(), params, body, teacher(t.id)).await;
+        assert!(matches!(resp, Err(MyError::Validation(_))));
+
+        let params = web::Path::from((t.id, c.id));
+        let body = web::Json(TransferCourse { teacher_id: t.id + 100 });
+        let resp = transfer_course(app_state.clone(), params, body, teacher(t.id)).await;
+        assert!(matches!(resp, Err(MyError::InvalidReference(_))));
+
+        let params = web::Path::from((t.id, c.id));
+        let body = web::Json(TransferCourse { teacher_id: t.id + 100 });
+        let resp = transfer_course(app_state, params, body, teacher(t.id + 100)).await;
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+    }
+}
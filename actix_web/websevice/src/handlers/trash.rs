@@ -0,0 +1,162 @@
+use crate::auth::AuthUser;
+use crate::state::AppState;
+use crate::errors::MyError;
+use crate::models::etag::etag;
+use actix_web::http::header;
+use actix_web::{web, HttpResponse};
+
+#[utoipa::path(
+    get,
+    path = "/trash",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Deleted teachers and courses not purged yet, most recently deleted first", body = [TrashItem]),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_trash(app_state: web::Data<AppState>, user: AuthUser) -> Result<HttpResponse, MyError> {
+    user.require_admin()?;
+    app_state.db.get_trash()
+        .await
+        .map(|items| HttpResponse::Ok().json(items))
+}
+
+#[utoipa::path(
+    post,
+    path = "/teacher/{teacher_id}/restore",
+    tag = "trash",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the deleted teacher"),
+    ),
+    responses(
+        (status = 200, description = "The teacher back out of the trash, its new version in the ETag header", body = Teacher),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found, or already purged", body = MyErrorResponse),
+        (status = 409, description = "The teacher is not deleted", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn restore_teacher(
+    app_state: web::Data<AppState>, path: web::Path<i32>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    user.require_admin()?;
+    app_state.db.restore_teacher(path.into_inner())
+        .await
+        .map(|teacher| HttpResponse::Ok().insert_header((header::ETAG, etag(teacher.version))).json(teacher))
+}
+
+#[utoipa::path(
+    post,
+    path = "/courses/{teacher_id}/{course_id}/restore",
+    tag = "trash",
+    params(
+        ("teacher_id" = i32, path, description = "Id of the teacher"),
+        ("course_id" = i32, path, description = "Id of the deleted course"),
+    ),
+    responses(
+        (status = 200, description = "The course back out of the trash, its new version in the ETag header", body = Course),
+        (status = 401, description = "Missing or invalid bearer token", body = MyErrorResponse),
+        (status = 403, description = "Not allowed for this user", body = MyErrorResponse),
+        (status = 404, description = "Not found, or already purged", body = MyErrorResponse),
+        (status = 409, description = "Not deleted, its teacher is deleted, or its time is taken by now", body = MyErrorResponse),
+        (status = 500, description = "Database or server error", body = MyErrorResponse),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn restore_course(
+    app_state: web::Data<AppState>, path: web::Path<(i32, i32)>, user: AuthUser
+) -> Result<HttpResponse, MyError> {
+    let (teacher_id, course_id) = path.into_inner();
+    user.require_admin()?;
+
+    app_state.db.restore_course(teacher_id, course_id)
+        .await
+        .map(|course| HttpResponse::Ok().insert_header((header::ETAG, etag(course.version))).json(course))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::etag::IfMatch;
+    use crate::models::transfer::TeacherDeletion;
+    use crate::test_support::*;
+    use actix_web::http::StatusCode;
+    use chrono::{Duration, Utc};
+
+    #[actix_rt::test]
+    async fn deleted_course_is_gone_until_restored() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        app_state.db.delete_course(t.id, c.id, &IfMatch::Any).await.unwrap();
+
+        let resp = app_state.db.get_course_detail(t.id, c.id).await;
+        assert!(matches!(resp, Err(MyError::Gone(..))));
+        let resp = app_state.db.delete_course(t.id, c.id, &IfMatch::Any).await;
+        assert!(matches!(resp, Err(MyError::Gone(..))));
+
+        //like the rest of the trash, restoring is up to an admin, not the course's teacher
+        let resp = restore_course(app_state.clone(), web::Path::from((t.id, c.id)), teacher(t.id)).await;
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+        let resp = restore_course(app_state.clone(), web::Path::from((t.id, c.id)), admin()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(app_state.db.get_course_detail(t.id, c.id).await.is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn trash_is_for_admins() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+
+        let resp = get_trash(app_state.clone(), teacher(t.id)).await;
+        assert!(matches!(resp, Err(MyError::Forbidden(_))));
+
+        app_state.db.delete_teacher(t.id, TeacherDeletion::Refuse, &IfMatch::Any).await.unwrap();
+        let resp = get_trash(app_state.clone(), admin()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let trash = app_state.db.get_trash().await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, t.id);
+    }
+
+    #[actix_rt::test]
+    async fn course_of_deleted_teacher_waits_for_the_teacher() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        app_state.db.delete_course(t.id, c.id, &IfMatch::Any).await.unwrap();
+        app_state.db.delete_teacher(t.id, TeacherDeletion::Refuse, &IfMatch::Any).await.unwrap();
+
+        let resp = restore_course(app_state.clone(), web::Path::from((t.id, c.id)), admin()).await;
+        assert!(matches!(resp, Err(MyError::Conflict(_))));
+
+        restore_teacher(app_state.clone(), web::Path::from(t.id), admin()).await.unwrap();
+        let resp = restore_course(app_state, web::Path::from((t.id, c.id)), admin()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn purge_only_takes_what_is_past_retention() {
+        let app_state = memory_state();
+        let t = seed_teacher(&app_state).await;
+        let c = seed_course(&app_state, t.id, "Rust").await;
+        app_state.db.delete_course(t.id, c.id, &IfMatch::Any).await.unwrap();
+        app_state.db.delete_teacher(t.id, TeacherDeletion::Refuse, &IfMatch::Any).await.unwrap();
+
+        let purged = app_state.db.purge_trash(Utc::now() - Duration::days(30)).await.unwrap();
+        assert_eq!(purged, 0);
+
+        let purged = app_state.db.purge_trash(Utc::now() + Duration::seconds(1)).await.unwrap();
+        assert_eq!(purged, 2);
+        assert!(app_state.db.get_trash().await.unwrap().is_empty());
+        let resp = app_state.db.get_course_detail(t.id, c.id).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+        let resp = app_state.db.get_teacher_details(t.id).await;
+        assert!(matches!(resp, Err(MyError::NotFound(..))));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::errors::MyError;
+use crate::repository::MigrationStatus;
+use sqlx::migrate::{Migrate, MigrateError, MigrationType, Migrator};
+use sqlx::{Database, Pool};
+
+//each backend embeds its own migrations/<backend> directory and hands it in here
+
+pub async fn run_migrations<DB>(migrator: &Migrator, pool: &Pool<DB>) -> Result<(), MyError>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    Ok(migrator.run(pool).await?)
+}
+
+pub async fn revert_last_migration<DB>(
+    migrator: &Migrator, pool: &Pool<DB>
+) -> Result<Option<i64>, MyError>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    let applied = conn.list_applied_migrations().await?;
+    let last = match applied.iter().map(|m| m.version).max() {
+        Some(version) => version,
+        None => return Ok(None),
+    };
+
+    let migration = migrator
+        .iter()
+        .find(|m| m.version == last && matches!(m.migration_type, MigrationType::ReversibleDown))
+        .ok_or(MigrateError::VersionMissing(last))?;
+
+    conn.lock().await?;
+    let reverted = conn.revert(migration).await;
+    conn.unlock().await?;
+    reverted?;
+
+    Ok(Some(last))
+}
+
+pub async fn migration_status<DB>(
+    migrator: &Migrator, pool: &Pool<DB>
+) -> Result<Vec<MigrationStatus>, MyError>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    let applied = conn.list_applied_migrations().await?;
+
+    Ok(migrator
+        .iter()
+        .filter(|m| !matches!(m.migration_type, MigrationType::ReversibleDown))
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.iter().any(|a| a.version == m.version),
+        })
+        .collect())
+}
@@ -0,0 +1,143 @@
+use crate::models::meta::Frequency;
+use crate::models::session::CourseSession;
+use chrono::{DateTime, Utc};
+
+//a session and the course name it is shown under
+pub struct CalendarEvent<'a> {
+    pub title: &'a str,
+    pub session: &'a CourseSession,
+}
+
+//an iCalendar (RFC 5545) document that calendar apps can subscribe to
+pub fn to_ics(name: &str, events: &[CalendarEvent<'_>], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//CourseManager//teacher-service//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+
+    for event in events {
+        let session = event.session;
+        let tz = session.tz();
+        lines.push("BEGIN:VEVENT".into());
+        lines.push(format!("UID:course-session-{}@coursemanager", session.id));
+        lines.push(format!("DTSTAMP:{}", utc_stamp(now)));
+        //local wall clock time plus TZID, so repeats follow daylight saving
+        lines.push(format!(
+            "DTSTART;TZID={}:{}",
+            session.timezone,
+            session.starts_at.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ));
+        lines.push(format!(
+            "DTEND;TZID={}:{}",
+            session.timezone,
+            session.ends_at.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+        ));
+        if let Some(rule) = rrule(session) {
+            lines.push(rule);
+        }
+        lines.push(format!("SUMMARY:{}", escape(event.title)));
+        if let Some(location) = session.location.as_ref().or(session.meeting_url.as_ref()) {
+            lines.push(format!("LOCATION:{}", escape(location)));
+        }
+        if let Some(url) = &session.meeting_url {
+            lines.push(format!("URL:{}", url));
+        }
+        lines.push("END:VEVENT".into());
+    }
+    lines.push("END:VCALENDAR".into());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn utc_stamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn rrule(session: &CourseSession) -> Option<String> {
+    let freq = match session.frequency? {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+    };
+    let mut rule = format!("RRULE:FREQ={}", freq);
+    if let Some(count) = session.occurrences {
+        rule.push_str(&format!(";COUNT={}", count));
+    } else if let Some(until) = session.repeat_until {
+        rule.push_str(&format!(";UNTIL={}", utc_stamp(until)));
+    }
+    Some(rule)
+}
+
+//TEXT values escape backslash, semicolon, comma and newlines
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+//lines end in CRLF and longer ones are folded at 75 octets, never inside a character
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn session() -> CourseSession {
+        CourseSession {
+            id: 7,
+            course_id: 1,
+            starts_at: Utc.with_ymd_and_hms(2022, 3, 1, 11, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2022, 3, 1, 12, 30, 0).unwrap(),
+            timezone: "Asia/Shanghai".into(),
+            location: Some("Room 101, Main building".into()),
+            meeting_url: None,
+            frequency: Some(Frequency::Weekly),
+            occurrences: Some(10),
+            repeat_until: None,
+        }
+    }
+
+    #[test]
+    fn renders_local_times_and_repeats() {
+        let session = session();
+        let events = [CalendarEvent { title: "Rust; the basics", session: &session }];
+        let ics = to_ics("Rust", &events, Utc.with_ymd_and_hms(2022, 2, 1, 0, 0, 0).unwrap());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Asia/Shanghai:20220301T190000\r\n"));
+        assert!(ics.contains("DTEND;TZID=Asia/Shanghai:20220301T203000\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;COUNT=10\r\n"));
+        assert!(ics.contains("SUMMARY:Rust\\; the basics\r\n"));
+        assert!(ics.contains("LOCATION:Room 101\\, Main building\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = "SUMMARY:".to_string() + &"课".repeat(40);
+        let folded = fold(&line);
+
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+}
@@ -1,81 +1,342 @@
-use crate::errors::MyError;
+use crate::errors::{FieldError, MyError};
+use crate::models::pagination::{order_sql, page_bounds};
+use crate::models::meta::{CourseFormat, CourseLevel, CourseStatus, Currency, Language};
+use crate::models::money::{ExchangeRates, Money};
+use crate::models::patch::MergePatch;
+use crate::models::timetable::interval_errors;
+use crate::models::validation::validated;
 use actix_web::web;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use validator::{Validate, ValidationError};
 use std::convert::TryFrom;
 
 //get course from database
-#[derive(Serialize, Debug, Clone, sqlx::FromRow)]
+#[derive(Serialize, Debug, Clone, Component)]
 pub struct Course {
     pub teacher_id: i32,
     pub id: i32,
     pub name: String,
     pub time: Option<DateTime<Utc>>,
+    //when the course is held, kept free of clashes per teacher and per room
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub room: Option<String>,
+    //seats that can be reserved, no limit when not set
+    pub capacity: Option<i32>,
+    //changed through the publish and archive endpoints only
+    pub status: CourseStatus,
+    //when the course went live, or when a scheduled one will
+    pub published_at: Option<DateTime<Utc>>,
+    //bumped by every write, sent as the ETag
+    pub version: i32,
     pub description: Option<String>,
-    pub format: Option<String>,
+    pub format: Option<CourseFormat>,
     pub structure: Option<String>,
     pub duration: Option<String>,
-    pub price: Option<i32>,
-    pub language: Option<String>,
-    pub level: Option<String>,
+    pub price: Option<Money>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
+    //price converted for the viewer, only when asked for with display_currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_price: Option<Money>,
+}
+
+//course row as stored, the price split over price and currency
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CourseRow {
+    pub teacher_id: i32,
+    pub id: i32,
+    pub name: String,
+    pub time: Option<DateTime<Utc>>,
+    //when the course is held, kept free of clashes per teacher and per room
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub room: Option<String>,
+    //seats that can be reserved, no limit when not set
+    pub capacity: Option<i32>,
+    //changed through the publish and archive endpoints only
+    pub status: CourseStatus,
+    //when the course went live, or when a scheduled one will
+    pub published_at: Option<DateTime<Utc>>,
+    //bumped by every write, sent as the ETag
+    pub version: i32,
+    pub description: Option<String>,
+    pub format: Option<CourseFormat>,
+    pub structure: Option<String>,
+    pub duration: Option<String>,
+    pub price: Option<i64>,
+    pub currency: Option<Currency>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
+}
+
+impl From<CourseRow> for Course {
+    fn from(row: CourseRow) -> Self {
+        Course {
+            teacher_id: row.teacher_id,
+            id: row.id,
+            name: row.name,
+            time: row.time,
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            room: row.room,
+            capacity: row.capacity,
+            status: row.status.at(row.published_at, Utc::now()),
+            published_at: row.published_at,
+            version: row.version,
+            description: row.description,
+            format: row.format,
+            structure: row.structure,
+            duration: row.duration,
+            price: Money::from_parts(row.price, row.currency),
+            language: row.language,
+            level: row.level,
+            display_price: None,
+        }
+    }
+}
+
+impl Course {
+    //whether the public gets to see the course
+    pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.status.at(self.published_at, now) == CourseStatus::Published
+    }
+
+    //the status as of now, for courses kept in memory rather than read back from a row
+    pub fn refreshed_at(mut self, now: DateTime<Utc>) -> Self {
+        self.status = self.status.at(self.published_at, now);
+        self
+    }
+
+    pub fn in_currency(mut self, rates: &ExchangeRates, currency: Option<Currency>) -> Self {
+        self.display_price = match (self.price, currency) {
+            (Some(price), Some(currency)) => rates.convert(price, currency),
+            _ => None,
+        };
+        self
+    }
 }
 
 //post course to database
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
 pub struct CreateCourse {
     pub teacher_id: i32,
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
     pub name: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[validate(length(min = 1, max = 50, message = "must be 1 to 50 characters"))]
+    pub room: Option<String>,
+    #[validate(range(min = 1, max = 10000, message = "must be between 1 and 10000"))]
+    pub capacity: Option<i32>,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
     pub description: Option<String>,
-    pub format: Option<String>,
+    pub format: Option<CourseFormat>,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
     pub structure: Option<String>,
+    #[validate(length(max = 30, message = "must be at most 30 characters"))]
     pub duration: Option<String>,
-    pub price: Option<i32>,
-    pub language: Option<String>,
-    pub level: Option<String>,
+    #[validate(custom = "non_negative")]
+    pub price: Option<Money>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
 }
 
 impl TryFrom<web::Json<CreateCourse>> for CreateCourse {
     type Error = MyError;
 
     fn try_from(course: web::Json<CreateCourse>) -> Result<Self, Self::Error> {
-        Ok(CreateCourse {
-            teacher_id: course.teacher_id,
-            name: course.name.clone(),
-            description: course.description.clone(),
-            format: course.format.clone(),
-            structure: course.structure.clone(),
-            duration: course.duration.clone(),
-            price: course.price,
-            language: course.language.clone(),
-            level: course.level.clone(),
-        })
+        let course = course.into_inner();
+        let errors = interval_errors(course.starts_at, course.ends_at);
+        validated(course, errors)
     }
 }
 
 //update course
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Component, Validate)]
 pub struct UpdateCourse {
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
     pub name: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[validate(length(min = 1, max = 50, message = "must be 1 to 50 characters"))]
+    pub room: Option<String>,
+    #[validate(range(min = 1, max = 10000, message = "must be between 1 and 10000"))]
+    pub capacity: Option<i32>,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
     pub description: Option<String>,
-    pub format: Option<String>,
+    pub format: Option<CourseFormat>,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
     pub structure: Option<String>,
+    #[validate(length(max = 30, message = "must be at most 30 characters"))]
     pub duration: Option<String>,
-    pub price: Option<i32>,
-    pub language: Option<String>,
-    pub level: Option<String>,
+    #[validate(custom = "non_negative")]
+    pub price: Option<Money>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
+}
+
+impl TryFrom<web::Json<UpdateCourse>> for UpdateCourse {
+    type Error = MyError;
+
+    fn try_from(course: web::Json<UpdateCourse>) -> Result<Self, Self::Error> {
+        validated(course.into_inner(), None)
+    }
+}
+
+fn non_negative(price: &Money) -> Result<(), ValidationError> {
+    if price.amount < 0 {
+        let mut err = ValidationError::new("range");
+        err.message = Some("must not be negative".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+impl UpdateCourse {
+    //PUT replaces the course: every field exactly as given, None clears it;
+    //the interval is checked by Booking::of
+    pub fn replace(self, course: Course) -> Course {
+        Course {
+            name: self.name,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            room: self.room,
+            capacity: self.capacity,
+            description: self.description,
+            format: self.format,
+            structure: self.structure,
+            duration: self.duration,
+            price: self.price,
+            language: self.language,
+            level: self.level,
+            display_price: None,
+            ..course
+        }
+    }
 }
 
-impl From<web::Json<UpdateCourse>> for UpdateCourse {
-    fn from(course: web::Json<UpdateCourse>) -> Self {
+//the editable fields of a course as they stand, what a merge patch applies to
+impl From<&Course> for UpdateCourse {
+    fn from(course: &Course) -> Self {
         UpdateCourse {
             name: course.name.clone(),
+            starts_at: course.starts_at,
+            ends_at: course.ends_at,
+            room: course.room.clone(),
+            capacity: course.capacity,
             description: course.description.clone(),
-            format: course.format.clone(),
+            format: course.format,
             structure: course.structure.clone(),
             duration: course.duration.clone(),
             price: course.price,
-            language: course.language.clone(),
-            level: course.level.clone(),
+            language: course.language,
+            level: course.level,
         }
     }
+}
+
+impl Course {
+    //the course with a merge patch applied, the interval is checked by Booking::of
+    pub fn patched(self, patch: &MergePatch) -> Result<Course, MyError> {
+        let fields = patch.apply(&UpdateCourse::from(&self))?;
+        Ok(fields.replace(self))
+    }
+}
+
+//query string of GET /courses/{teacher_id}/{course_id}
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct CourseDetailQuery {
+    //adds the price converted to this currency
+    pub display_currency: Option<Currency>,
+    //`outline` embeds the modules and lessons
+    pub include: Option<String>,
+}
+
+impl CourseDetailQuery {
+    pub fn wants_outline(&self) -> Result<bool, MyError> {
+        match self.include.as_deref() {
+            None => Ok(false),
+            Some("outline") => Ok(true),
+            Some(other) => Err(MyError::Validation(vec![FieldError::new(
+                "include",
+                format!("Unknown include '{}', expected outline", other),
+            )])),
+        }
+    }
+}
+
+//query string of GET /courses/{teacher_id}
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CourseQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<CourseLevel>,
+    //in minor units, only meaningful together with currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_currency: Option<Currency>,
+    //only honoured for the course's own teacher, everyone else sees published courses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CourseStatus>,
+    //set by the handler for the course's own teacher, never read from the query string
+    #[serde(skip)]
+    pub include_unpublished: bool,
+}
+
+impl CourseQuery {
+    pub fn page_bounds(&self) -> (u32, u32) {
+        page_bounds(self.page, self.per_page)
+    }
+
+    //the status the listing is restricted to, if any
+    pub fn status_filter(&self) -> Option<CourseStatus> {
+        if self.include_unpublished {
+            self.status
+        } else {
+            Some(CourseStatus::Published)
+        }
+    }
+
+    //ORDER BY clause, only whitelisted columns ever reach the sql
+    pub fn order_by(&self) -> Result<String, MyError> {
+        let column = match self.sort.as_deref() {
+            None => "id",
+            Some("name") => "name",
+            Some("time") => "time",
+            Some("price") => "price",
+            Some(other) => {
+                return Err(MyError::Validation(vec![FieldError::new(
+                    "sort",
+                    format!("Unknown sort '{}', expected name, time or price", other),
+                )]))
+            }
+        };
+        let order = order_sql(&self.order)?;
+        Ok(format!("ORDER BY {} {}, id {}", column, order, order))
+    }
+
+    //url of another page of the same listing
+    pub fn link(&self, path: &str, page: u32) -> String {
+        let mut query = self.clone();
+        query.page = Some(page);
+        query.per_page = Some(self.page_bounds().1);
+        format!("{}?{}", path, serde_urlencoded::to_string(&query).unwrap_or_default())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+
+//a student taking a course
+#[derive(Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct Enrollment {
+    pub id: i32,
+    pub student_id: i32,
+    pub course_id: i32,
+    pub enrolled_at: Option<DateTime<Utc>>,
+}
+
+//enroll a student into the course given in the path
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct CreateEnrollment {
+    pub student_id: i32,
+}
+
+impl From<web::Json<CreateEnrollment>> for CreateEnrollment {
+    fn from(enrollment: web::Json<CreateEnrollment>) -> Self {
+        CreateEnrollment {
+            student_id: enrollment.student_id,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::errors::MyError;
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{FromRequest, HttpRequest, HttpResponse};
+use serde::Serialize;
+use std::future::{ready, Ready};
+
+//teachers and courses carry a version that every write bumps, their ETag is that version quoted
+
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+//the versions named by an If-Match or If-None-Match value, None for `*`,
+//weak tags only count when `weak` is set since If-Match compares strongly
+fn versions(value: &str, weak: bool) -> Option<Vec<i32>> {
+    if value.trim() == "*" {
+        return None;
+    }
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter_map(|tag| match tag.strip_prefix("W/") {
+                Some(tag) if weak => Some(tag),
+                Some(_) => None,
+                None => Some(tag),
+            })
+            .filter_map(|tag| tag.strip_prefix('"')?.strip_suffix('"')?.parse().ok())
+            .collect(),
+    )
+}
+
+//If-Match of a PUT or DELETE, required so an editor working from a stale copy cannot overwrite newer changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    //`*`, whatever the current version is
+    Any,
+    Versions(Vec<i32>),
+}
+
+impl IfMatch {
+    pub fn parse(value: &str) -> Self {
+        versions(value, false).map_or(IfMatch::Any, IfMatch::Versions)
+    }
+
+    //412 unless the stored version is one the client has seen
+    pub fn check(&self, version: i32) -> Result<(), MyError> {
+        match self {
+            IfMatch::Versions(versions) if !versions.contains(&version) => Err(MyError::PreconditionFailed(format!(
+                "The record has changed since it was read, its ETag is now {}",
+                etag(version)
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromRequest for IfMatch {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.headers()
+                .get(header::IF_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(IfMatch::parse)
+                .ok_or_else(|| MyError::PreconditionRequired(
+                    "Send the ETag you last read in If-Match, or * to overwrite whatever is stored".into(),
+                )),
+        )
+    }
+}
+
+//whether the copy named in If-None-Match is still current
+pub fn is_fresh(req: &HttpRequest, version: i32) -> bool {
+    req.headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| versions(value, true).is_none_or(|versions| versions.contains(&version)))
+}
+
+//200 with the body and its ETag, or an empty 304 when the client already has this version
+pub fn tagged<T: Serialize>(req: &HttpRequest, version: i32, body: &T) -> HttpResponse {
+    if is_fresh(req, version) {
+        return HttpResponse::NotModified().insert_header((header::ETAG, etag(version))).finish();
+    }
+    HttpResponse::Ok().insert_header((header::ETAG, etag(version))).json(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn if_match_compares_strongly() {
+        assert_eq!(IfMatch::parse("*"), IfMatch::Any);
+        assert_eq!(IfMatch::parse("\"3\", W/\"4\", \"x\""), IfMatch::Versions(vec![3]));
+        assert!(IfMatch::parse("\"3\"").check(3).is_ok());
+        assert!(matches!(IfMatch::parse("\"3\"").check(4), Err(MyError::PreconditionFailed(_))));
+    }
+
+    #[test]
+    fn if_none_match_compares_weakly() {
+        let req = TestRequest::default().insert_header((header::IF_NONE_MATCH, "W/\"2\"")).to_http_request();
+        assert!(is_fresh(&req, 2));
+        assert!(!is_fresh(&req, 3));
+        assert!(!is_fresh(&TestRequest::default().to_http_request(), 2));
+    }
+}
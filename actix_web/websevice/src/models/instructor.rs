@@ -0,0 +1,125 @@
+use crate::errors::{FieldError, MyError};
+use crate::models::course::Course;
+use crate::models::teacher::Teacher;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use std::str::FromStr;
+
+//what a teacher does on a course, the lead is always the course's own teacher_id
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructorRole {
+    Lead,
+    CoTeacher,
+    Ta,
+}
+
+impl InstructorRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstructorRole::Lead => "lead",
+            InstructorRole::CoTeacher => "co_teacher",
+            InstructorRole::Ta => "ta",
+        }
+    }
+}
+
+impl FromStr for InstructorRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lead" => Ok(InstructorRole::Lead),
+            "co_teacher" => Ok(InstructorRole::CoTeacher),
+            "ta" => Ok(InstructorRole::Ta),
+            other => Err(format!("Unknown instructor role '{}'", other)),
+        }
+    }
+}
+
+//a teacher on a course
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Component)]
+pub struct CourseInstructor {
+    pub course_id: i32,
+    pub teacher_id: i32,
+    pub name: String,
+    pub role: InstructorRole,
+    //unset for the lead, who has the course since it was created or transferred
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+impl CourseInstructor {
+    pub fn lead(course: &Course, teacher: &Teacher) -> Self {
+        CourseInstructor {
+            course_id: course.id,
+            teacher_id: teacher.id,
+            name: teacher.name.clone(),
+            role: InstructorRole::Lead,
+            added_at: None,
+        }
+    }
+}
+
+//course_instructor row with the teacher's name, role still a plain string
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct InstructorRow {
+    pub course_id: i32,
+    pub teacher_id: i32,
+    pub name: String,
+    pub role: String,
+    pub added_at: DateTime<Utc>,
+}
+
+impl TryFrom<InstructorRow> for CourseInstructor {
+    type Error = MyError;
+
+    fn try_from(row: InstructorRow) -> Result<Self, Self::Error> {
+        Ok(CourseInstructor {
+            course_id: row.course_id,
+            teacher_id: row.teacher_id,
+            name: row.name,
+            role: row.role.parse().map_err(MyError::DBError)?,
+            added_at: Some(row.added_at),
+        })
+    }
+}
+
+//add a teacher to the course given in the path
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct AddInstructor {
+    pub teacher_id: i32,
+    //co_teacher or ta
+    pub role: InstructorRole,
+}
+
+impl AddInstructor {
+    //`lead_id` is the course's teacher, who is on it already
+    pub fn check(&self, lead_id: i32) -> Result<(), MyError> {
+        if self.role == InstructorRole::Lead {
+            return Err(MyError::Validation(vec![FieldError::new(
+                "role",
+                "A course has one lead, its teacher, transfer the course to change it",
+            )]));
+        }
+        if self.teacher_id == lead_id {
+            return Err(MyError::Conflict("The teacher already leads this course".into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_co_teachers_and_assistants_are_added() {
+        let add = |teacher_id, role| AddInstructor { teacher_id, role };
+
+        assert!(add(2, InstructorRole::Ta).check(1).is_ok());
+        assert!(matches!(add(2, InstructorRole::Lead).check(1), Err(MyError::Validation(_))));
+        assert!(matches!(add(1, InstructorRole::CoTeacher).check(1), Err(MyError::Conflict(_))));
+        assert_eq!("co_teacher".parse::<InstructorRole>(), Ok(InstructorRole::CoTeacher));
+    }
+}
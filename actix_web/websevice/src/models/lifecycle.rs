@@ -0,0 +1,149 @@
+use crate::errors::MyError;
+use crate::models::course::Course;
+use crate::models::meta::CourseStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+
+impl CourseStatus {
+    //a scheduled course is published once its time has come, nothing has to run at that moment
+    pub fn at(self, published_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> CourseStatus {
+        match (self, published_at) {
+            (CourseStatus::Scheduled, Some(at)) if at <= now => CourseStatus::Published,
+            (status, _) => status,
+        }
+    }
+}
+
+//body of POST /courses/{teacher_id}/{course_id}/publish
+#[derive(Deserialize, Debug, Clone, Default, Component)]
+pub struct PublishCourse {
+    //publish later instead of right away, a time already past publishes now
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+impl PublishCourse {
+    //the body is optional, an empty one publishes right away
+    pub fn from_body(body: &[u8]) -> Result<Self, MyError> {
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(PublishCourse::default());
+        }
+        serde_json::from_slice(body)
+            .map_err(|err| MyError::InvalidInput(format!("Please provide valid Json input: {}", err)))
+    }
+}
+
+//the moves a course can make: draft -> (scheduled ->) published -> archived
+#[derive(Debug, Clone, Copy)]
+pub enum Transition {
+    Publish { at: Option<DateTime<Utc>> },
+    Archive,
+}
+
+impl Transition {
+    //the status and publish time the course ends up with, or a conflict when it cannot move
+    pub fn apply(&self, course: &Course, now: DateTime<Utc>) -> Result<(CourseStatus, Option<DateTime<Utc>>), MyError> {
+        let current = course.status.at(course.published_at, now);
+        match (self, current) {
+            (Transition::Publish { at: Some(at) }, CourseStatus::Draft | CourseStatus::Scheduled) if *at > now => {
+                Ok((CourseStatus::Scheduled, Some(*at)))
+            }
+            (Transition::Publish { .. }, CourseStatus::Draft | CourseStatus::Scheduled) => {
+                Ok((CourseStatus::Published, Some(now)))
+            }
+            (Transition::Archive, CourseStatus::Published) => Ok((CourseStatus::Archived, course.published_at)),
+            (Transition::Publish { .. }, current) => Err(MyError::Conflict(format!(
+                "Only draft or scheduled courses can be published, this one is {}", current.as_str()
+            ))),
+            (Transition::Archive, current) => Err(MyError::Conflict(format!(
+                "Only published courses can be archived, this one is {}", current.as_str()
+            ))),
+        }
+    }
+}
+
+//one recorded move of a course between statuses
+#[derive(Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct StatusChange {
+    pub id: i32,
+    pub course_id: i32,
+    pub from_status: CourseStatus,
+    pub to_status: CourseStatus,
+    //the user who made the change, unset once that account is deleted
+    pub changed_by: Option<i32>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    fn course(status: CourseStatus, published_at: Option<DateTime<Utc>>) -> Course {
+        Course {
+            teacher_id: 1,
+            id: 1,
+            name: "Rust".into(),
+            time: None,
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            status,
+            published_at,
+            version: 1,
+            description: None,
+            format: None,
+            structure: None,
+            duration: None,
+            price: None,
+            language: None,
+            level: None,
+            display_price: None,
+        }
+    }
+
+    #[test]
+    fn scheduled_course_goes_live_on_time() {
+        let at = now() + Duration::hours(1);
+
+        assert_eq!(CourseStatus::Scheduled.at(Some(at), now()), CourseStatus::Scheduled);
+        assert_eq!(CourseStatus::Scheduled.at(Some(at), at), CourseStatus::Published);
+        assert_eq!(CourseStatus::Draft.at(Some(at), at), CourseStatus::Draft);
+    }
+
+    #[test]
+    fn publish_now_or_later() {
+        let draft = course(CourseStatus::Draft, None);
+        let later = now() + Duration::days(1);
+
+        let published = Transition::Publish { at: None }.apply(&draft, now()).unwrap();
+        assert_eq!(published, (CourseStatus::Published, Some(now())));
+        let scheduled = Transition::Publish { at: Some(later) }.apply(&draft, now()).unwrap();
+        assert_eq!(scheduled, (CourseStatus::Scheduled, Some(later)));
+        let past = Transition::Publish { at: Some(now() - Duration::days(1)) }.apply(&draft, now()).unwrap();
+        assert_eq!(past.0, CourseStatus::Published);
+    }
+
+    #[test]
+    fn archived_courses_stay_archived() {
+        let draft = course(CourseStatus::Draft, None);
+        let archived = course(CourseStatus::Archived, Some(now()));
+        let due = course(CourseStatus::Scheduled, Some(now() - Duration::hours(1)));
+
+        assert!(matches!(Transition::Archive.apply(&draft, now()), Err(MyError::Conflict(_))));
+        assert!(matches!(Transition::Publish { at: None }.apply(&archived, now()), Err(MyError::Conflict(_))));
+        assert_eq!(Transition::Archive.apply(&due, now()).unwrap().0, CourseStatus::Archived);
+    }
+
+    #[test]
+    fn publish_body_is_optional() {
+        assert!(PublishCourse::from_body(b"").unwrap().publish_at.is_none());
+        assert!(PublishCourse::from_body(b"{\"publish_at\": \"2022-03-02T09:00:00Z\"}").unwrap().publish_at.is_some());
+        assert!(matches!(PublishCourse::from_body(b"{"), Err(MyError::InvalidInput(_))));
+    }
+}
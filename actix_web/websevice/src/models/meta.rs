@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use utoipa::Component;
+
+//closed vocabularies for course fields, stored as their code in a text column
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum CourseLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum CourseFormat {
+    Online,
+    Offline,
+    Hybrid,
+    SelfPaced,
+}
+
+//ISO 639-1 language of instruction
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum Language {
+    Zh,
+    En,
+    Ja,
+    Ko,
+    Fr,
+    De,
+    Es,
+    Pt,
+    It,
+    Ru,
+}
+
+//ISO 4217 currency of a price
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "UPPERCASE", try_from = "String")]
+pub enum Currency {
+    Cny,
+    Usd,
+    Eur,
+    Gbp,
+    Jpy,
+    Hkd,
+    Krw,
+}
+
+//how often a course session repeats, maps onto an iCalendar RRULE FREQ
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+//where a course is in its lifecycle, only published courses are listed publicly
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[serde(rename_all = "snake_case", try_from = "String")]
+pub enum CourseStatus {
+    Draft,
+    Scheduled,
+    Published,
+    Archived,
+}
+
+impl Currency {
+    //digits after the decimal point, prices are stored in these minor units
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Currency::Jpy | Currency::Krw => 0,
+            _ => 2,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Cny | Currency::Jpy => "¥",
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+            Currency::Hkd => "HK$",
+            Currency::Krw => "₩",
+        }
+    }
+}
+
+//one entry of a GET /meta/* list, `value` is what the api accepts and returns
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct MetaOption {
+    #[component(example = "intermediate")]
+    pub value: &'static str,
+    #[component(example = "Intermediate")]
+    pub label: &'static str,
+}
+
+//code, label and the other spellings found in older data, all matched ignoring case
+macro_rules! vocabulary {
+    ($name:ident, $what:literal, { $($variant:ident => $code:literal, $label:literal, [$($alias:literal),*]);+ $(;)? }) => {
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $code),+
+                }
+            }
+
+            pub fn label(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $label),+
+                }
+            }
+
+            pub fn options() -> Vec<MetaOption> {
+                Self::ALL
+                    .iter()
+                    .map(|v| MetaOption { value: v.as_str(), label: v.label() })
+                    .collect()
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let wanted = s.trim().to_lowercase();
+                $(
+                    if wanted == $code.to_lowercase() || wanted == $label.to_lowercase() $(|| wanted == $alias)* {
+                        return Ok($name::$variant);
+                    }
+                )+
+                let codes: Vec<&str> = Self::ALL.iter().map(|v| v.as_str()).collect();
+                Err(format!("Unknown {} '{}', expected one of {}", $what, s, codes.join(", ")))
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                value.parse()
+            }
+        }
+
+        impl<DB: sqlx::Database> sqlx::Type<DB> for $name
+        where
+            str: sqlx::Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <str as sqlx::Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <str as sqlx::Type<DB>>::compatible(ty)
+            }
+        }
+
+        impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for $name
+        where
+            &'q str: sqlx::Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self, buf: &mut <DB as sqlx::database::HasArguments<'q>>::ArgumentBuffer
+            ) -> sqlx::encode::IsNull {
+                <&'q str as sqlx::Encode<'q, DB>>::encode_by_ref(&self.as_str(), buf)
+            }
+        }
+
+        impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for $name
+        where
+            &'r str: sqlx::Decode<'r, DB>,
+        {
+            fn decode(
+                value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                let text = <&'r str as sqlx::Decode<'r, DB>>::decode(value)?;
+                Ok(text.parse()?)
+            }
+        }
+    };
+}
+
+vocabulary!(CourseLevel, "level", {
+    Beginner => "beginner", "Beginner", ["basic", "easy", "entry", "初级", "入门"];
+    Intermediate => "intermediate", "Intermediate", ["medium", "中级"];
+    Advanced => "advanced", "Advanced", ["hard", "expert", "高级"];
+});
+
+vocabulary!(CourseFormat, "format", {
+    Online => "online", "Online", ["remote", "线上", "在线"];
+    Offline => "offline", "Offline", ["in person", "in-person", "classroom", "线下", "面授"];
+    Hybrid => "hybrid", "Hybrid", ["blended", "混合"];
+    SelfPaced => "self_paced", "Self-paced", ["self paced", "recorded", "录播"];
+});
+
+vocabulary!(Language, "language", {
+    Zh => "zh", "Chinese", ["zh-cn", "mandarin", "中文", "汉语"];
+    En => "en", "English", ["英语", "英文"];
+    Ja => "ja", "Japanese", ["日语", "日本語"];
+    Ko => "ko", "Korean", ["韩语", "한국어"];
+    Fr => "fr", "French", ["法语", "français"];
+    De => "de", "German", ["德语", "deutsch"];
+    Es => "es", "Spanish", ["西班牙语", "español"];
+    Pt => "pt", "Portuguese", ["葡萄牙语", "português"];
+    It => "it", "Italian", ["意大利语", "italiano"];
+    Ru => "ru", "Russian", ["俄语", "русский"];
+});
+
+vocabulary!(Currency, "currency", {
+    Cny => "CNY", "Chinese yuan", ["rmb", "yuan", "人民币", "元"];
+    Usd => "USD", "US dollar", ["dollar", "美元"];
+    Eur => "EUR", "Euro", ["欧元"];
+    Gbp => "GBP", "Pound sterling", ["pound", "英镑"];
+    Jpy => "JPY", "Japanese yen", ["yen", "日元"];
+    Hkd => "HKD", "Hong Kong dollar", ["港币"];
+    Krw => "KRW", "South Korean won", ["won", "韩元"];
+});
+
+vocabulary!(Frequency, "frequency", {
+    Daily => "daily", "Daily", ["每天"];
+    Weekly => "weekly", "Weekly", ["每周"];
+    Monthly => "monthly", "Monthly", ["每月"];
+});
+
+vocabulary!(CourseStatus, "status", {
+    Draft => "draft", "Draft", ["草稿"];
+    Scheduled => "scheduled", "Scheduled", ["定时发布"];
+    Published => "published", "Published", ["已发布"];
+    Archived => "archived", "Archived", ["已归档"];
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_spellings_are_normalised() {
+        for legacy in ["Medium", "medium", "Intermediate", "中级"] {
+            assert_eq!(legacy.parse::<CourseLevel>(), Ok(CourseLevel::Intermediate));
+        }
+        assert_eq!("Chinese".parse::<Language>(), Ok(Language::Zh));
+        assert_eq!("self-paced".parse::<CourseFormat>(), Ok(CourseFormat::SelfPaced));
+        assert!("Klingon".parse::<Language>().is_err());
+        assert_eq!("rmb".parse::<Currency>(), Ok(Currency::Cny));
+    }
+
+    #[test]
+    fn serialized_as_code() {
+        let level: CourseLevel = serde_json::from_str("\"Medium\"").unwrap();
+        assert_eq!(serde_json::to_string(&level).unwrap(), "\"intermediate\"");
+        assert_eq!(serde_json::to_string(&CourseFormat::SelfPaced).unwrap(), "\"self_paced\"");
+        assert_eq!(serde_json::to_string(&Currency::Cny).unwrap(), "\"CNY\"");
+    }
+}
@@ -1,2 +1,23 @@
+pub mod calendar;
 pub mod course;
-pub mod teacher;
\ No newline at end of file
+pub mod enrollment;
+pub mod etag;
+pub mod instructor;
+pub mod lifecycle;
+pub mod meta;
+pub mod money;
+pub mod pagination;
+pub mod patch;
+pub mod revision;
+pub mod seat;
+pub mod search;
+pub mod session;
+pub mod slug;
+pub mod student;
+pub mod syllabus;
+pub mod teacher;
+pub mod timetable;
+pub mod transfer;
+pub mod trash;
+pub mod user;
+pub mod validation;
\ No newline at end of file
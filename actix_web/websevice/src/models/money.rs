@@ -0,0 +1,116 @@
+use crate::models::meta::Currency;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::Component;
+
+//an amount in the minor units of its currency, 9900 CNY is ¥99.00
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct Money {
+    #[component(example = 9900)]
+    pub amount: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+
+    //from the price and currency columns, a price needs both
+    pub fn from_parts(amount: Option<i64>, currency: Option<Currency>) -> Option<Self> {
+        Some(Money::new(amount?, currency?))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //integer division keeps amounts past 2^53 exact
+        let digits = self.currency.minor_units();
+        let unit = 10u64.pow(digits);
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let amount = self.amount.unsigned_abs();
+        write!(f, "{}{}{}", sign, self.currency.symbol(), amount / unit)?;
+        if digits > 0 {
+            write!(f, ".{:0width$}", amount % unit, width = digits as usize)?;
+        }
+        Ok(())
+    }
+}
+
+//how much one unit of the shared base is worth in each currency, read from
+//EXCHANGE_RATES as e.g. "CNY=1,USD=0.157,EUR=0.144"; only for display
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeRates {
+    rates: HashMap<Currency, f64>,
+}
+
+impl ExchangeRates {
+    //None when either currency has no rate
+    pub fn convert(&self, money: Money, to: Currency) -> Option<Money> {
+        if money.currency == to {
+            return Some(money);
+        }
+        let from_rate = self.rates.get(&money.currency)?;
+        let to_rate = self.rates.get(&to)?;
+
+        let major = money.amount as f64 / 10f64.powi(money.currency.minor_units() as i32);
+        let converted = major / from_rate * to_rate;
+        let amount = (converted * 10f64.powi(to.minor_units() as i32)).round() as i64;
+        Some(Money::new(amount, to))
+    }
+}
+
+impl FromStr for ExchangeRates {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rates = HashMap::new();
+        for entry in s.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (currency, rate) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected CURRENCY=rate, got '{}'", entry))?;
+            let currency: Currency = currency.parse()?;
+            let rate: f64 = rate
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid rate '{}' for {}", rate, currency.as_str()))?;
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(format!("Rate for {} must be positive", currency.as_str()));
+            }
+            rates.insert(currency, rate);
+        }
+        Ok(ExchangeRates { rates })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_minor_units() {
+        let rates: ExchangeRates = "CNY=1, USD=0.15, JPY=17".parse().unwrap();
+
+        let price = Money::new(10000, Currency::Cny);
+        assert_eq!(rates.convert(price, Currency::Usd), Some(Money::new(1500, Currency::Usd)));
+        assert_eq!(rates.convert(price, Currency::Jpy), Some(Money::new(1700, Currency::Jpy)));
+        assert_eq!(rates.convert(price, Currency::Eur), None);
+    }
+
+    #[test]
+    fn displays_with_symbol() {
+        assert_eq!(Money::new(9900, Currency::Cny).to_string(), "¥99.00");
+        assert_eq!(Money::new(1200, Currency::Jpy).to_string(), "¥1200");
+        assert_eq!(Money::new(-1205, Currency::Usd).to_string(), "-$12.05");
+        assert_eq!(Money::new(9_007_199_254_740_993, Currency::Usd).to_string(), "$90071992547409.93");
+    }
+
+    #[test]
+    fn rejects_bad_rates() {
+        assert!("USD".parse::<ExchangeRates>().is_err());
+        assert!("USD=abc".parse::<ExchangeRates>().is_err());
+        assert!("USD=0".parse::<ExchangeRates>().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::errors::{FieldError, MyError};
+use serde::Serialize;
+
+pub const DEFAULT_PER_PAGE: u32 = 20;
+pub const MAX_PER_PAGE: u32 = 100;
+
+//response envelope for list endpoints
+#[derive(Serialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+//page and per_page as given by the client, clamped to sane values
+pub fn page_bounds(page: Option<u32>, per_page: Option<u32>) -> (u32, u32) {
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    (page, per_page)
+}
+
+impl<T> Page<T> {
+    //`link` builds the url of another page with the same filters
+    pub fn new<F>(items: Vec<T>, total: i64, page: u32, per_page: u32, link: F) -> Self
+    where
+        F: Fn(u32) -> String,
+    {
+        let last_page = (total.max(0) as u64).div_ceil(per_page as u64) as u32;
+        let next = if page < last_page { Some(link(page + 1)) } else { None };
+        let prev = if page > 1 { Some(link((page - 1).min(last_page.max(1)))) } else { None };
+
+        Page {
+            items,
+            total,
+            page,
+            per_page,
+            next,
+            prev,
+        }
+    }
+}
+
+//sort direction shared by all list endpoints
+pub fn order_sql(order: &Option<String>) -> Result<&'static str, MyError> {
+    match order.as_deref() {
+        None | Some("asc") => Ok("ASC"),
+        Some("desc") => Ok("DESC"),
+        Some(other) => Err(MyError::Validation(vec![FieldError::new(
+            "order",
+            format!("Unknown order '{}', expected asc or desc", other),
+        )])),
+    }
+}
+
+//LIKE pattern matching `term` anywhere, its own % and _ taken literally; use with ESCAPE '!'
+pub fn contains_pattern(term: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in term.chars() {
+        if matches!(c, '!' | '%' | '_') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_wildcards_in_a_term_are_escaped() {
+        assert_eq!(contains_pattern("Kong"), "%Kong%");
+        assert_eq!(contains_pattern("100%_a!"), "%100!%!_a!!%");
+    }
+}
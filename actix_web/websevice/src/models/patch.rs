@@ -0,0 +1,93 @@
+use crate::errors::{FieldError, MyError};
+use crate::models::validation::validated;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use validator::Validate;
+
+//body of a PATCH, an RFC 7396 merge patch: a member set to null is cleared,
+//a member left out keeps its value and objects such as price merge member by member
+#[derive(Debug, Clone)]
+pub struct MergePatch(pub Value);
+
+impl MergePatch {
+    //`current` with the patch merged in, validated as a whole like a PUT body
+    pub fn apply<T>(&self, current: &T) -> Result<T, MyError>
+    where
+        T: Serialize + DeserializeOwned + Validate,
+    {
+        let patch = match &self.0 {
+            Value::Object(patch) => patch,
+            _ => return Err(MyError::InvalidInput("A merge patch must be a JSON object".into())),
+        };
+
+        let mut target = serde_json::to_value(current).unwrap_or_default();
+        //status, version and the ids have their own endpoints, a patch naming them is refused rather than ignored
+        let unknown: Vec<FieldError> = patch
+            .keys()
+            .filter(|field| target.get(field.as_str()).is_none())
+            .map(|field| FieldError::new(field, "cannot be changed by a patch"))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(MyError::Validation(unknown));
+        }
+
+        merge(&mut target, &self.0);
+        let patched = serde_json::from_value(target)
+            .map_err(|err| MyError::InvalidInput(format!("Invalid merge patch: {}", err)))?;
+        validated(patched, None)
+    }
+}
+
+//the MergePatch algorithm of RFC 7396 section 2
+pub fn merge(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+
+    if let Value::Object(target) = target {
+        for (name, value) in patch {
+            if value.is_null() {
+                target.remove(name);
+            } else {
+                merge(target.entry(name.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merge_follows_the_rfc_examples() {
+        let mut target = json!({"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"], "content": "This will be unchanged"});
+
+        merge(&mut target, &json!({"title": "Hello!", "phoneNumber": "+01-123-456-7890",
+            "author": {"familyName": null}, "tags": ["example"]}));
+
+        assert_eq!(target, json!({"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"],
+            "content": "This will be unchanged", "phoneNumber": "+01-123-456-7890"}));
+    }
+
+    #[test]
+    fn merge_replaces_non_objects() {
+        let mut target = json!({"a": "b"});
+        merge(&mut target, &json!(["c"]));
+        assert_eq!(target, json!(["c"]));
+
+        let mut target = json!({"a": "foo"});
+        merge(&mut target, &json!({"a": {"b": "c"}}));
+        assert_eq!(target, json!({"a": {"b": "c"}}));
+    }
+}
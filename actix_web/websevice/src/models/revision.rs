@@ -0,0 +1,239 @@
+use crate::models::course::Course;
+use crate::models::meta::{CourseFormat, CourseLevel, Currency, Language};
+use crate::models::money::Money;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::Component;
+
+//the fields a revision keeps, in the order a diff lists them
+const REVISED_FIELDS: [&str; 12] = [
+    "name", "starts_at", "ends_at", "room", "capacity", "description",
+    "format", "structure", "duration", "price", "language", "level",
+];
+
+//one saved version of a course's editable fields, status changes are kept apart
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct CourseRevision {
+    pub id: i32,
+    pub course_id: i32,
+    //1 for the course as created, one more for every save after it
+    pub revision: i32,
+    pub name: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub room: Option<String>,
+    pub capacity: Option<i32>,
+    pub description: Option<String>,
+    pub format: Option<CourseFormat>,
+    pub structure: Option<String>,
+    pub duration: Option<String>,
+    pub price: Option<Money>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
+    //the user who saved it, unset once that account is deleted
+    pub changed_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+//course_revision row as stored, the price split over price and currency
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RevisionRow {
+    pub id: i32,
+    pub course_id: i32,
+    pub revision: i32,
+    pub name: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub room: Option<String>,
+    pub capacity: Option<i32>,
+    pub description: Option<String>,
+    pub format: Option<CourseFormat>,
+    pub structure: Option<String>,
+    pub duration: Option<String>,
+    pub price: Option<i64>,
+    pub currency: Option<Currency>,
+    pub language: Option<Language>,
+    pub level: Option<CourseLevel>,
+    pub changed_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RevisionRow> for CourseRevision {
+    fn from(row: RevisionRow) -> Self {
+        CourseRevision {
+            id: row.id,
+            course_id: row.course_id,
+            revision: row.revision,
+            name: row.name,
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            room: row.room,
+            capacity: row.capacity,
+            description: row.description,
+            format: row.format,
+            structure: row.structure,
+            duration: row.duration,
+            price: Money::from_parts(row.price, row.currency),
+            language: row.language,
+            level: row.level,
+            changed_by: row.changed_by,
+            created_at: row.created_at,
+        }
+    }
+}
+
+impl CourseRevision {
+    //the course as just saved, for the backends that do not build it in sql
+    pub fn of(course: &Course, id: i32, revision: i32, changed_by: i32, now: DateTime<Utc>) -> Self {
+        CourseRevision {
+            id,
+            course_id: course.id,
+            revision,
+            name: course.name.clone(),
+            starts_at: course.starts_at,
+            ends_at: course.ends_at,
+            room: course.room.clone(),
+            capacity: course.capacity,
+            description: course.description.clone(),
+            format: course.format,
+            structure: course.structure.clone(),
+            duration: course.duration.clone(),
+            price: course.price,
+            language: course.language,
+            level: course.level,
+            changed_by: Some(changed_by),
+            created_at: now,
+        }
+    }
+
+    //the course with every revised field put back, cleared ones included
+    pub fn restore_onto(self, course: Course) -> Course {
+        Course {
+            name: self.name,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            room: self.room,
+            capacity: self.capacity,
+            description: self.description,
+            format: self.format,
+            structure: self.structure,
+            duration: self.duration,
+            price: self.price,
+            language: self.language,
+            level: self.level,
+            display_price: None,
+            ..course
+        }
+    }
+}
+
+//query string of GET /courses/{teacher_id}/{course_id}/revisions/diff
+#[derive(Deserialize, Debug, Clone)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+//one field that differs between two revisions, values as they appear in the revisions
+#[derive(Serialize, Debug, Clone, PartialEq, Component)]
+pub struct FieldChange {
+    #[component(example = "description")]
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    //only the fields that differ, empty when the revisions are the same
+    pub changes: Vec<FieldChange>,
+}
+
+impl RevisionDiff {
+    pub fn between(from: &CourseRevision, to: &CourseRevision) -> Self {
+        let (old, new) = (serde_json::to_value(from), serde_json::to_value(to));
+        let (old, new) = (old.unwrap_or_default(), new.unwrap_or_default());
+
+        let changes = REVISED_FIELDS
+            .iter()
+            .filter(|field| old[**field] != new[**field])
+            .map(|field| FieldChange {
+                field: field.to_string(),
+                from: old[*field].clone(),
+                to: new[*field].clone(),
+            })
+            .collect();
+
+        RevisionDiff { from: from.revision, to: to.revision, changes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::meta::CourseStatus;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn course() -> Course {
+        Course {
+            teacher_id: 1,
+            id: 2,
+            name: "Rust".into(),
+            time: None,
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            status: CourseStatus::Published,
+            published_at: None,
+            version: 1,
+            description: Some("All about Rust".into()),
+            format: None,
+            structure: None,
+            duration: None,
+            price: Some(Money::new(10000, Currency::Cny)),
+            language: Some(Language::Zh),
+            level: None,
+            display_price: None,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn diff_lists_changed_fields_only() {
+        let first = CourseRevision::of(&course(), 10, 1, 1, now());
+        let wiped = Course { description: None, price: Some(Money::new(9900, Currency::Cny)), ..course() };
+        let second = CourseRevision::of(&wiped, 11, 2, 1, now());
+
+        let diff = RevisionDiff::between(&first, &second);
+
+        assert_eq!((diff.from, diff.to), (1, 2));
+        assert_eq!(diff.changes, vec![
+            FieldChange { field: "description".into(), from: json!("All about Rust"), to: Value::Null },
+            FieldChange {
+                field: "price".into(),
+                from: json!({"amount": 10000, "currency": "CNY"}),
+                to: json!({"amount": 9900, "currency": "CNY"}),
+            },
+        ]);
+        assert!(RevisionDiff::between(&first, &first).changes.is_empty());
+    }
+
+    #[test]
+    fn restore_puts_cleared_fields_back() {
+        let first = CourseRevision::of(&course(), 10, 1, 1, now());
+        let wiped = Course { description: None, status: CourseStatus::Archived, ..course() };
+
+        let restored = first.restore_onto(wiped);
+
+        assert_eq!(restored.description.as_deref(), Some("All about Rust"));
+        assert_eq!(restored.status, CourseStatus::Archived);
+    }
+}
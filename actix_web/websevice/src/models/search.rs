@@ -0,0 +1,67 @@
+use crate::errors::{FieldError, MyError};
+use crate::models::course::Course;
+use crate::models::meta::{CourseFormat, CourseLevel, Currency, Language};
+use crate::models::pagination::{page_bounds, Page};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+
+//query string of GET /courses/search
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CourseSearchQuery {
+    pub q: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<CourseLevel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<CourseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_currency: Option<Currency>,
+}
+
+impl CourseSearchQuery {
+    pub fn page_bounds(&self) -> (u32, u32) {
+        page_bounds(self.page, self.per_page)
+    }
+
+    //the search terms, refusing a blank search
+    pub fn terms(&self) -> Result<&str, MyError> {
+        let q = self.q.trim();
+        if q.is_empty() {
+            return Err(MyError::Validation(vec![FieldError::new("q", "Please provide a search term")]));
+        }
+        Ok(q)
+    }
+
+    pub fn link(&self, path: &str, page: u32) -> String {
+        let mut query = self.clone();
+        query.page = Some(page);
+        query.per_page = Some(self.page_bounds().1);
+        format!("{}?{}", path, serde_urlencoded::to_string(&query).unwrap_or_default())
+    }
+}
+
+//how many matching courses carry one value of a field
+#[derive(Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct FacetCount {
+    pub value: Option<String>,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, Component)]
+pub struct SearchFacets {
+    pub language: Vec<FacetCount>,
+    pub level: Vec<FacetCount>,
+    pub format: Vec<FacetCount>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CourseSearchResult {
+    #[serde(flatten)]
+    pub page: Page<Course>,
+    pub facets: SearchFacets,
+}
@@ -0,0 +1,153 @@
+use crate::errors::MyError;
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use std::str::FromStr;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[serde(rename_all = "lowercase")]
+pub enum SeatStatus {
+    Reserved,
+    Waitlisted,
+}
+
+impl SeatStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeatStatus::Reserved => "reserved",
+            SeatStatus::Waitlisted => "waitlisted",
+        }
+    }
+}
+
+impl FromStr for SeatStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reserved" => Ok(SeatStatus::Reserved),
+            "waitlisted" => Ok(SeatStatus::Waitlisted),
+            other => Err(format!("Unknown seat status '{}'", other)),
+        }
+    }
+}
+
+//a student's seat in a course, or their place in the queue for one
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Component)]
+pub struct Reservation {
+    pub id: i32,
+    pub course_id: i32,
+    pub student_id: i32,
+    pub status: SeatStatus,
+    pub reserved_at: Option<DateTime<Utc>>,
+    //1 is next in line, only set while waitlisted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waitlist_position: Option<i32>,
+}
+
+//seat_reservation row as stored, status still a plain string
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ReservationRow {
+    pub id: i32,
+    pub course_id: i32,
+    pub student_id: i32,
+    pub status: String,
+    pub reserved_at: Option<DateTime<Utc>>,
+}
+
+//reserve a seat for a student in the course given in the path
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct CreateReservation {
+    pub student_id: i32,
+}
+
+impl From<web::Json<CreateReservation>> for CreateReservation {
+    fn from(reservation: web::Json<CreateReservation>) -> Self {
+        CreateReservation {
+            student_id: reservation.student_id,
+        }
+    }
+}
+
+//what a new reservation gets with `reserved` seats already taken, no capacity means no limit
+pub fn seat_status(capacity: Option<i32>, reserved: i64) -> SeatStatus {
+    match capacity {
+        Some(capacity) if reserved >= capacity as i64 => SeatStatus::Waitlisted,
+        _ => SeatStatus::Reserved,
+    }
+}
+
+//how many waitlisted reservations can move up, a lowered capacity never takes seats back
+pub fn open_seats(capacity: Option<i32>, reserved: i64) -> i64 {
+    match capacity {
+        Some(capacity) => (capacity as i64 - reserved).max(0),
+        None => i64::MAX,
+    }
+}
+
+//reservations of one course in the order they were made, the queue numbered from 1
+pub fn with_positions(rows: Vec<ReservationRow>) -> Result<Vec<Reservation>, MyError> {
+    let mut position = 0;
+    rows.into_iter()
+        .map(|row| {
+            let status: SeatStatus = row.status.parse().map_err(MyError::DBError)?;
+            let waitlist_position = match status {
+                SeatStatus::Reserved => None,
+                SeatStatus::Waitlisted => {
+                    position += 1;
+                    Some(position)
+                }
+            };
+            Ok(Reservation {
+                id: row.id,
+                course_id: row.course_id,
+                student_id: row.student_id,
+                status,
+                reserved_at: row.reserved_at,
+                waitlist_position,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: i32, status: SeatStatus) -> ReservationRow {
+        ReservationRow {
+            id,
+            course_id: 1,
+            student_id: id,
+            status: status.as_str().into(),
+            reserved_at: None,
+        }
+    }
+
+    #[test]
+    fn last_seat_then_waitlist() {
+        assert_eq!(seat_status(Some(2), 1), SeatStatus::Reserved);
+        assert_eq!(seat_status(Some(2), 2), SeatStatus::Waitlisted);
+        assert_eq!(seat_status(None, 1000), SeatStatus::Reserved);
+
+        assert_eq!(open_seats(Some(3), 1), 2);
+        assert_eq!(open_seats(Some(1), 3), 0);
+    }
+
+    #[test]
+    fn waitlist_is_numbered_in_order() {
+        let rows = vec![
+            row(1, SeatStatus::Waitlisted),
+            row(2, SeatStatus::Reserved),
+            row(3, SeatStatus::Waitlisted),
+        ];
+        let positions: Vec<Option<i32>> = with_positions(rows)
+            .unwrap()
+            .iter()
+            .map(|r| r.waitlist_position)
+            .collect();
+
+        assert_eq!(positions, vec![Some(1), None, Some(2)]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::errors::{FieldError, MyError};
+use crate::models::meta::Frequency;
+use crate::models::validation::validated;
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use validator::{Validate, ValidationError};
+use std::convert::TryFrom;
+
+//a class meeting of a course, repeated when `frequency` is set
+#[derive(Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct CourseSession {
+    pub id: i32,
+    pub course_id: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    //IANA name, repeats keep their wall clock time in this zone
+    #[component(example = "Asia/Shanghai")]
+    pub timezone: String,
+    pub location: Option<String>,
+    pub meeting_url: Option<String>,
+    pub frequency: Option<Frequency>,
+    //how many meetings in total, or the last day they can happen
+    pub occurrences: Option<i32>,
+    pub repeat_until: Option<DateTime<Utc>>,
+}
+
+impl CourseSession {
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct CreateSession {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[validate(custom = "valid_timezone")]
+    pub timezone: String,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
+    pub location: Option<String>,
+    #[validate(url(message = "must be a valid URL"))]
+    pub meeting_url: Option<String>,
+    pub frequency: Option<Frequency>,
+    #[validate(range(min = 1, max = 500, message = "must be between 1 and 500"))]
+    pub occurrences: Option<i32>,
+    pub repeat_until: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<web::Json<CreateSession>> for CreateSession {
+    type Error = MyError;
+
+    fn try_from(session: web::Json<CreateSession>) -> Result<Self, Self::Error> {
+        let session = session.into_inner();
+        let errors = schedule_errors(
+            session.starts_at, session.ends_at, session.frequency, session.occurrences, session.repeat_until
+        );
+        validated(session, errors)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct UpdateSession {
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[validate(custom = "valid_timezone")]
+    pub timezone: Option<String>,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
+    pub location: Option<String>,
+    #[validate(url(message = "must be a valid URL"))]
+    pub meeting_url: Option<String>,
+    pub frequency: Option<Frequency>,
+    #[validate(range(min = 1, max = 500, message = "must be between 1 and 500"))]
+    pub occurrences: Option<i32>,
+    pub repeat_until: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<web::Json<UpdateSession>> for UpdateSession {
+    type Error = MyError;
+
+    fn try_from(session: web::Json<UpdateSession>) -> Result<Self, Self::Error> {
+        validated(session.into_inner(), None)
+    }
+}
+
+impl UpdateSession {
+    //fields left out of the update keep their current value, the result is checked as a whole
+    pub fn apply_to(self, session: CourseSession) -> Result<CourseSession, MyError> {
+        let session = CourseSession {
+            id: session.id,
+            course_id: session.course_id,
+            starts_at: self.starts_at.unwrap_or(session.starts_at),
+            ends_at: self.ends_at.unwrap_or(session.ends_at),
+            timezone: self.timezone.unwrap_or(session.timezone),
+            location: self.location.or(session.location),
+            meeting_url: self.meeting_url.or(session.meeting_url),
+            frequency: self.frequency.or(session.frequency),
+            occurrences: self.occurrences.or(session.occurrences),
+            repeat_until: self.repeat_until.or(session.repeat_until),
+        };
+
+        let errors = schedule_errors(
+            session.starts_at, session.ends_at, session.frequency, session.occurrences, session.repeat_until
+        );
+        if errors.is_empty() {
+            Ok(session)
+        } else {
+            Err(MyError::Validation(errors))
+        }
+    }
+}
+
+fn valid_timezone(timezone: &str) -> Result<(), ValidationError> {
+    if timezone.parse::<Tz>().is_err() {
+        let mut err = ValidationError::new("timezone");
+        err.message = Some("must be an IANA time zone such as Asia/Shanghai".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+//rules that span more than one field
+fn schedule_errors(
+    starts_at: DateTime<Utc>, ends_at: DateTime<Utc>, frequency: Option<Frequency>,
+    occurrences: Option<i32>, repeat_until: Option<DateTime<Utc>>
+) -> Vec<FieldError> {
+    let mut errors = vec![];
+    if ends_at <= starts_at {
+        errors.push(FieldError::new("ends_at", "must be after starts_at"));
+    }
+    if frequency.is_none() && (occurrences.is_some() || repeat_until.is_some()) {
+        errors.push(FieldError::new("frequency", "is required for a repeating session"));
+    }
+    if occurrences.is_some() && repeat_until.is_some() {
+        errors.push(FieldError::new("repeat_until", "cannot be combined with occurrences"));
+    }
+    if repeat_until.is_some_and(|until| until < starts_at) {
+        errors.push(FieldError::new("repeat_until", "must not be before starts_at"));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn schedule_rules() {
+        let start = Utc.with_ymd_and_hms(2022, 3, 1, 11, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2022, 3, 1, 12, 0, 0).unwrap();
+
+        assert!(schedule_errors(start, end, None, None, None).is_empty());
+        assert!(schedule_errors(start, end, Some(Frequency::Weekly), Some(10), None).is_empty());
+
+        let fields: Vec<String> = schedule_errors(end, start, None, Some(3), None)
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["ends_at", "frequency"]);
+    }
+
+    #[test]
+    fn timezone_must_be_iana() {
+        assert!(valid_timezone("Asia/Shanghai").is_ok());
+        assert!(valid_timezone("Beijing time").is_err());
+    }
+}
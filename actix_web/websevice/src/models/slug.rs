@@ -0,0 +1,114 @@
+//slugs are cut to this length before a -2, -3... suffix is added
+const MAX_SLUG_LEN: usize = 80;
+
+//lowercase ascii letters and digits, anything else becomes a single dash
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    let slug = slug.trim_end_matches('-');
+
+    //names without any ascii, e.g. chinese ones, still need something to number
+    if slug.is_empty() {
+        "course".into()
+    } else {
+        slug.into()
+    }
+}
+
+//whether the slug is the base or the base with a number added for a collision
+fn fits(slug: &str, base: &str) -> bool {
+    match slug.strip_prefix(base) {
+        Some("") => true,
+        //-2, -3... but not -02
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|n| n.parse::<u32>().is_ok_and(|number| number >= 2 && number.to_string() == n)),
+        None => false,
+    }
+}
+
+//the slug a course named `name` should be known by, None while its current one still fits.
+//`own` are the slugs the course had before, `taken` the ones starting with the base of any course
+pub fn next_slug(name: &str, current: Option<&str>, own: &[String], taken: &[String]) -> Option<String> {
+    let base = slugify(name);
+    if current.is_some_and(|slug| fits(slug, &base)) {
+        return None;
+    }
+    //renamed back, the old slug is the course's again
+    if let Some(slug) = own.iter().find(|slug| fits(slug, &base)) {
+        return Some(slug.clone());
+    }
+
+    std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{}-{}", base, n)))
+        .find(|slug| !taken.contains(slug))
+}
+
+//LIKE pattern for the slugs next_slug has to know are taken
+pub fn taken_pattern(name: &str) -> (String, String) {
+    let base = slugify(name);
+    let pattern = format!("{}-%", base);
+    (base, pattern)
+}
+
+//where /c/{slug} sends its caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlugLookup {
+    //the course's current slug
+    Course(i32),
+    //an old slug, redirects to the current one
+    Moved(String),
+}
+
+impl SlugLookup {
+    //`current` is the slug the course that ever had `slug` goes by now
+    pub fn of(slug: &str, course_id: i32, current: String) -> Self {
+        if current == slug {
+            SlugLookup::Course(course_id)
+        } else {
+            SlugLookup::Moved(current)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_become_lowercase_dashed_slugs() {
+        assert_eq!(slugify("Intro to Rust!"), "intro-to-rust");
+        assert_eq!(slugify("  C++ & Go -- 2022 "), "c-go-2022");
+        assert_eq!(slugify("Rust 入门"), "rust");
+        assert_eq!(slugify("数据结构"), "course");
+        assert!(slugify(&"a".repeat(200)).len() <= MAX_SLUG_LEN);
+    }
+
+    #[test]
+    fn collisions_are_numbered_and_renames_keep_their_slug() {
+        let taken = vec!["intro-to-rust".to_string(), "intro-to-rust-2".to_string()];
+        assert_eq!(next_slug("Intro to Rust", None, &[], &taken), Some("intro-to-rust-3".into()));
+        assert_eq!(next_slug("Intro to Rust", None, &[], &[]), Some("intro-to-rust".into()));
+
+        //a save that keeps the name keeps the slug, even a numbered one
+        assert_eq!(next_slug("Intro to rust", Some("intro-to-rust-2"), &[], &taken), None);
+        assert_eq!(next_slug("Intro to Rust", Some("intro-to-rust-02"), &[], &taken), Some("intro-to-rust-3".into()));
+
+        //renamed back to an earlier name
+        let own = vec!["intro-to-rust-2".to_string()];
+        assert_eq!(next_slug("Intro to Rust", Some("rust-basics"), &own, &taken), Some("intro-to-rust-2".into()));
+    }
+
+    #[test]
+    fn old_slugs_point_at_the_current_one() {
+        assert_eq!(SlugLookup::of("rust", 3, "rust".into()), SlugLookup::Course(3));
+        assert_eq!(SlugLookup::of("rust", 3, "rust-basics".into()), SlugLookup::Moved("rust-basics".into()));
+    }
+}
@@ -0,0 +1,91 @@
+use actix_web::web;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::Component;
+
+#[derive(Deserialize, Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct Student {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct CreateStudent {
+    pub name: String,
+    pub email: String,
+    pub profile: Option<String>,
+}
+
+//fields left out keep their value, a profile set to null is cleared
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct UpdateStudent {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub profile: Option<Option<String>>,
+}
+
+//tells a member sent as null, Some(None), from one left out, None
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl From<web::Json<CreateStudent>> for CreateStudent {
+    fn from(new_student: web::Json<CreateStudent>) -> Self {
+        CreateStudent {
+            name: new_student.name.clone(),
+            email: new_student.email.clone(),
+            profile: new_student.profile.clone(),
+        }
+    }
+}
+
+impl From<web::Json<UpdateStudent>> for UpdateStudent {
+    fn from(update_student: web::Json<UpdateStudent>) -> Self {
+        UpdateStudent {
+            name: update_student.name.clone(),
+            email: update_student.email.clone(),
+            profile: update_student.profile.clone(),
+        }
+    }
+}
+
+impl UpdateStudent {
+    pub fn apply_to(self, student: Student) -> Student {
+        Student {
+            id: student.id,
+            name: self.name.unwrap_or(student.name),
+            email: self.email.unwrap_or(student.email),
+            profile: self.profile.unwrap_or(student.profile),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn student() -> Student {
+        Student {
+            id: 1,
+            name: "Li Lei".into(),
+            email: "li.lei@example.com".into(),
+            profile: Some("First year".into()),
+        }
+    }
+
+    #[test]
+    fn null_profile_clears_and_missing_profile_keeps() {
+        let update: UpdateStudent = serde_json::from_str(r#"{"profile": null}"#).unwrap();
+        assert_eq!(update.apply_to(student()).profile, None);
+
+        let update: UpdateStudent = serde_json::from_str(r#"{"name": "Han Meimei"}"#).unwrap();
+        let updated = update.apply_to(student());
+        assert_eq!((updated.name.as_str(), updated.profile.as_deref()), ("Han Meimei", Some("First year")));
+    }
+}
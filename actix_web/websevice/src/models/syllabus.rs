@@ -0,0 +1,275 @@
+use crate::errors::{FieldError, MyError};
+use crate::models::course::Course;
+use crate::models::validation::validated;
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use validator::{validate_url, Validate, ValidationError};
+use std::convert::TryFrom;
+
+//a chapter of a course, modules and their lessons are kept in `position` order
+#[derive(Serialize, Debug, Clone, sqlx::FromRow, Component)]
+pub struct CourseModule {
+    pub id: i32,
+    pub course_id: i32,
+    pub title: String,
+    pub summary: Option<String>,
+    pub position: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Component)]
+pub struct Attachment {
+    #[component(example = "slides.pdf")]
+    pub name: String,
+    #[component(example = "https://onederive.com/files/slides.pdf")]
+    pub url: String,
+}
+
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct Lesson {
+    pub id: i32,
+    pub module_id: i32,
+    pub title: String,
+    //Markdown
+    pub body: Option<String>,
+    //estimated time to complete, in minutes
+    pub duration_minutes: Option<i32>,
+    pub attachments: Vec<Attachment>,
+    pub position: i32,
+}
+
+//lesson row as stored, attachments are a json array in a text column
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LessonRow {
+    pub id: i32,
+    pub module_id: i32,
+    pub title: String,
+    pub body: Option<String>,
+    pub duration_minutes: Option<i32>,
+    pub attachments: Option<String>,
+    pub position: i32,
+}
+
+impl From<LessonRow> for Lesson {
+    fn from(row: LessonRow) -> Self {
+        Lesson {
+            id: row.id,
+            module_id: row.module_id,
+            title: row.title,
+            body: row.body,
+            duration_minutes: row.duration_minutes,
+            attachments: row
+                .attachments
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            position: row.position,
+        }
+    }
+}
+
+//what goes into the attachments column
+pub fn attachments_json(attachments: &[Attachment]) -> String {
+    serde_json::to_string(attachments).unwrap_or_else(|_| "[]".into())
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct CreateModule {
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
+    pub title: String,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
+    pub summary: Option<String>,
+}
+
+impl TryFrom<web::Json<CreateModule>> for CreateModule {
+    type Error = MyError;
+
+    fn try_from(module: web::Json<CreateModule>) -> Result<Self, Self::Error> {
+        validated(module.into_inner(), None)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct UpdateModule {
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
+    pub title: Option<String>,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
+    pub summary: Option<String>,
+}
+
+impl TryFrom<web::Json<UpdateModule>> for UpdateModule {
+    type Error = MyError;
+
+    fn try_from(module: web::Json<UpdateModule>) -> Result<Self, Self::Error> {
+        validated(module.into_inner(), None)
+    }
+}
+
+impl UpdateModule {
+    //fields left out of the update keep their current value
+    pub fn apply_to(self, module: CourseModule) -> CourseModule {
+        CourseModule {
+            id: module.id,
+            course_id: module.course_id,
+            title: self.title.unwrap_or(module.title),
+            summary: self.summary.or(module.summary),
+            position: module.position,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct CreateLesson {
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
+    pub title: String,
+    #[validate(length(max = 20000, message = "must be at most 20000 characters"))]
+    pub body: Option<String>,
+    #[validate(range(min = 0, max = 10000, message = "must be between 0 and 10000 minutes"))]
+    pub duration_minutes: Option<i32>,
+    #[serde(default)]
+    #[validate(custom = "valid_attachments")]
+    pub attachments: Vec<Attachment>,
+}
+
+impl TryFrom<web::Json<CreateLesson>> for CreateLesson {
+    type Error = MyError;
+
+    fn try_from(lesson: web::Json<CreateLesson>) -> Result<Self, Self::Error> {
+        validated(lesson.into_inner(), None)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
+pub struct UpdateLesson {
+    #[validate(length(min = 1, max = 140, message = "must be 1 to 140 characters"))]
+    pub title: Option<String>,
+    #[validate(length(max = 20000, message = "must be at most 20000 characters"))]
+    pub body: Option<String>,
+    #[validate(range(min = 0, max = 10000, message = "must be between 0 and 10000 minutes"))]
+    pub duration_minutes: Option<i32>,
+    //replaces the whole list when given
+    #[validate(custom = "valid_attachments")]
+    pub attachments: Option<Vec<Attachment>>,
+}
+
+impl TryFrom<web::Json<UpdateLesson>> for UpdateLesson {
+    type Error = MyError;
+
+    fn try_from(lesson: web::Json<UpdateLesson>) -> Result<Self, Self::Error> {
+        validated(lesson.into_inner(), None)
+    }
+}
+
+fn valid_attachments(attachments: &[Attachment]) -> Result<(), ValidationError> {
+    let valid = attachments
+        .iter()
+        .all(|a| !a.name.trim().is_empty() && a.name.len() <= 140 && validate_url(a.url.as_str()));
+    if !valid {
+        let mut err = ValidationError::new("attachments");
+        err.message = Some("every attachment needs a name and a valid URL".into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+impl UpdateLesson {
+    //fields left out of the update keep their current value
+    pub fn apply_to(self, lesson: Lesson) -> Lesson {
+        Lesson {
+            id: lesson.id,
+            module_id: lesson.module_id,
+            title: self.title.unwrap_or(lesson.title),
+            body: self.body.or(lesson.body),
+            duration_minutes: self.duration_minutes.or(lesson.duration_minutes),
+            attachments: self.attachments.unwrap_or(lesson.attachments),
+            position: lesson.position,
+        }
+    }
+}
+
+//PUT .../order, every module (or every lesson of the module) in its new order
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct Reorder {
+    pub ids: Vec<i32>,
+}
+
+impl Reorder {
+    //a reorder has to name each current id exactly once
+    pub fn check(&self, current: &[i32]) -> Result<(), MyError> {
+        let mut wanted = self.ids.clone();
+        let mut current = current.to_vec();
+        wanted.sort_unstable();
+        current.sort_unstable();
+
+        if wanted != current {
+            return Err(MyError::Validation(vec![FieldError::new(
+                "ids",
+                "must list every id exactly once",
+            )]));
+        }
+        Ok(())
+    }
+}
+
+//a module with its lessons, as embedded in GET course detail
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct ModuleOutline {
+    pub id: i32,
+    pub title: String,
+    pub summary: Option<String>,
+    pub position: i32,
+    pub lessons: Vec<Lesson>,
+}
+
+//modules and lessons both already in position order
+pub fn outline(modules: Vec<CourseModule>, lessons: Vec<Lesson>) -> Vec<ModuleOutline> {
+    modules
+        .into_iter()
+        .map(|module| ModuleOutline {
+            id: module.id,
+            title: module.title,
+            summary: module.summary,
+            position: module.position,
+            lessons: lessons.iter().filter(|l| l.module_id == module.id).cloned().collect(),
+        })
+        .collect()
+}
+
+//GET course detail, the outline only when asked for with ?include=outline
+#[derive(Serialize, Debug, Clone)]
+pub struct CourseDetail {
+    #[serde(flatten)]
+    pub course: Course,
+    //the public /c/{slug} name of the course
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline: Option<Vec<ModuleOutline>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reorder_must_name_every_id_once() {
+        let current = [1, 2, 3];
+        assert!(Reorder { ids: vec![3, 1, 2] }.check(&current).is_ok());
+        assert!(Reorder { ids: vec![3, 1] }.check(&current).is_err());
+        assert!(Reorder { ids: vec![3, 1, 1] }.check(&current).is_err());
+        assert!(Reorder { ids: vec![3, 1, 2, 4] }.check(&current).is_err());
+    }
+
+    #[test]
+    fn unreadable_attachments_become_empty() {
+        let row = LessonRow {
+            id: 1,
+            module_id: 1,
+            title: "Intro".into(),
+            body: None,
+            duration_minutes: None,
+            attachments: Some("not json".into()),
+            position: 1,
+        };
+        assert!(Lesson::from(row).attachments.is_empty());
+    }
+}
@@ -1,43 +1,132 @@
 use actix_web::web;
 use serde::{Deserialize, Serialize};
-#[derive(Deserialize, Serialize, Debug, Clone)]
+use utoipa::Component;
+use validator::Validate;
+use crate::errors::{FieldError, MyError};
+use crate::models::pagination::{order_sql, page_bounds};
+use crate::models::patch::MergePatch;
+use crate::models::validation::validated;
+
+#[derive(Deserialize, Serialize, Debug, Clone, sqlx::FromRow, Component)]
 pub struct Teacher {
     pub id: i32,
     pub name: String,
     pub picture_url: String,
     pub profile: String,
+    //bumped by every write, sent as the ETag
+    pub version: i32,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Component, Validate)]
 pub struct CreateTeacher {
+    #[validate(length(min = 1, max = 100, message = "must be 1 to 100 characters"))]
     pub name: String,
+    #[validate(url(message = "must be a valid url"), length(max = 200, message = "must be at most 200 characters"))]
     pub picture_url: String,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
     pub profile: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Component, Validate)]
 pub struct UpdateTeacher {
+    #[validate(length(min = 1, max = 100, message = "must be 1 to 100 characters"))]
     pub name: Option<String>,
+    #[validate(url(message = "must be a valid url"), length(max = 200, message = "must be at most 200 characters"))]
     pub picture_url: Option<String>,
+    #[validate(length(max = 2000, message = "must be at most 2000 characters"))]
     pub profile: Option<String>,
 }
 
-impl From<web::Json<CreateTeacher>> for CreateTeacher {
-    fn from(new_teacher: web::Json<CreateTeacher>) -> Self {
-        CreateTeacher {
-            name: new_teacher.name.clone(),
-            picture_url: new_teacher.picture_url.clone(),
-            profile: new_teacher.profile.clone(),
+impl TryFrom<web::Json<CreateTeacher>> for CreateTeacher {
+    type Error = MyError;
+
+    fn try_from(new_teacher: web::Json<CreateTeacher>) -> Result<Self, Self::Error> {
+        validated(new_teacher.into_inner(), None)
+    }
+}
+
+impl TryFrom<web::Json<UpdateTeacher>> for UpdateTeacher {
+    type Error = MyError;
+
+    fn try_from(update_teacher: web::Json<UpdateTeacher>) -> Result<Self, Self::Error> {
+        validated(update_teacher.into_inner(), None)
+    }
+}
+
+impl UpdateTeacher {
+    pub fn apply_to(self, teacher: Teacher) -> Teacher {
+        Teacher {
+            id: teacher.id,
+            name: self.name.unwrap_or(teacher.name),
+            picture_url: self.picture_url.unwrap_or(teacher.picture_url),
+            profile: self.profile.unwrap_or(teacher.profile),
+            version: teacher.version,
         }
     }
 }
 
-impl From<web::Json<UpdateTeacher>> for UpdateTeacher {
-    fn from(update_teacher: web::Json<UpdateTeacher>) -> Self {
-        UpdateTeacher {
-            name: update_teacher.name.clone(),
-            picture_url: update_teacher.picture_url.clone(),
-            profile: update_teacher.profile.clone(),
+//every field of a teacher is required, so a merge patch applies to them as if creating the teacher again
+impl From<&Teacher> for CreateTeacher {
+    fn from(teacher: &Teacher) -> Self {
+        CreateTeacher {
+            name: teacher.name.clone(),
+            picture_url: teacher.picture_url.clone(),
+            profile: teacher.profile.clone(),
         }
     }
+}
+
+impl Teacher {
+    pub fn patched(self, patch: &MergePatch) -> Result<Teacher, MyError> {
+        let fields = patch.apply(&CreateTeacher::from(&self))?;
+        Ok(Teacher {
+            name: fields.name,
+            picture_url: fields.picture_url,
+            profile: fields.profile,
+            ..self
+        })
+    }
+}
+
+//query string of GET /teacher/
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct TeacherQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl TeacherQuery {
+    pub fn page_bounds(&self) -> (u32, u32) {
+        page_bounds(self.page, self.per_page)
+    }
+
+    pub fn order_by(&self) -> Result<String, MyError> {
+        let column = match self.sort.as_deref() {
+            None | Some("id") => "id",
+            Some("name") => "name",
+            Some(other) => {
+                return Err(MyError::Validation(vec![FieldError::new(
+                    "sort",
+                    format!("Unknown sort '{}', expected id or name", other),
+                )]))
+            }
+        };
+        let order = order_sql(&self.order)?;
+        Ok(format!("ORDER BY {} {}, id {}", column, order, order))
+    }
+
+    pub fn link(&self, path: &str, page: u32) -> String {
+        let mut query = self.clone();
+        query.page = Some(page);
+        query.per_page = Some(self.page_bounds().1);
+        format!("{}?{}", path, serde_urlencoded::to_string(&query).unwrap_or_default())
+    }
 }
\ No newline at end of file
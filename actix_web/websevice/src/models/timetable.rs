@@ -0,0 +1,255 @@
+use crate::errors::{ConflictReason, FieldError, MyError, ScheduleConflict};
+use crate::models::course::{Course, CreateCourse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+
+//the longest window GET /teacher/{id}/availability looks at
+const MAX_WINDOW_DAYS: i64 = 366;
+
+//intervals are half open, a course ending at 10:00 leaves 10:00 free for the next one
+pub fn overlaps(
+    a_start: DateTime<Utc>, a_end: DateTime<Utc>, b_start: DateTime<Utc>, b_end: DateTime<Utc>
+) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+//ends_at must come with starts_at and after it
+pub fn interval_errors(starts_at: Option<DateTime<Utc>>, ends_at: Option<DateTime<Utc>>) -> Vec<FieldError> {
+    match (starts_at, ends_at) {
+        (Some(start), Some(end)) if end <= start => vec![FieldError::new("ends_at", "must be after starts_at")],
+        (Some(_), None) => vec![FieldError::new("ends_at", "is required together with starts_at")],
+        (None, Some(_)) => vec![FieldError::new("starts_at", "is required together with ends_at")],
+        _ => vec![],
+    }
+}
+
+//the time a course holds its teacher and room
+#[derive(Debug, Clone)]
+pub struct Booking {
+    //None for a course that is not stored yet
+    pub course_id: Option<i32>,
+    pub teacher_id: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub room: Option<String>,
+}
+
+impl Booking {
+    //None when the course has no interval, it cannot clash with anything
+    pub fn new(
+        course_id: Option<i32>, teacher_id: i32, starts_at: Option<DateTime<Utc>>,
+        ends_at: Option<DateTime<Utc>>, room: Option<String>
+    ) -> Result<Option<Booking>, MyError> {
+        let errors = interval_errors(starts_at, ends_at);
+        if !errors.is_empty() {
+            return Err(MyError::Validation(errors));
+        }
+
+        Ok(starts_at.zip(ends_at).map(|(starts_at, ends_at)| Booking {
+            course_id,
+            teacher_id,
+            starts_at,
+            ends_at,
+            room,
+        }))
+    }
+
+    pub fn of(course: &Course) -> Result<Option<Booking>, MyError> {
+        Booking::new(Some(course.id), course.teacher_id, course.starts_at, course.ends_at, course.room.clone())
+    }
+
+    pub fn of_new(course: &CreateCourse) -> Result<Option<Booking>, MyError> {
+        Booking::new(None, course.teacher_id, course.starts_at, course.ends_at, course.room.clone())
+    }
+
+    //the courses among `others` that overlap and share the teacher or the room
+    pub fn conflicts<'a>(&self, others: impl IntoIterator<Item = &'a Course>) -> Vec<ScheduleConflict> {
+        others
+            .into_iter()
+            .filter(|other| Some(other.id) != self.course_id)
+            .filter_map(|other| {
+                let (starts_at, ends_at) = (other.starts_at?, other.ends_at?);
+                if !overlaps(self.starts_at, self.ends_at, starts_at, ends_at) {
+                    return None;
+                }
+                let reason = if other.teacher_id == self.teacher_id {
+                    ConflictReason::Teacher
+                } else if self.room.is_some() && other.room == self.room {
+                    ConflictReason::Room
+                } else {
+                    return None;
+                };
+                Some(ScheduleConflict {
+                    course_id: other.id,
+                    teacher_id: other.teacher_id,
+                    name: other.name.clone(),
+                    starts_at,
+                    ends_at,
+                    room: other.room.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    pub fn check<'a>(&self, others: impl IntoIterator<Item = &'a Course>) -> Result<(), MyError> {
+        let conflicts = self.conflicts(others);
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(MyError::ScheduleConflict(conflicts))
+        }
+    }
+}
+
+//query string of GET /teacher/{teacher_id}/availability
+#[derive(Deserialize, Debug, Clone)]
+pub struct AvailabilityQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl AvailabilityQuery {
+    pub fn window(&self) -> Result<(DateTime<Utc>, DateTime<Utc>), MyError> {
+        if self.to <= self.from {
+            return Err(MyError::Validation(vec![FieldError::new("to", "must be after from")]));
+        }
+        if self.to - self.from > Duration::days(MAX_WINDOW_DAYS) {
+            return Err(MyError::Validation(vec![FieldError::new(
+                "to",
+                format!("must be at most {} days after from", MAX_WINDOW_DAYS),
+            )]));
+        }
+        Ok((self.from, self.to))
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Component)]
+pub struct TimeBlock {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    //the courses filling a busy block, empty for a free one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub course_ids: Vec<i32>,
+}
+
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct Availability {
+    pub teacher_id: i32,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub busy: Vec<TimeBlock>,
+    pub free: Vec<TimeBlock>,
+}
+
+impl Availability {
+    //busy blocks are the teacher's courses clipped to the window, overlapping ones merged,
+    //free blocks are the gaps between them
+    pub fn new(teacher_id: i32, from: DateTime<Utc>, to: DateTime<Utc>, courses: &[Course]) -> Self {
+        let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>, i32)> = courses
+            .iter()
+            .filter_map(|c| Some((c.starts_at?, c.ends_at?, c.id)))
+            .filter(|(start, end, _)| overlaps(*start, *end, from, to))
+            .map(|(start, end, id)| (start.max(from), end.min(to), id))
+            .collect();
+        intervals.sort();
+
+        let mut busy: Vec<TimeBlock> = vec![];
+        for (start, end, id) in intervals {
+            match busy.last_mut() {
+                Some(last) if start <= last.ends_at => {
+                    last.ends_at = last.ends_at.max(end);
+                    last.course_ids.push(id);
+                }
+                _ => busy.push(TimeBlock { starts_at: start, ends_at: end, course_ids: vec![id] }),
+            }
+        }
+
+        let mut free = vec![];
+        let mut cursor = from;
+        for block in &busy {
+            if cursor < block.starts_at {
+                free.push(TimeBlock { starts_at: cursor, ends_at: block.starts_at, course_ids: vec![] });
+            }
+            cursor = block.ends_at;
+        }
+        if cursor < to {
+            free.push(TimeBlock { starts_at: cursor, ends_at: to, course_ids: vec![] });
+        }
+
+        Availability { teacher_id, from, to, busy, free }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::meta::CourseStatus;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn course(id: i32, teacher_id: i32, start: u32, end: u32, room: Option<&str>) -> Course {
+        Course {
+            teacher_id,
+            id,
+            name: format!("course {}", id),
+            time: None,
+            starts_at: Some(at(start)),
+            ends_at: Some(at(end)),
+            room: room.map(String::from),
+            capacity: None,
+            status: CourseStatus::Published,
+            published_at: None,
+            version: 1,
+            description: None,
+            format: None,
+            structure: None,
+            duration: None,
+            price: None,
+            language: None,
+            level: None,
+            display_price: None,
+        }
+    }
+
+    #[test]
+    fn clashes_by_teacher_or_room() {
+        let booking = Booking::new(None, 1, Some(at(9)), Some(at(11)), Some("A101".into()))
+            .unwrap()
+            .unwrap();
+        let others = [
+            course(1, 1, 10, 12, None),
+            course(2, 2, 8, 10, Some("A101")),
+            course(3, 2, 9, 11, Some("B202")),
+            course(4, 1, 11, 12, Some("A101")),
+        ];
+
+        let conflicts = booking.conflicts(&others);
+        let found: Vec<(i32, ConflictReason)> = conflicts.iter().map(|c| (c.course_id, c.reason)).collect();
+        assert_eq!(found, vec![(1, ConflictReason::Teacher), (2, ConflictReason::Room)]);
+    }
+
+    #[test]
+    fn a_course_does_not_clash_with_itself() {
+        let current = course(1, 1, 9, 11, Some("A101"));
+        let booking = Booking::of(&current).unwrap().unwrap();
+
+        assert!(booking.check([&current]).is_ok());
+    }
+
+    #[test]
+    fn free_blocks_are_the_gaps() {
+        let courses = [course(1, 1, 9, 11, None), course(2, 1, 10, 12, None), course(3, 1, 14, 20, None)];
+        let availability = Availability::new(1, at(8), at(18), &courses);
+
+        assert_eq!(availability.busy[0], TimeBlock { starts_at: at(9), ends_at: at(12), course_ids: vec![1, 2] });
+        assert_eq!(availability.busy[1].ends_at, at(18));
+        let free: Vec<(DateTime<Utc>, DateTime<Utc>)> =
+            availability.free.iter().map(|b| (b.starts_at, b.ends_at)).collect();
+        assert_eq!(free, vec![(at(8), at(9)), (at(12), at(14))]);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::errors::{FieldError, MyError};
+use serde::Deserialize;
+use utoipa::Component;
+
+//what DELETE /teacher/{teacher_id} does with the teacher's courses
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnCourses {
+    Refuse,
+    Cascade,
+    Reassign,
+}
+
+//query string of DELETE /teacher/{teacher_id}
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DeleteTeacherQuery {
+    pub courses: Option<OnCourses>,
+    pub reassign_to: Option<i32>,
+}
+
+//the courses of a deleted teacher, only those outside the trash are touched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeacherDeletion {
+    //409 while the teacher has courses
+    Refuse,
+    //the courses go to the trash with the teacher
+    Cascade,
+    //the courses move to this teacher first
+    Reassign(i32),
+}
+
+impl DeleteTeacherQuery {
+    pub fn deletion(&self, teacher_id: i32) -> Result<TeacherDeletion, MyError> {
+        match (self.courses.unwrap_or(OnCourses::Refuse), self.reassign_to) {
+            (OnCourses::Reassign, Some(to)) if to == teacher_id => Err(MyError::Validation(vec![
+                FieldError::new("reassign_to", "Courses cannot be reassigned to the deleted teacher"),
+            ])),
+            (OnCourses::Reassign, Some(to)) => Ok(TeacherDeletion::Reassign(to)),
+            (OnCourses::Reassign, None) => Err(MyError::Validation(vec![
+                FieldError::new("reassign_to", "Required when courses=reassign"),
+            ])),
+            (_, Some(_)) => Err(MyError::Validation(vec![
+                FieldError::new("reassign_to", "Only allowed with courses=reassign"),
+            ])),
+            (OnCourses::Refuse, None) => Ok(TeacherDeletion::Refuse),
+            (OnCourses::Cascade, None) => Ok(TeacherDeletion::Cascade),
+        }
+    }
+}
+
+//body of POST /courses/{teacher_id}/{course_id}/transfer
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct TransferCourse {
+    //the teacher the course moves to
+    #[component(example = 2)]
+    pub teacher_id: i32,
+}
+
+impl TransferCourse {
+    pub fn check(&self, teacher_id: i32) -> Result<(), MyError> {
+        if self.teacher_id == teacher_id {
+            return Err(MyError::Validation(vec![
+                FieldError::new("teacher_id", "The course already belongs to this teacher"),
+            ]));
+        }
+        Ok(())
+    }
+}
+
+//teacher and course id of a path under /courses/{teacher_id}/{course_id}
+pub fn course_path(path: &str) -> Option<(i32, i32)> {
+    let mut segments = path.strip_prefix("/courses/")?.split('/');
+    let teacher_id = segments.next()?.parse().ok()?;
+    let course_id = segments.next()?.parse().ok()?;
+    Some((teacher_id, course_id))
+}
+
+//the same path and query with the course's current teacher in place of the old one
+pub fn moved_path(path: &str, query: &str, teacher_id: i32) -> Option<String> {
+    let rest = path.strip_prefix("/courses/")?;
+    let rest = &rest[rest.find('/')?..];
+    let mut moved = format!("/courses/{}{}", teacher_id, rest);
+    if !query.is_empty() {
+        moved.push('?');
+        moved.push_str(query);
+    }
+    Some(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_course_paths_move_to_the_new_teacher() {
+        assert_eq!(course_path("/courses/1/7/modules"), Some((1, 7)));
+        assert_eq!(course_path("/courses/search"), None);
+        assert_eq!(course_path("/courses/1"), None);
+        assert_eq!(
+            moved_path("/courses/1/7/modules", "include=outline", 2),
+            Some("/courses/2/7/modules?include=outline".into())
+        );
+        assert_eq!(moved_path("/courses/1/7", "", 2), Some("/courses/2/7".into()));
+    }
+
+    #[test]
+    fn reassign_needs_another_teacher() {
+        let query = |courses, reassign_to| DeleteTeacherQuery { courses, reassign_to };
+
+        assert_eq!(query(None, None).deletion(1).unwrap(), TeacherDeletion::Refuse);
+        assert_eq!(query(Some(OnCourses::Cascade), None).deletion(1).unwrap(), TeacherDeletion::Cascade);
+        assert_eq!(query(Some(OnCourses::Reassign), Some(2)).deletion(1).unwrap(), TeacherDeletion::Reassign(2));
+        assert!(matches!(query(Some(OnCourses::Reassign), None).deletion(1), Err(MyError::Validation(_))));
+        assert!(matches!(query(Some(OnCourses::Reassign), Some(1)).deletion(1), Err(MyError::Validation(_))));
+        assert!(matches!(query(Some(OnCourses::Cascade), Some(2)).deletion(1), Err(MyError::Validation(_))));
+    }
+}
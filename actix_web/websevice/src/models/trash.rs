@@ -0,0 +1,91 @@
+use crate::models::course::Course;
+use crate::models::teacher::Teacher;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use utoipa::Component;
+
+//how long deleted teachers and courses stay restorable when TRASH_RETENTION_DAYS is not set
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+//TRASH_RETENTION_DAYS as a duration, the default for a missing or unreadable value
+pub fn retention(days: Option<&str>) -> Duration {
+    let days = days
+        .and_then(|days| days.trim().parse::<i64>().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    Duration::days(days)
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Teacher,
+    Course,
+}
+
+//a deleted teacher or course, kept until the purge job removes it for good
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: i32,
+    //the course's teacher, unset for teachers
+    pub teacher_id: Option<i32>,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl TrashItem {
+    pub fn teacher(teacher: &Teacher, deleted_at: DateTime<Utc>) -> Self {
+        TrashItem {
+            kind: TrashKind::Teacher,
+            id: teacher.id,
+            teacher_id: None,
+            name: teacher.name.clone(),
+            deleted_at,
+        }
+    }
+
+    pub fn course(course: &Course, deleted_at: DateTime<Utc>) -> Self {
+        TrashItem {
+            kind: TrashKind::Course,
+            id: course.id,
+            teacher_id: Some(course.teacher_id),
+            name: course.name.clone(),
+            deleted_at,
+        }
+    }
+}
+
+//teacher or course row in the trash as selected by the sql backends, teacher_id is null for teachers
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TrashRow {
+    pub id: i32,
+    pub teacher_id: Option<i32>,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<TrashRow> for TrashItem {
+    fn from(row: TrashRow) -> Self {
+        TrashItem {
+            kind: if row.teacher_id.is_some() { TrashKind::Course } else { TrashKind::Teacher },
+            id: row.id,
+            teacher_id: row.teacher_id,
+            name: row.name,
+            deleted_at: row.deleted_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_falls_back_to_the_default() {
+        assert_eq!(retention(Some("7")), Duration::days(7));
+        assert_eq!(retention(Some("-1")), Duration::days(DEFAULT_RETENTION_DAYS));
+        assert_eq!(retention(Some("week")), Duration::days(DEFAULT_RETENTION_DAYS));
+        assert_eq!(retention(None), Duration::days(DEFAULT_RETENTION_DAYS));
+    }
+}
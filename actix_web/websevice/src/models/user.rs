@@ -0,0 +1,107 @@
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use utoipa::Component;
+use std::str::FromStr;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Component)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Teacher,
+    Student,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Teacher => "teacher",
+            Role::Student => "student",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(Role::Admin),
+            "teacher" => Ok(Role::Teacher),
+            "student" => Ok(Role::Student),
+            other => Err(format!("Unknown role '{}'", other)),
+        }
+    }
+}
+
+//an account that can log in, linked to the teacher or student it acts as
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct User {
+    pub id: i32,
+    pub username: String,
+    #[serde(skip)]
+    pub password_hash: String,
+    pub role: Role,
+    pub teacher_id: Option<i32>,
+    pub student_id: Option<i32>,
+}
+
+//app_user row as stored, role still a plain string
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserRow {
+    pub id: i32,
+    pub username: String,
+    pub password_hash: String,
+    pub role: String,
+    pub teacher_id: Option<i32>,
+    pub student_id: Option<i32>,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = String;
+
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        Ok(User {
+            id: row.id,
+            username: row.username,
+            password_hash: row.password_hash,
+            role: row.role.parse()?,
+            teacher_id: row.teacher_id,
+            student_id: row.student_id,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct CreateUser {
+    pub username: String,
+    pub password: String,
+    pub role: Role,
+    pub teacher_id: Option<i32>,
+    pub student_id: Option<i32>,
+}
+
+impl From<web::Json<CreateUser>> for CreateUser {
+    fn from(new_user: web::Json<CreateUser>) -> Self {
+        CreateUser {
+            username: new_user.username.clone(),
+            password: new_user.password.clone(),
+            role: new_user.role,
+            teacher_id: new_user.teacher_id,
+            student_id: new_user.student_id,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Component)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Debug, Clone, Component)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
@@ -0,0 +1,35 @@
+use crate::errors::{FieldError, MyError};
+use validator::{Validate, ValidationErrors};
+
+//`value` if it passes its #[validate] rules, otherwise every failing field plus `extra`
+pub fn validated<T: Validate>(
+    value: T, extra: impl IntoIterator<Item = FieldError>
+) -> Result<T, MyError> {
+    let mut errors = match value.validate() {
+        Ok(()) => Vec::new(),
+        Err(errors) => field_errors(&errors),
+    };
+    errors.extend(extra);
+
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(MyError::Validation(errors))
+    }
+}
+
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut fields: Vec<_> = errors.field_errors().into_iter().collect();
+    //HashMap order is random, keep responses stable
+    fields.sort_by_key(|(field, _)| *field);
+
+    fields
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |e| {
+                let message = e.message.as_ref().map(|m| m.to_string()).unwrap_or_else(|| e.code.to_string());
+                FieldError::new(field, message)
+            })
+        })
+        .collect()
+}
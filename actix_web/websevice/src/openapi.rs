@@ -0,0 +1,199 @@
+use crate::errors::{ConflictReason, FieldError, MyErrorResponse, ScheduleConflict};
+use crate::handlers::{
+    auth, course, enrollment, general, instructor, lifecycle, meta, revision, seat, session, student,
+    syllabus, teacher, transfer, trash,
+};
+use crate::models::course::{Course, CourseDetailQuery, CourseQuery, CreateCourse, UpdateCourse};
+use crate::models::enrollment::{CreateEnrollment, Enrollment};
+use crate::models::instructor::{AddInstructor, CourseInstructor, InstructorRole};
+use crate::models::lifecycle::{PublishCourse, StatusChange};
+use crate::models::meta::{
+    CourseFormat, CourseLevel, CourseStatus, Currency, Frequency, Language, MetaOption,
+};
+use crate::models::money::Money;
+use crate::models::revision::{CourseRevision, FieldChange, RevisionDiff, RevisionDiffQuery};
+use crate::models::search::{CourseSearchQuery, FacetCount, SearchFacets};
+use crate::models::seat::{CreateReservation, Reservation, SeatStatus};
+use crate::models::session::{CourseSession, CreateSession, UpdateSession};
+use crate::models::student::{CreateStudent, Student, UpdateStudent};
+use crate::models::syllabus::{
+    Attachment, CourseModule, CreateLesson, CreateModule, Lesson, ModuleOutline, Reorder,
+    UpdateLesson, UpdateModule,
+};
+use crate::models::teacher::{CreateTeacher, Teacher, TeacherQuery, UpdateTeacher};
+use crate::models::timetable::{Availability, AvailabilityQuery, TimeBlock};
+use crate::models::transfer::{DeleteTeacherQuery, TransferCourse};
+use crate::models::trash::{TrashItem, TrashKind};
+use crate::models::user::{CreateUser, LoginRequest, Role, TokenResponse, User};
+use serde::Serialize;
+use utoipa::openapi::path::{Parameter, ParameterIn};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Component, IntoParams, Modify, OpenApi};
+
+//OpenAPI document served at /openapi.json, the swagger ui lives under /swagger-ui/
+#[derive(OpenApi)]
+#[openapi(
+    handlers(
+        general::health_check_handler,
+        meta::get_levels,
+        meta::get_formats,
+        meta::get_languages,
+        meta::get_currencies,
+        meta::get_frequencies,
+        meta::get_statuses,
+        auth::login,
+        auth::post_new_user,
+        teacher::get_all_teachers,
+        teacher::get_teacher_details,
+        teacher::post_new_teacher,
+        teacher::update_teacher_details,
+        teacher::patch_teacher_details,
+        teacher::delete_teacher,
+        session::get_teacher_calendar,
+        teacher::get_teacher_availability,
+        course::post_new_course,
+        course::search_courses,
+        course::get_courses_for_teacher,
+        course::get_course_detail,
+        course::get_course_by_id,
+        course::get_course_by_slug,
+        course::update_course_detail,
+        course::patch_course_detail,
+        course::delete_course,
+        transfer::transfer_course,
+        instructor::get_instructors,
+        instructor::add_instructor,
+        instructor::remove_instructor,
+        lifecycle::publish_course,
+        lifecycle::archive_course,
+        lifecycle::get_status_changes,
+        revision::get_revisions,
+        revision::get_revision_diff,
+        revision::get_revision,
+        revision::restore_revision,
+        enrollment::post_new_enrollment,
+        enrollment::get_enrollments_for_course,
+        enrollment::delete_enrollment,
+        seat::get_reservations,
+        seat::reserve_seat,
+        seat::get_reservation,
+        seat::release_seat,
+        syllabus::get_modules,
+        syllabus::post_new_module,
+        syllabus::reorder_modules,
+        syllabus::update_module,
+        syllabus::delete_module,
+        syllabus::get_lessons,
+        syllabus::post_new_lesson,
+        syllabus::reorder_lessons,
+        syllabus::update_lesson,
+        syllabus::delete_lesson,
+        session::get_sessions,
+        session::post_new_session,
+        session::update_session,
+        session::delete_session,
+        session::get_course_calendar,
+        student::get_all_students,
+        student::get_student_details,
+        student::post_new_student,
+        student::update_student_details,
+        student::delete_student,
+        enrollment::get_enrollments_for_student,
+        trash::get_trash,
+        trash::restore_teacher,
+        trash::restore_course,
+    ),
+    components(
+        CourseLevel, CourseFormat, Language, Currency, Frequency, CourseStatus, Money, MetaOption,
+        Course, CreateCourse, UpdateCourse, CoursePage, CourseSearchPage, FacetCount, SearchFacets,
+        PublishCourse, TransferCourse, CourseInstructor, InstructorRole, AddInstructor, StatusChange,
+        CourseRevision, RevisionDiff, FieldChange,
+        CourseModule, CreateModule, UpdateModule, Lesson, CreateLesson, UpdateLesson, Attachment,
+        ModuleOutline, Reorder, CourseSession, CreateSession, UpdateSession,
+        Teacher, CreateTeacher, UpdateTeacher, TeacherPage, Availability, TimeBlock,
+        Student, CreateStudent, UpdateStudent, Enrollment, CreateEnrollment,
+        Reservation, CreateReservation, SeatStatus,
+        User, Role, CreateUser, LoginRequest, TokenResponse, MyErrorResponse, FieldError,
+        ScheduleConflict, ConflictReason, TrashItem, TrashKind,
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "general", description = "Service health"),
+        (name = "meta", description = "Accepted values of course levels, formats, languages, currencies, session frequencies and course statuses"),
+        (name = "auth", description = "Login and user accounts"),
+        (name = "teacher", description = "Teachers"),
+        (name = "course", description = "Courses and their lifecycle, revisions, transfers and instructors, modules, lessons, sessions and seats, and course search"),
+        (name = "student", description = "Students and their enrollments"),
+        (name = "trash", description = "Deleted teachers and courses, restorable until purged"),
+    )
+)]
+pub struct ApiDoc;
+
+//the Authorization: Bearer <jwt> scheme issued by POST /auth/login
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+//utoipa asks every web::Query<T> for its parameters, ours are listed one by one in the
+//handler's params(...) where each gets a description
+macro_rules! params_listed_by_handler {
+    ($($query:ty),* $(,)?) => {
+        $(impl IntoParams for $query {
+            fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+                Vec::new()
+            }
+        })*
+    };
+}
+
+params_listed_by_handler!(
+    AvailabilityQuery,
+    CourseDetailQuery,
+    CourseQuery,
+    CourseSearchQuery,
+    DeleteTeacherQuery,
+    RevisionDiffQuery,
+    TeacherQuery,
+);
+
+//schemas of the Page<T> envelopes, utoipa cannot describe the generic struct itself
+
+#[derive(Serialize, Component)]
+pub struct CoursePage {
+    pub items: Vec<Course>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+#[derive(Serialize, Component)]
+pub struct TeacherPage {
+    pub items: Vec<Teacher>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+#[derive(Serialize, Component)]
+pub struct CourseSearchPage {
+    pub items: Vec<Course>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    pub facets: SearchFacets,
+}
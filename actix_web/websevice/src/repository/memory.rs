@@ -0,0 +1,1335 @@
+use crate::errors::{MyError, Resource};
+use crate::models::course::{Course, CourseQuery, CreateCourse, UpdateCourse};
+use crate::models::enrollment::{CreateEnrollment, Enrollment};
+use crate::models::etag::IfMatch;
+use crate::models::instructor::{AddInstructor, CourseInstructor};
+use crate::models::lifecycle::{StatusChange, Transition};
+use crate::models::meta::CourseStatus;
+use crate::models::pagination::order_sql;
+use crate::models::patch::MergePatch;
+use crate::models::revision::CourseRevision;
+use crate::models::search::{CourseSearchQuery, FacetCount, SearchFacets};
+use crate::models::seat::{
+    open_seats, seat_status, with_positions, CreateReservation, Reservation, ReservationRow, SeatStatus,
+};
+use crate::models::session::{CourseSession, CreateSession, UpdateSession};
+use crate::models::slug::{next_slug, SlugLookup};
+use crate::models::student::{CreateStudent, Student, UpdateStudent};
+use crate::models::syllabus::{
+    CourseModule, CreateLesson, CreateModule, Lesson, Reorder, UpdateLesson, UpdateModule,
+};
+use crate::models::teacher::{CreateTeacher, Teacher, TeacherQuery, UpdateTeacher};
+use crate::models::timetable::{overlaps, Booking};
+use crate::models::transfer::TeacherDeletion;
+use crate::models::trash::TrashItem;
+use crate::models::user::{CreateUser, Role, User};
+use crate::repository::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+//everything kept by the in-memory backend, keyed by id
+#[derive(Debug, Default)]
+struct MemoryStore {
+    next_id: i32,
+    teachers: BTreeMap<i32, Teacher>,
+    courses: BTreeMap<i32, Course>,
+    //deleted records with the time they were deleted, until they are restored or purged
+    trashed_teachers: BTreeMap<i32, (Teacher, DateTime<Utc>)>,
+    trashed_courses: BTreeMap<i32, (Course, DateTime<Utc>)>,
+    students: BTreeMap<i32, Student>,
+    enrollments: BTreeMap<i32, Enrollment>,
+    modules: BTreeMap<i32, CourseModule>,
+    lessons: BTreeMap<i32, Lesson>,
+    sessions: BTreeMap<i32, CourseSession>,
+    reservations: BTreeMap<i32, ReservationRow>,
+    status_changes: BTreeMap<i32, StatusChange>,
+    revisions: BTreeMap<i32, CourseRevision>,
+    //course id and the teacher it was transferred away from
+    transfers: Vec<(i32, i32)>,
+    //co-teachers and assistants in the order they were added
+    instructors: Vec<CourseInstructor>,
+    //slugs with the course they name, a course's last one is its current slug
+    slugs: Vec<(String, i32)>,
+    users: BTreeMap<i32, User>,
+}
+
+impl MemoryStore {
+    //one sequence for all tables, ids are never reused
+    fn next_id(&mut self) -> i32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn teacher(&self, teacher_id: i32) -> Result<&Teacher, MyError> {
+        match self.teachers.get(&teacher_id) {
+            Some(teacher) => Ok(teacher),
+            None if self.trashed_teachers.contains_key(&teacher_id) => {
+                Err(MyError::Gone(Resource::Teacher, "Teacher was deleted".into()))
+            }
+            None => Err(MyError::NotFound(Resource::Teacher, "Teacher id not found".into())),
+        }
+    }
+
+    fn course(&self, teacher_id: i32, course_id: i32) -> Result<&Course, MyError> {
+        match self.courses.get(&course_id).filter(|course| course.teacher_id == teacher_id) {
+            Some(course) => Ok(course),
+            None if self.trashed_course(teacher_id, course_id).is_some() => {
+                Err(MyError::Gone(Resource::Course, "Course was deleted".into()))
+            }
+            None => Err(MyError::NotFound(Resource::Course, "Course ID not found".into())),
+        }
+    }
+
+    fn trashed_course(&self, teacher_id: i32, course_id: i32) -> Option<&Course> {
+        self.trashed_courses
+            .get(&course_id)
+            .map(|(course, _)| course)
+            .filter(|course| course.teacher_id == teacher_id)
+    }
+
+    //drops a course and everything hanging off it, like the cascading foreign keys do
+    fn purge_course(&mut self, course_id: i32) {
+        self.trashed_courses.remove(&course_id);
+        self.enrollments.retain(|_, e| e.course_id != course_id);
+        let module_ids: Vec<i32> = self.modules_of(course_id).iter().map(|m| m.id).collect();
+        self.modules.retain(|_, m| m.course_id != course_id);
+        self.lessons.retain(|_, l| !module_ids.contains(&l.module_id));
+        self.sessions.retain(|_, s| s.course_id != course_id);
+        self.reservations.retain(|_, r| r.course_id != course_id);
+        self.status_changes.retain(|_, c| c.course_id != course_id);
+        self.revisions.retain(|_, r| r.course_id != course_id);
+        self.transfers.retain(|(id, _)| *id != course_id);
+        self.instructors.retain(|i| i.course_id != course_id);
+        self.slugs.retain(|(_, id)| *id != course_id);
+    }
+
+    //gives a stored course to another teacher, who has to be free at the course's time
+    fn transfer_course(&mut self, course: Course, to_teacher_id: i32) -> Result<Course, MyError> {
+        if self.teacher(to_teacher_id).is_err() {
+            return Err(MyError::InvalidReference("The teacher to transfer to does not exist".into()));
+        }
+        let from_teacher_id = course.teacher_id;
+        let course = Course { teacher_id: to_teacher_id, version: course.version + 1, ..course };
+        if let Some(booking) = Booking::of(&course)? {
+            booking.check(self.courses.values())?;
+        }
+
+        self.courses.insert(course.id, course.clone());
+        self.transfers.push((course.id, from_teacher_id));
+        //a co-teacher who gets the course leads it from now on
+        self.instructors.retain(|i| !(i.course_id == course.id && i.teacher_id == to_teacher_id));
+        Ok(course)
+    }
+
+    fn module(&self, teacher_id: i32, course_id: i32, module_id: i32) -> Result<&CourseModule, MyError> {
+        let course_id = self.course(teacher_id, course_id)?.id;
+        self.modules
+            .get(&module_id)
+            .filter(|module| module.course_id == course_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Module, "Module not found".into()))
+    }
+
+    fn lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+    ) -> Result<&Lesson, MyError> {
+        let module_id = self.module(teacher_id, course_id, module_id)?.id;
+        self.lessons
+            .get(&lesson_id)
+            .filter(|lesson| lesson.module_id == module_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Lesson, "Lesson not found".into()))
+    }
+
+    //modules of a course in position order
+    fn modules_of(&self, course_id: i32) -> Vec<CourseModule> {
+        let mut modules: Vec<CourseModule> = self
+            .modules
+            .values()
+            .filter(|m| m.course_id == course_id)
+            .cloned()
+            .collect();
+        modules.sort_by_key(|m| (m.position, m.id));
+        modules
+    }
+
+    //lessons of a module in position order
+    fn lessons_of(&self, module_id: i32) -> Vec<Lesson> {
+        let mut lessons: Vec<Lesson> = self
+            .lessons
+            .values()
+            .filter(|l| l.module_id == module_id)
+            .cloned()
+            .collect();
+        lessons.sort_by_key(|l| (l.position, l.id));
+        lessons
+    }
+
+    fn session(&self, teacher_id: i32, course_id: i32, session_id: i32) -> Result<&CourseSession, MyError> {
+        let course_id = self.course(teacher_id, course_id)?.id;
+        self.sessions
+            .get(&session_id)
+            .filter(|session| session.course_id == course_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Session, "Session not found".into()))
+    }
+
+    //reservations of a course in the order they were made
+    fn reservations_of(&self, course_id: i32) -> Result<Vec<Reservation>, MyError> {
+        with_positions(
+            self.reservations
+                .values()
+                .filter(|r| r.course_id == course_id)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn reservation(&self, course_id: i32, student_id: i32) -> Result<Reservation, MyError> {
+        self.reservations_of(course_id)?
+            .into_iter()
+            .find(|r| r.student_id == student_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Reservation, "Reservation not found".into()))
+    }
+
+    //moves the head of the waitlist into any free seats
+    fn fill_seats(&mut self, course_id: i32, capacity: Option<i32>) {
+        let reserved = self
+            .reservations
+            .values()
+            .filter(|r| r.course_id == course_id && r.status == SeatStatus::Reserved.as_str())
+            .count();
+        let open = open_seats(capacity, reserved as i64);
+
+        for row in self
+            .reservations
+            .values_mut()
+            .filter(|r| r.course_id == course_id && r.status == SeatStatus::Waitlisted.as_str())
+            .take(usize::try_from(open).unwrap_or(usize::MAX))
+        {
+            row.status = SeatStatus::Reserved.as_str().into();
+        }
+    }
+
+    //stores changed fields of an existing course, the same way for updates and restores
+    fn save_course(&mut self, course: Course, changed_by: i32) -> Result<Course, MyError> {
+        if let Some(booking) = Booking::of(&course)? {
+            booking.check(self.courses.values())?;
+        }
+        let course = Course { version: course.version + 1, ..course };
+        self.courses.insert(course.id, course.clone());
+        self.record_revision(&course, changed_by);
+        self.assign_slug(&course);
+        //a raised capacity lets the waitlist in
+        self.fill_seats(course.id, course.capacity);
+
+        Ok(course)
+    }
+
+    //gives the course the slug next_slug picks for its name, the slugs it had before stay for redirects
+    fn assign_slug(&mut self, course: &Course) {
+        let own: Vec<String> = self
+            .slugs
+            .iter()
+            .filter(|(_, id)| *id == course.id)
+            .map(|(slug, _)| slug.clone())
+            .collect();
+        let taken: Vec<String> = self.slugs.iter().map(|(slug, _)| slug.clone()).collect();
+
+        if let Some(slug) = next_slug(&course.name, own.last().map(String::as_str), &own, &taken) {
+            self.slugs.retain(|(other, _)| *other != slug);
+            self.slugs.push((slug, course.id));
+        }
+    }
+
+    fn slug_of(&self, course_id: i32) -> Option<String> {
+        self.slugs.iter().rev().find(|(_, id)| *id == course_id).map(|(slug, _)| slug.clone())
+    }
+
+    //keeps the course as just saved under the next revision number
+    fn record_revision(&mut self, course: &Course, changed_by: i32) {
+        let revision = self.revisions_of(course.id).len() as i32 + 1;
+        let revision = CourseRevision::of(course, self.next_id(), revision, changed_by, Utc::now());
+        self.revisions.insert(revision.id, revision);
+    }
+
+    //revisions of a course, oldest first
+    fn revisions_of(&self, course_id: i32) -> Vec<CourseRevision> {
+        self.revisions.values().filter(|r| r.course_id == course_id).cloned().collect()
+    }
+
+    fn revision(&self, course_id: i32, revision: i32) -> Result<CourseRevision, MyError> {
+        self.revisions
+            .values()
+            .find(|r| r.course_id == course_id && r.revision == revision)
+            .cloned()
+            .ok_or_else(|| MyError::NotFound(Resource::Revision, "Revision not found".into()))
+    }
+
+    fn student(&self, student_id: i32) -> Result<&Student, MyError> {
+        self.students
+            .get(&student_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Student, "Student id not found".into()))
+    }
+}
+
+//storage without a database, for tests and quick local runs (DATABASE_URL=memory://)
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    store: Mutex<MemoryStore>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        MemoryRepository::default()
+    }
+
+    fn store(&self) -> MutexGuard<'_, MemoryStore> {
+        self.store.lock().unwrap()
+    }
+}
+
+//apply page/per_page to an already filtered and sorted list
+fn paginate<T>(items: Vec<T>, page: u32, per_page: u32) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(((page - 1) * per_page) as usize)
+        .take(per_page as usize)
+        .collect()
+}
+
+fn descending(order: &str, ordering: Ordering) -> Ordering {
+    if order == "DESC" {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn contains_ignore_case(field: &Option<String>, term: &str) -> bool {
+    field
+        .as_ref()
+        .map(|value| value.to_lowercase().contains(term))
+        .unwrap_or(false)
+}
+
+fn facet(courses: &[&Course], field: fn(&Course) -> Option<&'static str>) -> Vec<FacetCount> {
+    let mut counts: BTreeMap<Option<String>, i64> = BTreeMap::new();
+    for course in courses {
+        *counts.entry(field(course).map(String::from)).or_insert(0) += 1;
+    }
+
+    let mut facets: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    facets.sort_by_key(|facet| std::cmp::Reverse(facet.count));
+    facets
+}
+
+#[async_trait]
+impl TeacherRepository for MemoryRepository {
+    async fn get_all_teachers(&self, query: &TeacherQuery) -> Result<(Vec<Teacher>, i64), MyError> {
+        let (page, per_page) = query.page_bounds();
+        //validates sort and order the same way the sql backends do
+        query.order_by()?;
+        let order = order_sql(&query.order)?;
+
+        let name = query.name.as_ref().map(|name| name.to_lowercase());
+        let mut teachers: Vec<Teacher> = self
+            .store()
+            .teachers
+            .values()
+            .filter(|t| name.as_ref().is_none_or(|name| t.name.to_lowercase().contains(name)))
+            .cloned()
+            .collect();
+
+        let total = teachers.len() as i64;
+
+        teachers.sort_by(|a, b| {
+            let ordering = match query.sort.as_deref() {
+                Some("name") => a.name.cmp(&b.name).then(a.id.cmp(&b.id)),
+                _ => a.id.cmp(&b.id),
+            };
+            descending(order, ordering)
+        });
+
+        Ok((paginate(teachers, page, per_page), total))
+    }
+
+    async fn get_teacher_details(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        self.store().teacher(teacher_id).cloned()
+    }
+
+    async fn post_new_teacher(&self, new_teacher: CreateTeacher) -> Result<Teacher, MyError> {
+        let mut store = self.store();
+        let teacher = Teacher {
+            id: store.next_id(),
+            name: new_teacher.name,
+            picture_url: new_teacher.picture_url,
+            profile: new_teacher.profile,
+            version: 1,
+        };
+        store.teachers.insert(teacher.id, teacher.clone());
+
+        Ok(teacher)
+    }
+
+    async fn update_teacher_details(
+        &self, teacher_id: i32, update_teacher: UpdateTeacher, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        let mut store = self.store();
+        let current = store.teacher(teacher_id)?.clone();
+        if_match.check(current.version)?;
+
+        let teacher = update_teacher.apply_to(current);
+        let teacher = Teacher { version: teacher.version + 1, ..teacher };
+        store.teachers.insert(teacher.id, teacher.clone());
+
+        Ok(teacher)
+    }
+
+    async fn patch_teacher_details(
+        &self, teacher_id: i32, patch: &MergePatch, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        let mut store = self.store();
+        let current = store.teacher(teacher_id)?.clone();
+        if_match.check(current.version)?;
+
+        let teacher = current.patched(patch)?;
+        let teacher = Teacher { version: teacher.version + 1, ..teacher };
+        store.teachers.insert(teacher.id, teacher.clone());
+
+        Ok(teacher)
+    }
+
+    async fn delete_teacher(
+        &self, teacher_id: i32, deletion: TeacherDeletion, if_match: &IfMatch
+    ) -> Result<TrashItem, MyError> {
+        let mut store = self.store();
+        let teacher = store.teacher(teacher_id)?.clone();
+        if_match.check(teacher.version)?;
+        let courses: Vec<Course> = store.courses.values().filter(|c| c.teacher_id == teacher_id).cloned().collect();
+
+        let deleted_at = Utc::now();
+        match deletion {
+            TeacherDeletion::Refuse if !courses.is_empty() => {
+                return Err(MyError::Conflict("Unable to delete teacher, it still has courses".into()));
+            }
+            TeacherDeletion::Refuse => {}
+            TeacherDeletion::Cascade => {
+                for course in courses {
+                    let course = Course { version: course.version + 1, ..course };
+                    store.courses.remove(&course.id);
+                    store.trashed_courses.insert(course.id, (course, deleted_at));
+                }
+            }
+            TeacherDeletion::Reassign(to_teacher_id) => {
+                //put everything back when one of the courses cannot move
+                let (courses_before, transfers_before) = (store.courses.clone(), store.transfers.len());
+                for course in courses {
+                    if let Err(err) = store.transfer_course(course, to_teacher_id) {
+                        store.courses = courses_before;
+                        store.transfers.truncate(transfers_before);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        let teacher = Teacher { version: teacher.version + 1, ..teacher };
+        store.teachers.remove(&teacher_id);
+        store.trashed_teachers.insert(teacher_id, (teacher.clone(), deleted_at));
+
+        Ok(TrashItem::teacher(&teacher, deleted_at))
+    }
+}
+
+#[async_trait]
+impl CourseRepository for MemoryRepository {
+    async fn get_course_for_teacher(
+        &self, teacher_id: i32, query: &CourseQuery
+    ) -> Result<(Vec<Course>, i64), MyError> {
+        let (page, per_page) = query.page_bounds();
+        query.order_by()?;
+        let order = order_sql(&query.order)?;
+
+        let now = Utc::now();
+        let status = query.status_filter();
+        let store = self.store();
+        //courses the teacher leads or helps with
+        let mut courses: Vec<Course> = store
+            .courses
+            .values()
+            .filter(|c| {
+                c.teacher_id == teacher_id
+                    || store.instructors.iter().any(|i| i.course_id == c.id && i.teacher_id == teacher_id)
+            })
+            .filter(|c| status.is_none_or(|status| c.status.at(c.published_at, now) == status))
+            .filter(|c| query.language.is_none() || c.language == query.language)
+            .filter(|c| query.level.is_none() || c.level == query.level)
+            .filter(|c| query.min_price.is_none_or(|min| c.price.is_some_and(|p| p.amount >= min)))
+            .filter(|c| query.max_price.is_none_or(|max| c.price.is_some_and(|p| p.amount <= max)))
+            .filter(|c| query.currency.is_none_or(|cur| c.price.is_some_and(|p| p.currency == cur)))
+            .map(|c| c.clone().refreshed_at(now))
+            .collect();
+
+        courses.sort_by(|a, b| {
+            let ordering = match query.sort.as_deref() {
+                Some("name") => a.name.cmp(&b.name),
+                Some("time") => a.time.cmp(&b.time),
+                Some("price") => a.price.map(|p| p.amount).cmp(&b.price.map(|p| p.amount)),
+                _ => Ordering::Equal,
+            };
+            descending(order, ordering.then(a.id.cmp(&b.id)))
+        });
+
+        let total = courses.len() as i64;
+        Ok((paginate(courses, page, per_page), total))
+    }
+
+    async fn search_courses(
+        &self, query: &CourseSearchQuery
+    ) -> Result<(Vec<Course>, i64, SearchFacets), MyError> {
+        let terms: Vec<String> = query.terms()?.split_whitespace().map(str::to_lowercase).collect();
+        let (page, per_page) = query.page_bounds();
+
+        let now = Utc::now();
+        let store = self.store();
+        //relevance is the number of terms a course matches
+        let mut matches: Vec<(usize, &Course)> = store
+            .courses
+            .values()
+            .filter(|c| c.is_live(now))
+            .filter(|c| query.language.is_none() || c.language == query.language)
+            .filter(|c| query.level.is_none() || c.level == query.level)
+            .filter(|c| query.format.is_none() || c.format == query.format)
+            .map(|c| {
+                let score = terms
+                    .iter()
+                    .filter(|term| {
+                        c.name.to_lowercase().contains(term.as_str())
+                            || contains_ignore_case(&c.description, term)
+                            || contains_ignore_case(&c.structure, term)
+                    })
+                    .count();
+                (score, c)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        matches.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.id.cmp(&b.id)));
+
+        let courses: Vec<&Course> = matches.into_iter().map(|(_, course)| course).collect();
+        let facets = SearchFacets {
+            language: facet(&courses, |c| c.language.map(|v| v.as_str())),
+            level: facet(&courses, |c| c.level.map(|v| v.as_str())),
+            format: facet(&courses, |c| c.format.map(|v| v.as_str())),
+        };
+        let total = courses.len() as i64;
+        let courses = paginate(courses.into_iter().map(|c| c.clone().refreshed_at(now)).collect(), page, per_page);
+
+        Ok((courses, total, facets))
+    }
+
+    async fn get_course_detail(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        self.store().course(teacher_id, course_id).map(|c| c.clone().refreshed_at(Utc::now()))
+    }
+
+    async fn post_new_course(&self, new_course: CreateCourse, changed_by: i32) -> Result<Course, MyError> {
+        let mut store = self.store();
+        if store.teacher(new_course.teacher_id).is_err() {
+            return Err(MyError::InvalidReference("A referenced record does not exist".into()));
+        }
+
+        if let Some(booking) = Booking::of_new(&new_course)? {
+            booking.check(store.courses.values())?;
+        }
+
+        let course = Course {
+            teacher_id: new_course.teacher_id,
+            id: store.next_id(),
+            name: new_course.name,
+            time: Some(Utc::now()),
+            starts_at: new_course.starts_at,
+            ends_at: new_course.ends_at,
+            room: new_course.room,
+            capacity: new_course.capacity,
+            status: CourseStatus::Draft,
+            published_at: None,
+            version: 1,
+            description: new_course.description,
+            format: new_course.format,
+            structure: new_course.structure,
+            duration: new_course.duration,
+            price: new_course.price,
+            language: new_course.language,
+            level: new_course.level,
+            display_price: None,
+        };
+        store.courses.insert(course.id, course.clone());
+        store.record_revision(&course, changed_by);
+        store.assign_slug(&course);
+
+        Ok(course)
+    }
+
+    async fn update_course(
+        &self, teacher_id: i32, course_id: i32, update_course: UpdateCourse, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        let mut store = self.store();
+        let current = store.course(teacher_id, course_id)?.clone();
+        if_match.check(current.version)?;
+
+        let course = store.save_course(update_course.replace(current), changed_by)?;
+        Ok(course.refreshed_at(Utc::now()))
+    }
+
+    async fn patch_course(
+        &self, teacher_id: i32, course_id: i32, patch: &MergePatch, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        let mut store = self.store();
+        let current = store.course(teacher_id, course_id)?.clone();
+        if_match.check(current.version)?;
+
+        let course = store.save_course(current.patched(patch)?, changed_by)?;
+        Ok(course.refreshed_at(Utc::now()))
+    }
+
+    async fn delete_course(&self, teacher_id: i32, course_id: i32, if_match: &IfMatch) -> Result<TrashItem, MyError> {
+        let mut store = self.store();
+        let course = store.course(teacher_id, course_id)?.clone();
+        if_match.check(course.version)?;
+
+        let course = Course { version: course.version + 1, ..course };
+        let deleted_at = Utc::now();
+        store.courses.remove(&course_id);
+        store.trashed_courses.insert(course_id, (course.clone(), deleted_at));
+
+        Ok(TrashItem::course(&course, deleted_at))
+    }
+
+    async fn get_courses_between(
+        &self, teacher_id: i32, from: DateTime<Utc>, to: DateTime<Utc>
+    ) -> Result<Vec<Course>, MyError> {
+        let mut courses: Vec<Course> = self
+            .store()
+            .courses
+            .values()
+            .filter(|c| c.teacher_id == teacher_id)
+            .filter(|c| match (c.starts_at, c.ends_at) {
+                (Some(start), Some(end)) => overlaps(start, end, from, to),
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        courses.sort_by_key(|c| (c.starts_at, c.id));
+        Ok(courses)
+    }
+
+    async fn change_course_status(
+        &self, teacher_id: i32, course_id: i32, transition: Transition, changed_by: i32
+    ) -> Result<Course, MyError> {
+        let now = Utc::now();
+        let mut store = self.store();
+        let course = store.course(teacher_id, course_id)?.clone().refreshed_at(now);
+
+        let (status, published_at) = transition.apply(&course, now)?;
+        let change = StatusChange {
+            id: store.next_id(),
+            course_id: course.id,
+            from_status: course.status,
+            to_status: status,
+            changed_by: Some(changed_by),
+            changed_at: now,
+        };
+        store.status_changes.insert(change.id, change);
+
+        let course = Course { status, published_at, version: course.version + 1, ..course };
+        store.courses.insert(course.id, course.clone());
+
+        Ok(course)
+    }
+
+    async fn get_status_changes(&self, teacher_id: i32, course_id: i32) -> Result<Vec<StatusChange>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        Ok(store
+            .status_changes
+            .values()
+            .filter(|c| c.course_id == course_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_revisions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseRevision>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        Ok(store.revisions_of(course_id))
+    }
+
+    async fn get_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32
+    ) -> Result<CourseRevision, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        store.revision(course_id, revision)
+    }
+
+    async fn restore_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32, changed_by: i32
+    ) -> Result<Course, MyError> {
+        let mut store = self.store();
+        let current = store.course(teacher_id, course_id)?.clone();
+        let revision = store.revision(current.id, revision)?;
+
+        let course = store.save_course(revision.restore_onto(current), changed_by)?;
+        Ok(course.refreshed_at(Utc::now()))
+    }
+
+    async fn transfer_course(&self, teacher_id: i32, course_id: i32, to_teacher_id: i32) -> Result<Course, MyError> {
+        let mut store = self.store();
+        let current = store.course(teacher_id, course_id)?.clone();
+
+        let course = store.transfer_course(current, to_teacher_id)?;
+        Ok(course.refreshed_at(Utc::now()))
+    }
+
+    async fn find_transferred_course(&self, teacher_id: i32, course_id: i32) -> Result<Option<i32>, MyError> {
+        let store = self.store();
+        if !store.transfers.contains(&(course_id, teacher_id)) {
+            return Ok(None);
+        }
+        let current = store
+            .courses
+            .get(&course_id)
+            .or_else(|| store.trashed_courses.get(&course_id).map(|(course, _)| course));
+        Ok(current.map(|course| course.teacher_id).filter(|id| *id != teacher_id))
+    }
+
+    async fn get_course_by_id(&self, course_id: i32) -> Result<Course, MyError> {
+        let store = self.store();
+        //the teacher of a course in the trash too, so the lookup answers 410 for it
+        let teacher_id = store
+            .courses
+            .get(&course_id)
+            .or_else(|| store.trashed_courses.get(&course_id).map(|(course, _)| course))
+            .map(|course| course.teacher_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Course, "Course ID not found".into()))?;
+
+        store.course(teacher_id, course_id).map(|c| c.clone().refreshed_at(Utc::now()))
+    }
+
+    async fn get_course_slug(&self, course_id: i32) -> Result<Option<String>, MyError> {
+        Ok(self.store().slug_of(course_id))
+    }
+
+    async fn find_course_slug(&self, slug: &str) -> Result<Option<SlugLookup>, MyError> {
+        let store = self.store();
+        let course_id = store.slugs.iter().find(|(other, _)| other == slug).map(|(_, id)| *id);
+
+        Ok(course_id.and_then(|id| store.slug_of(id).map(|current| SlugLookup::of(slug, id, current))))
+    }
+}
+
+#[async_trait]
+impl StudentRepository for MemoryRepository {
+    async fn get_all_students(&self) -> Result<Vec<Student>, MyError> {
+        Ok(self.store().students.values().cloned().collect())
+    }
+
+    async fn get_student_details(&self, student_id: i32) -> Result<Student, MyError> {
+        self.store().student(student_id).cloned()
+    }
+
+    async fn post_new_student(&self, new_student: CreateStudent) -> Result<Student, MyError> {
+        let mut store = self.store();
+        if store.students.values().any(|s| s.email == new_student.email) {
+            return Err(MyError::Conflict("A record with the same value already exists".into()));
+        }
+
+        let student = Student {
+            id: store.next_id(),
+            name: new_student.name,
+            email: new_student.email,
+            profile: new_student.profile,
+        };
+        store.students.insert(student.id, student.clone());
+
+        Ok(student)
+    }
+
+    async fn update_student_details(
+        &self, student_id: i32, update_student: UpdateStudent
+    ) -> Result<Student, MyError> {
+        let mut store = self.store();
+        let current = store.student(student_id)?.clone();
+
+        let student = update_student.apply_to(current);
+        store.students.insert(student.id, student.clone());
+
+        Ok(student)
+    }
+
+    async fn delete_student(&self, student_id: i32) -> Result<String, MyError> {
+        let mut store = self.store();
+        store.student(student_id)?;
+        store.enrollments.retain(|_, e| e.student_id != student_id);
+        store.reservations.retain(|_, r| r.student_id != student_id);
+
+        store.students.remove(&student_id);
+        Ok("Deleted 1 record".into())
+    }
+}
+
+#[async_trait]
+impl EnrollmentRepository for MemoryRepository {
+    async fn post_new_enrollment(
+        &self, teacher_id: i32, course_id: i32, new_enrollment: CreateEnrollment
+    ) -> Result<Enrollment, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+        let student_id = store.student(new_enrollment.student_id)?.id;
+
+        if store
+            .enrollments
+            .values()
+            .any(|e| e.student_id == student_id && e.course_id == course_id)
+        {
+            return Err(MyError::Conflict("Student already enrolled in this course".into()));
+        }
+
+        let enrollment = Enrollment {
+            id: store.next_id(),
+            student_id,
+            course_id,
+            enrolled_at: Some(Utc::now()),
+        };
+        store.enrollments.insert(enrollment.id, enrollment.clone());
+
+        Ok(enrollment)
+    }
+
+    async fn get_enrollments_for_course(
+        &self, teacher_id: i32, course_id: i32
+    ) -> Result<Vec<Enrollment>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        Ok(store
+            .enrollments
+            .values()
+            .filter(|e| e.course_id == course_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_enrollments_for_student(&self, student_id: i32) -> Result<Vec<Enrollment>, MyError> {
+        let store = self.store();
+        let student_id = store.student(student_id)?.id;
+
+        Ok(store
+            .enrollments
+            .values()
+            .filter(|e| e.student_id == student_id && store.courses.contains_key(&e.course_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_enrollment(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<String, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let before = store.enrollments.len();
+        store
+            .enrollments
+            .retain(|_, e| !(e.course_id == course_id && e.student_id == student_id));
+
+        match before - store.enrollments.len() {
+            0 => Err(MyError::NotFound(Resource::Enrollment, "Enrollment not found".into())),
+            deleted => Ok(format!("Deleted {} record", deleted)),
+        }
+    }
+}
+
+#[async_trait]
+impl SyllabusRepository for MemoryRepository {
+    async fn get_modules(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseModule>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        Ok(store.modules_of(course_id))
+    }
+
+    async fn post_new_module(
+        &self, teacher_id: i32, course_id: i32, new_module: CreateModule
+    ) -> Result<CourseModule, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let position = store.modules_of(course_id).last().map_or(0, |m| m.position) + 1;
+        let module = CourseModule {
+            id: store.next_id(),
+            course_id,
+            title: new_module.title,
+            summary: new_module.summary,
+            position,
+        };
+        store.modules.insert(module.id, module.clone());
+
+        Ok(module)
+    }
+
+    async fn update_module(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, update_module: UpdateModule
+    ) -> Result<CourseModule, MyError> {
+        let mut store = self.store();
+        let current = store.module(teacher_id, course_id, module_id)?.clone();
+
+        let module = update_module.apply_to(current);
+        store.modules.insert(module.id, module.clone());
+
+        Ok(module)
+    }
+
+    async fn delete_module(&self, teacher_id: i32, course_id: i32, module_id: i32) -> Result<String, MyError> {
+        let mut store = self.store();
+        let module_id = store.module(teacher_id, course_id, module_id)?.id;
+
+        store.modules.remove(&module_id);
+        store.lessons.retain(|_, l| l.module_id != module_id);
+        Ok("Deleted 1 record".into())
+    }
+
+    async fn reorder_modules(
+        &self, teacher_id: i32, course_id: i32, reorder: Reorder
+    ) -> Result<Vec<CourseModule>, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+        let current: Vec<i32> = store.modules_of(course_id).iter().map(|m| m.id).collect();
+        reorder.check(&current)?;
+
+        for (position, module_id) in reorder.ids.iter().enumerate() {
+            if let Some(module) = store.modules.get_mut(module_id) {
+                module.position = position as i32 + 1;
+            }
+        }
+
+        Ok(store.modules_of(course_id))
+    }
+
+    async fn get_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32
+    ) -> Result<Vec<Lesson>, MyError> {
+        let store = self.store();
+        let module_id = store.module(teacher_id, course_id, module_id)?.id;
+
+        Ok(store.lessons_of(module_id))
+    }
+
+    async fn get_lessons_for_course(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Lesson>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        Ok(store
+            .modules_of(course_id)
+            .iter()
+            .flat_map(|m| store.lessons_of(m.id))
+            .collect())
+    }
+
+    async fn post_new_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, new_lesson: CreateLesson
+    ) -> Result<Lesson, MyError> {
+        let mut store = self.store();
+        let module_id = store.module(teacher_id, course_id, module_id)?.id;
+
+        let position = store.lessons_of(module_id).last().map_or(0, |l| l.position) + 1;
+        let lesson = Lesson {
+            id: store.next_id(),
+            module_id,
+            title: new_lesson.title,
+            body: new_lesson.body,
+            duration_minutes: new_lesson.duration_minutes,
+            attachments: new_lesson.attachments,
+            position,
+        };
+        store.lessons.insert(lesson.id, lesson.clone());
+
+        Ok(lesson)
+    }
+
+    async fn update_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32, update_lesson: UpdateLesson
+    ) -> Result<Lesson, MyError> {
+        let mut store = self.store();
+        let current = store.lesson(teacher_id, course_id, module_id, lesson_id)?.clone();
+
+        let lesson = update_lesson.apply_to(current);
+        store.lessons.insert(lesson.id, lesson.clone());
+
+        Ok(lesson)
+    }
+
+    async fn delete_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+    ) -> Result<String, MyError> {
+        let mut store = self.store();
+        let lesson_id = store.lesson(teacher_id, course_id, module_id, lesson_id)?.id;
+
+        store.lessons.remove(&lesson_id);
+        Ok("Deleted 1 record".into())
+    }
+
+    async fn reorder_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, reorder: Reorder
+    ) -> Result<Vec<Lesson>, MyError> {
+        let mut store = self.store();
+        let module_id = store.module(teacher_id, course_id, module_id)?.id;
+        let current: Vec<i32> = store.lessons_of(module_id).iter().map(|l| l.id).collect();
+        reorder.check(&current)?;
+
+        for (position, lesson_id) in reorder.ids.iter().enumerate() {
+            if let Some(lesson) = store.lessons.get_mut(lesson_id) {
+                lesson.position = position as i32 + 1;
+            }
+        }
+
+        Ok(store.lessons_of(module_id))
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MemoryRepository {
+    async fn get_sessions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let mut sessions: Vec<CourseSession> = store
+            .sessions
+            .values()
+            .filter(|s| s.course_id == course_id)
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| (s.starts_at, s.id));
+        Ok(sessions)
+    }
+
+    async fn get_sessions_for_teacher(&self, teacher_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        let store = self.store();
+        let teacher_id = store.teacher(teacher_id)?.id;
+
+        let mut sessions: Vec<CourseSession> = store
+            .sessions
+            .values()
+            .filter(|s| store.courses.get(&s.course_id).is_some_and(|c| c.teacher_id == teacher_id))
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| (s.starts_at, s.id));
+        Ok(sessions)
+    }
+
+    async fn post_new_session(
+        &self, teacher_id: i32, course_id: i32, new_session: CreateSession
+    ) -> Result<CourseSession, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let session = CourseSession {
+            id: store.next_id(),
+            course_id,
+            starts_at: new_session.starts_at,
+            ends_at: new_session.ends_at,
+            timezone: new_session.timezone,
+            location: new_session.location,
+            meeting_url: new_session.meeting_url,
+            frequency: new_session.frequency,
+            occurrences: new_session.occurrences,
+            repeat_until: new_session.repeat_until,
+        };
+        store.sessions.insert(session.id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn update_session(
+        &self, teacher_id: i32, course_id: i32, session_id: i32, update_session: UpdateSession
+    ) -> Result<CourseSession, MyError> {
+        let mut store = self.store();
+        let current = store.session(teacher_id, course_id, session_id)?.clone();
+
+        let session = update_session.apply_to(current)?;
+        store.sessions.insert(session.id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn delete_session(&self, teacher_id: i32, course_id: i32, session_id: i32) -> Result<String, MyError> {
+        let mut store = self.store();
+        let session_id = store.session(teacher_id, course_id, session_id)?.id;
+
+        store.sessions.remove(&session_id);
+        Ok("Deleted 1 record".into())
+    }
+}
+
+#[async_trait]
+impl SeatRepository for MemoryRepository {
+    async fn get_reservations(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Reservation>, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let mut reservations = store.reservations_of(course_id)?;
+        reservations.sort_by_key(|r| (r.waitlist_position, r.id));
+        Ok(reservations)
+    }
+
+    async fn get_reservation(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<Reservation, MyError> {
+        let store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        store.reservation(course_id, student_id)
+    }
+
+    async fn reserve_seat(
+        &self, teacher_id: i32, course_id: i32, new_reservation: CreateReservation
+    ) -> Result<Reservation, MyError> {
+        //the store stays locked from the seat count to the insert
+        let mut store = self.store();
+        let course = store.course(teacher_id, course_id)?.clone();
+        let student_id = store.student(new_reservation.student_id)?.id;
+
+        let reservations = store.reservations_of(course.id)?;
+        if reservations.iter().any(|r| r.student_id == student_id) {
+            return Err(MyError::Conflict("Student already has a seat or a waitlist place in this course".into()));
+        }
+        let reserved = reservations.iter().filter(|r| r.status == SeatStatus::Reserved).count();
+
+        let row = ReservationRow {
+            id: store.next_id(),
+            course_id: course.id,
+            student_id,
+            status: seat_status(course.capacity, reserved as i64).as_str().into(),
+            reserved_at: Some(Utc::now()),
+        };
+        store.reservations.insert(row.id, row);
+
+        store.reservation(course.id, student_id)
+    }
+
+    async fn release_seat(&self, teacher_id: i32, course_id: i32, student_id: i32) -> Result<String, MyError> {
+        let mut store = self.store();
+        let course = store.course(teacher_id, course_id)?.clone();
+
+        let id = store
+            .reservations
+            .values()
+            .find(|r| r.course_id == course.id && r.student_id == student_id)
+            .map(|r| r.id)
+            .ok_or_else(|| MyError::NotFound(Resource::Reservation, "Reservation not found".into()))?;
+        store.reservations.remove(&id);
+        store.fill_seats(course.id, course.capacity);
+
+        Ok("Deleted 1 record".into())
+    }
+}
+
+#[async_trait]
+impl InstructorRepository for MemoryRepository {
+    async fn get_instructors(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseInstructor>, MyError> {
+        let store = self.store();
+        let course = store.course(teacher_id, course_id)?;
+        let mut instructors = vec![CourseInstructor::lead(course, store.teacher(teacher_id)?)];
+        //teachers in the trash are left out, their names are read as they are now
+        instructors.extend(
+            store
+                .instructors
+                .iter()
+                .filter(|i| i.course_id == course.id)
+                .filter_map(|i| {
+                    let teacher = store.teachers.get(&i.teacher_id)?;
+                    Some(CourseInstructor { name: teacher.name.clone(), ..i.clone() })
+                }),
+        );
+        Ok(instructors)
+    }
+
+    async fn add_instructor(
+        &self, teacher_id: i32, course_id: i32, new_instructor: AddInstructor
+    ) -> Result<CourseInstructor, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+        let teacher = match store.teacher(new_instructor.teacher_id) {
+            Ok(teacher) => teacher.clone(),
+            Err(_) => return Err(MyError::InvalidReference("A referenced record does not exist".into())),
+        };
+        if store.instructors.iter().any(|i| i.course_id == course_id && i.teacher_id == teacher.id) {
+            return Err(MyError::Conflict("A record with the same value already exists".into()));
+        }
+
+        let instructor = CourseInstructor {
+            course_id,
+            teacher_id: teacher.id,
+            name: teacher.name,
+            role: new_instructor.role,
+            added_at: Some(Utc::now()),
+        };
+        store.instructors.push(instructor.clone());
+        Ok(instructor)
+    }
+
+    async fn remove_instructor(
+        &self, teacher_id: i32, course_id: i32, instructor_id: i32
+    ) -> Result<String, MyError> {
+        let mut store = self.store();
+        let course_id = store.course(teacher_id, course_id)?.id;
+
+        let before = store.instructors.len();
+        store.instructors.retain(|i| !(i.course_id == course_id && i.teacher_id == instructor_id));
+        match before - store.instructors.len() {
+            0 => Err(MyError::NotFound(Resource::Instructor, "Instructor not found".into())),
+            deleted => Ok(format!("Deleted {} record", deleted)),
+        }
+    }
+}
+
+#[async_trait]
+impl TrashRepository for MemoryRepository {
+    async fn get_trash(&self) -> Result<Vec<TrashItem>, MyError> {
+        let store = self.store();
+        let mut items: Vec<TrashItem> = store
+            .trashed_teachers
+            .values()
+            .map(|(teacher, deleted_at)| TrashItem::teacher(teacher, *deleted_at))
+            .chain(store.trashed_courses.values().map(|(course, deleted_at)| TrashItem::course(course, *deleted_at)))
+            .collect();
+        items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(b.id.cmp(&a.id)));
+        Ok(items)
+    }
+
+    async fn restore_teacher(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        let mut store = self.store();
+        let teacher = match store.trashed_teachers.remove(&teacher_id) {
+            Some((teacher, _)) => teacher,
+            None => {
+                store.teacher(teacher_id)?;
+                return Err(MyError::Conflict("Teacher is not deleted".into()));
+            }
+        };
+
+        let teacher = Teacher { version: teacher.version + 1, ..teacher };
+        store.teachers.insert(teacher.id, teacher.clone());
+        Ok(teacher)
+    }
+
+    async fn restore_course(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        let mut store = self.store();
+        let course = match store.trashed_course(teacher_id, course_id) {
+            Some(course) => course.clone(),
+            None => {
+                store.course(teacher_id, course_id)?;
+                return Err(MyError::Conflict("Course is not deleted".into()));
+            }
+        };
+        if store.trashed_teachers.contains_key(&teacher_id) {
+            return Err(MyError::Conflict("The course's teacher is deleted, restore the teacher first".into()));
+        }
+        //the slot may have been taken while the course was in the trash
+        if let Some(booking) = Booking::of(&course)? {
+            booking.check(store.courses.values())?;
+        }
+
+        let course = Course { version: course.version + 1, ..course };
+        store.trashed_courses.remove(&course_id);
+        store.courses.insert(course.id, course.clone());
+        Ok(course.refreshed_at(Utc::now()))
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, MyError> {
+        let mut store = self.store();
+        let course_ids: Vec<i32> = store
+            .trashed_courses
+            .iter()
+            .filter(|(_, (_, deleted_at))| *deleted_at < before)
+            .map(|(id, _)| *id)
+            .collect();
+        for course_id in &course_ids {
+            store.purge_course(*course_id);
+        }
+
+        //a teacher stays while a course still refers to it, like the foreign key on course.teacher_id
+        let teacher_ids: Vec<i32> = store
+            .trashed_teachers
+            .iter()
+            .filter(|(_, (_, deleted_at))| *deleted_at < before)
+            .map(|(id, _)| *id)
+            .filter(|id| !store.trashed_courses.values().any(|(course, _)| course.teacher_id == *id))
+            .collect();
+        for teacher_id in &teacher_ids {
+            store.trashed_teachers.remove(teacher_id);
+            store.instructors.retain(|i| i.teacher_id != *teacher_id);
+            for user in store.users.values_mut().filter(|u| u.teacher_id == Some(*teacher_id)) {
+                user.teacher_id = None;
+            }
+        }
+
+        Ok((course_ids.len() + teacher_ids.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn get_user_by_username(&self, username: &str) -> Result<User, MyError> {
+        self.store()
+            .users
+            .values()
+            .find(|u| u.username == username)
+            .cloned()
+            .ok_or_else(|| MyError::NotFound(Resource::User, "User not found".into()))
+    }
+
+    async fn post_new_user(&self, new_user: CreateUser, password_hash: String) -> Result<User, MyError> {
+        let mut store = self.store();
+        if store.users.values().any(|u| u.username == new_user.username) {
+            return Err(MyError::Conflict("Username already taken".into()));
+        }
+
+        let user = User {
+            id: store.next_id(),
+            username: new_user.username,
+            password_hash,
+            role: new_user.role,
+            teacher_id: new_user.teacher_id,
+            student_id: new_user.student_id,
+        };
+        store.users.insert(user.id, user.clone());
+
+        Ok(user)
+    }
+
+    async fn count_admins(&self) -> Result<i64, MyError> {
+        Ok(self.store().users.values().filter(|u| u.role == Role::Admin).count() as i64)
+    }
+}
+
+//nothing to migrate, the schema is the structs above
+#[async_trait]
+impl SchemaMigrations for MemoryRepository {
+    async fn run_migrations(&self) -> Result<(), MyError> {
+        Ok(())
+    }
+
+    async fn revert_last_migration(&self) -> Result<Option<i64>, MyError> {
+        Ok(None)
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, MyError> {
+        Ok(vec![])
+    }
+}
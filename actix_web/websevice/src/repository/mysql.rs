@@ -0,0 +1,404 @@
+use crate::dbaccess::{
+    course::*, enrollment::*, instructor::*, lifecycle::*, revision::*, seat::*, session::*, slug::*,
+    student::*, syllabus::*, teacher::*, transfer::*, trash::*, user::*,
+};
+use crate::errors::MyError;
+use crate::migrate;
+use crate::models::course::{Course, CourseQuery, CreateCourse, UpdateCourse};
+use crate::models::enrollment::{CreateEnrollment, Enrollment};
+use crate::models::etag::IfMatch;
+use crate::models::instructor::{AddInstructor, CourseInstructor};
+use crate::models::lifecycle::{StatusChange, Transition};
+use crate::models::patch::MergePatch;
+use crate::models::revision::CourseRevision;
+use crate::models::search::{CourseSearchQuery, SearchFacets};
+use crate::models::seat::{CreateReservation, Reservation};
+use crate::models::session::{CourseSession, CreateSession, UpdateSession};
+use crate::models::slug::SlugLookup;
+use crate::models::student::{CreateStudent, Student, UpdateStudent};
+use crate::models::syllabus::{
+    CourseModule, CreateLesson, CreateModule, Lesson, Reorder, UpdateLesson, UpdateModule,
+};
+use crate::models::teacher::{CreateTeacher, Teacher, TeacherQuery, UpdateTeacher};
+use crate::models::transfer::TeacherDeletion;
+use crate::models::trash::TrashItem;
+use crate::models::user::{CreateUser, User};
+use crate::repository::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+
+//MySQL backend, a thin wrapper around the functions in dbaccess
+#[derive(Debug, Clone)]
+pub struct MySqlRepository {
+    pub pool: MySqlPool,
+}
+
+impl MySqlRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        MySqlRepository { pool }
+    }
+
+    pub async fn connect(db_url: &str) -> Result<Self, MyError> {
+        let pool = MySqlPoolOptions::new().connect(db_url).await?;
+        Ok(MySqlRepository::new(pool))
+    }
+}
+
+#[async_trait]
+impl TeacherRepository for MySqlRepository {
+    async fn get_all_teachers(&self, query: &TeacherQuery) -> Result<(Vec<Teacher>, i64), MyError> {
+        get_all_teachers_db(&self.pool, query).await
+    }
+
+    async fn get_teacher_details(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        get_teacher_details_db(&self.pool, teacher_id).await
+    }
+
+    async fn post_new_teacher(&self, new_teacher: CreateTeacher) -> Result<Teacher, MyError> {
+        post_new_teacher_db(&self.pool, new_teacher).await
+    }
+
+    async fn update_teacher_details(
+        &self, teacher_id: i32, update_teacher: UpdateTeacher, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        update_teacher_details_db(&self.pool, teacher_id, update_teacher, if_match).await
+    }
+
+    async fn patch_teacher_details(
+        &self, teacher_id: i32, patch: &MergePatch, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        patch_teacher_details_db(&self.pool, teacher_id, patch, if_match).await
+    }
+
+    async fn delete_teacher(
+        &self, teacher_id: i32, deletion: TeacherDeletion, if_match: &IfMatch
+    ) -> Result<TrashItem, MyError> {
+        delete_teacher_db(&self.pool, teacher_id, deletion, if_match).await
+    }
+}
+
+#[async_trait]
+impl CourseRepository for MySqlRepository {
+    async fn get_course_for_teacher(
+        &self, teacher_id: i32, query: &CourseQuery
+    ) -> Result<(Vec<Course>, i64), MyError> {
+        get_course_for_teacher_db(&self.pool, teacher_id, query).await
+    }
+
+    async fn search_courses(
+        &self, query: &CourseSearchQuery
+    ) -> Result<(Vec<Course>, i64, SearchFacets), MyError> {
+        search_courses_db(&self.pool, query).await
+    }
+
+    async fn get_course_detail(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        get_course_detail_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn post_new_course(&self, new_course: CreateCourse, changed_by: i32) -> Result<Course, MyError> {
+        post_new_course_db(&self.pool, new_course, changed_by).await
+    }
+
+    async fn update_course(
+        &self, teacher_id: i32, course_id: i32, update_course: UpdateCourse, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        update_course_db(&self.pool, teacher_id, course_id, update_course, changed_by, if_match).await
+    }
+
+    async fn patch_course(
+        &self, teacher_id: i32, course_id: i32, patch: &MergePatch, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        patch_course_db(&self.pool, teacher_id, course_id, patch, changed_by, if_match).await
+    }
+
+    async fn delete_course(&self, teacher_id: i32, course_id: i32, if_match: &IfMatch) -> Result<TrashItem, MyError> {
+        delete_course_db(&self.pool, teacher_id, course_id, if_match).await
+    }
+
+    async fn get_courses_between(
+        &self, teacher_id: i32, from: DateTime<Utc>, to: DateTime<Utc>
+    ) -> Result<Vec<Course>, MyError> {
+        get_courses_between_db(&self.pool, teacher_id, from, to).await
+    }
+
+    async fn change_course_status(
+        &self, teacher_id: i32, course_id: i32, transition: Transition, changed_by: i32
+    ) -> Result<Course, MyError> {
+        change_course_status_db(&self.pool, teacher_id, course_id, transition, changed_by).await
+    }
+
+    async fn get_status_changes(&self, teacher_id: i32, course_id: i32) -> Result<Vec<StatusChange>, MyError> {
+        get_status_changes_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_revisions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseRevision>, MyError> {
+        get_revisions_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32
+    ) -> Result<CourseRevision, MyError> {
+        get_revision_db(&self.pool, teacher_id, course_id, revision).await
+    }
+
+    async fn restore_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32, changed_by: i32
+    ) -> Result<Course, MyError> {
+        restore_revision_db(&self.pool, teacher_id, course_id, revision, changed_by).await
+    }
+
+    async fn transfer_course(&self, teacher_id: i32, course_id: i32, to_teacher_id: i32) -> Result<Course, MyError> {
+        transfer_course_db(&self.pool, teacher_id, course_id, to_teacher_id).await
+    }
+
+    async fn find_transferred_course(&self, teacher_id: i32, course_id: i32) -> Result<Option<i32>, MyError> {
+        find_transferred_course_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_course_by_id(&self, course_id: i32) -> Result<Course, MyError> {
+        get_course_by_id_db(&self.pool, course_id).await
+    }
+
+    async fn get_course_slug(&self, course_id: i32) -> Result<Option<String>, MyError> {
+        get_course_slug_db(&self.pool, course_id).await
+    }
+
+    async fn find_course_slug(&self, slug: &str) -> Result<Option<SlugLookup>, MyError> {
+        find_course_slug_db(&self.pool, slug).await
+    }
+}
+
+#[async_trait]
+impl StudentRepository for MySqlRepository {
+    async fn get_all_students(&self) -> Result<Vec<Student>, MyError> {
+        get_all_students_db(&self.pool).await
+    }
+
+    async fn get_student_details(&self, student_id: i32) -> Result<Student, MyError> {
+        get_student_details_db(&self.pool, student_id).await
+    }
+
+    async fn post_new_student(&self, new_student: CreateStudent) -> Result<Student, MyError> {
+        post_new_student_db(&self.pool, new_student).await
+    }
+
+    async fn update_student_details(
+        &self, student_id: i32, update_student: UpdateStudent
+    ) -> Result<Student, MyError> {
+        update_student_details_db(&self.pool, student_id, update_student).await
+    }
+
+    async fn delete_student(&self, student_id: i32) -> Result<String, MyError> {
+        delete_student_db(&self.pool, student_id).await
+    }
+}
+
+#[async_trait]
+impl EnrollmentRepository for MySqlRepository {
+    async fn post_new_enrollment(
+        &self, teacher_id: i32, course_id: i32, new_enrollment: CreateEnrollment
+    ) -> Result<Enrollment, MyError> {
+        post_new_enrollment_db(&self.pool, teacher_id, course_id, new_enrollment).await
+    }
+
+    async fn get_enrollments_for_course(
+        &self, teacher_id: i32, course_id: i32
+    ) -> Result<Vec<Enrollment>, MyError> {
+        get_enrollments_for_course_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_enrollments_for_student(&self, student_id: i32) -> Result<Vec<Enrollment>, MyError> {
+        get_enrollments_for_student_db(&self.pool, student_id).await
+    }
+
+    async fn delete_enrollment(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<String, MyError> {
+        delete_enrollment_db(&self.pool, teacher_id, course_id, student_id).await
+    }
+}
+
+#[async_trait]
+impl SyllabusRepository for MySqlRepository {
+    async fn get_modules(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseModule>, MyError> {
+        get_modules_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn post_new_module(
+        &self, teacher_id: i32, course_id: i32, new_module: CreateModule
+    ) -> Result<CourseModule, MyError> {
+        post_new_module_db(&self.pool, teacher_id, course_id, new_module).await
+    }
+
+    async fn update_module(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, update_module: UpdateModule
+    ) -> Result<CourseModule, MyError> {
+        update_module_db(&self.pool, teacher_id, course_id, module_id, update_module).await
+    }
+
+    async fn delete_module(&self, teacher_id: i32, course_id: i32, module_id: i32) -> Result<String, MyError> {
+        delete_module_db(&self.pool, teacher_id, course_id, module_id).await
+    }
+
+    async fn reorder_modules(
+        &self, teacher_id: i32, course_id: i32, reorder: Reorder
+    ) -> Result<Vec<CourseModule>, MyError> {
+        reorder_modules_db(&self.pool, teacher_id, course_id, reorder).await
+    }
+
+    async fn get_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32
+    ) -> Result<Vec<Lesson>, MyError> {
+        get_lessons_db(&self.pool, teacher_id, course_id, module_id).await
+    }
+
+    async fn get_lessons_for_course(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Lesson>, MyError> {
+        get_lessons_for_course_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn post_new_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, new_lesson: CreateLesson
+    ) -> Result<Lesson, MyError> {
+        post_new_lesson_db(&self.pool, teacher_id, course_id, module_id, new_lesson).await
+    }
+
+    async fn update_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32, update_lesson: UpdateLesson
+    ) -> Result<Lesson, MyError> {
+        update_lesson_db(&self.pool, teacher_id, course_id, module_id, lesson_id, update_lesson).await
+    }
+
+    async fn delete_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+    ) -> Result<String, MyError> {
+        delete_lesson_db(&self.pool, teacher_id, course_id, module_id, lesson_id).await
+    }
+
+    async fn reorder_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, reorder: Reorder
+    ) -> Result<Vec<Lesson>, MyError> {
+        reorder_lessons_db(&self.pool, teacher_id, course_id, module_id, reorder).await
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MySqlRepository {
+    async fn get_sessions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        get_sessions_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_sessions_for_teacher(&self, teacher_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        get_sessions_for_teacher_db(&self.pool, teacher_id).await
+    }
+
+    async fn post_new_session(
+        &self, teacher_id: i32, course_id: i32, new_session: CreateSession
+    ) -> Result<CourseSession, MyError> {
+        post_new_session_db(&self.pool, teacher_id, course_id, new_session).await
+    }
+
+    async fn update_session(
+        &self, teacher_id: i32, course_id: i32, session_id: i32, update_session: UpdateSession
+    ) -> Result<CourseSession, MyError> {
+        update_session_db(&self.pool, teacher_id, course_id, session_id, update_session).await
+    }
+
+    async fn delete_session(&self, teacher_id: i32, course_id: i32, session_id: i32) -> Result<String, MyError> {
+        delete_session_db(&self.pool, teacher_id, course_id, session_id).await
+    }
+}
+
+#[async_trait]
+impl SeatRepository for MySqlRepository {
+    async fn get_reservations(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Reservation>, MyError> {
+        get_reservations_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn get_reservation(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<Reservation, MyError> {
+        get_reservation_db(&self.pool, teacher_id, course_id, student_id).await
+    }
+
+    async fn reserve_seat(
+        &self, teacher_id: i32, course_id: i32, new_reservation: CreateReservation
+    ) -> Result<Reservation, MyError> {
+        reserve_seat_db(&self.pool, teacher_id, course_id, new_reservation).await
+    }
+
+    async fn release_seat(&self, teacher_id: i32, course_id: i32, student_id: i32) -> Result<String, MyError> {
+        release_seat_db(&self.pool, teacher_id, course_id, student_id).await
+    }
+}
+
+#[async_trait]
+impl InstructorRepository for MySqlRepository {
+    async fn get_instructors(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseInstructor>, MyError> {
+        get_instructors_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn add_instructor(
+        &self, teacher_id: i32, course_id: i32, new_instructor: AddInstructor
+    ) -> Result<CourseInstructor, MyError> {
+        add_instructor_db(&self.pool, teacher_id, course_id, new_instructor).await
+    }
+
+    async fn remove_instructor(
+        &self, teacher_id: i32, course_id: i32, instructor_id: i32
+    ) -> Result<String, MyError> {
+        remove_instructor_db(&self.pool, teacher_id, course_id, instructor_id).await
+    }
+}
+
+#[async_trait]
+impl TrashRepository for MySqlRepository {
+    async fn get_trash(&self) -> Result<Vec<TrashItem>, MyError> {
+        get_trash_db(&self.pool).await
+    }
+
+    async fn restore_teacher(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        restore_teacher_db(&self.pool, teacher_id).await
+    }
+
+    async fn restore_course(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        restore_course_db(&self.pool, teacher_id, course_id).await
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, MyError> {
+        purge_trash_db(&self.pool, before).await
+    }
+}
+
+#[async_trait]
+impl UserRepository for MySqlRepository {
+    async fn get_user_by_username(&self, username: &str) -> Result<User, MyError> {
+        get_user_by_username_db(&self.pool, username).await
+    }
+
+    async fn post_new_user(&self, new_user: CreateUser, password_hash: String) -> Result<User, MyError> {
+        post_new_user_db(&self.pool, new_user, password_hash).await
+    }
+
+    async fn count_admins(&self) -> Result<i64, MyError> {
+        count_admins_db(&self.pool).await
+    }
+}
+
+#[async_trait]
+impl SchemaMigrations for MySqlRepository {
+    async fn run_migrations(&self) -> Result<(), MyError> {
+        migrate::run_migrations(&MIGRATOR, &self.pool).await
+    }
+
+    async fn revert_last_migration(&self) -> Result<Option<i64>, MyError> {
+        migrate::revert_last_migration(&MIGRATOR, &self.pool).await
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, MyError> {
+        migrate::migration_status(&MIGRATOR, &self.pool).await
+    }
+}
@@ -0,0 +1,1596 @@
+use crate::errors::{MyError, Resource};
+use crate::migrate;
+use crate::models::course::{Course, CourseQuery, CourseRow, CreateCourse, UpdateCourse};
+use crate::models::enrollment::{CreateEnrollment, Enrollment};
+use crate::models::etag::IfMatch;
+use crate::models::instructor::{AddInstructor, CourseInstructor, InstructorRow};
+use crate::models::lifecycle::{StatusChange, Transition};
+use crate::models::meta::CourseStatus;
+use crate::models::pagination::contains_pattern;
+use crate::models::patch::MergePatch;
+use crate::models::revision::{CourseRevision, RevisionRow};
+use crate::models::search::{CourseSearchQuery, FacetCount, SearchFacets};
+use crate::models::seat::{
+    open_seats, seat_status, with_positions, CreateReservation, Reservation, ReservationRow,
+};
+use crate::models::session::{CourseSession, CreateSession, UpdateSession};
+use crate::models::slug::{next_slug, taken_pattern, SlugLookup};
+use crate::models::student::{CreateStudent, Student, UpdateStudent};
+use crate::models::syllabus::{
+    attachments_json, CourseModule, CreateLesson, CreateModule, Lesson, LessonRow, Reorder,
+    UpdateLesson, UpdateModule,
+};
+use crate::models::teacher::{CreateTeacher, Teacher, TeacherQuery, UpdateTeacher};
+use crate::models::timetable::Booking;
+use crate::models::transfer::TeacherDeletion;
+use crate::models::trash::{TrashItem, TrashRow};
+use crate::models::user::{CreateUser, Role, User, UserRow};
+use crate::repository::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgArguments, PgPool, PgPoolOptions, Postgres};
+use sqlx::Transaction;
+use sqlx::query::{QueryAs, QueryScalar};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+//the text GET /courses/search matches, same expression as the GIN index
+const COURSE_TEXT: &str =
+    "to_tsvector('simple', name || ' ' || coalesce(description, '') || ' ' || coalesce(structure, ''))";
+
+#[derive(Debug, Clone)]
+pub struct PgRepository {
+    pub pool: PgPool,
+}
+
+impl PgRepository {
+    pub fn new(pool: PgPool) -> Self {
+        PgRepository { pool }
+    }
+
+    pub async fn connect(db_url: &str) -> Result<Self, MyError> {
+        let pool = PgPoolOptions::new().connect(db_url).await?;
+        Ok(PgRepository::new(pool))
+    }
+}
+
+fn bind_args<'q, O>(
+    mut query: QueryAs<'q, Postgres, O, PgArguments>, args: &'q [SqlArg]
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    for arg in args {
+        query = match arg {
+            SqlArg::Int(value) => query.bind(*value),
+            SqlArg::BigInt(value) => query.bind(*value),
+            SqlArg::Str(value) => query.bind(value.as_str()),
+            SqlArg::Time(value) => query.bind(*value),
+        };
+    }
+    query
+}
+
+fn bind_scalar_args<'q, O>(
+    mut query: QueryScalar<'q, Postgres, O, PgArguments>, args: &'q [SqlArg]
+) -> QueryScalar<'q, Postgres, O, PgArguments> {
+    for arg in args {
+        query = match arg {
+            SqlArg::Int(value) => query.bind(*value),
+            SqlArg::BigInt(value) => query.bind(*value),
+            SqlArg::Str(value) => query.bind(value.as_str()),
+            SqlArg::Time(value) => query.bind(*value),
+        };
+    }
+    query
+}
+
+//push an argument and return its $n placeholder
+fn push_arg(args: &mut Vec<SqlArg>, arg: SqlArg) -> String {
+    args.push(arg);
+    format!("${}", args.len())
+}
+
+#[async_trait]
+impl TeacherRepository for PgRepository {
+    async fn get_all_teachers(&self, query: &TeacherQuery) -> Result<(Vec<Teacher>, i64), MyError> {
+        let (page, per_page) = query.page_bounds();
+        let order_by = query.order_by()?;
+
+        let mut filters = String::from("WHERE deleted_at IS NULL");
+        let mut args = vec![];
+        if let Some(name) = &query.name {
+            let p = push_arg(&mut args, SqlArg::Str(contains_pattern(name)));
+            filters.push_str(&format!(" AND name ILIKE {} ESCAPE '!'", p));
+        }
+
+        let count_sql = format!("SELECT COUNT(*) FROM teacher {}", filters);
+        let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+            .fetch_one(&self.pool).await?;
+
+        let rows_sql = format!(
+            "SELECT * FROM teacher {} {} LIMIT ${} OFFSET ${}",
+            filters, order_by, args.len() + 1, args.len() + 2
+        );
+        let teachers = bind_args(sqlx::query_as::<_, Teacher>(&rows_sql), &args)
+            .bind(per_page as i64)
+            .bind(((page - 1) * per_page) as i64)
+            .fetch_all(&self.pool).await?;
+
+        Ok((teachers, total))
+    }
+
+    async fn get_teacher_details(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        let teacher = sqlx::query_as::<_, Teacher>("SELECT * FROM teacher WHERE id = $1 AND deleted_at IS NULL")
+            .bind(teacher_id)
+            .fetch_optional(&self.pool).await?;
+
+        match teacher {
+            Some(teacher) => Ok(teacher),
+            None => Err(self.missing_teacher(teacher_id).await),
+        }
+    }
+
+    async fn post_new_teacher(&self, new_teacher: CreateTeacher) -> Result<Teacher, MyError> {
+        let teacher = sqlx::query_as::<_, Teacher>(
+            "INSERT INTO teacher (name, picture_url, profile) VALUES ($1, $2, $3) RETURNING *"
+        )
+            .bind(new_teacher.name)
+            .bind(new_teacher.picture_url)
+            .bind(new_teacher.profile)
+            .fetch_one(&self.pool).await?;
+
+        Ok(teacher)
+    }
+
+    async fn update_teacher_details(
+        &self, teacher_id: i32, update_teacher: UpdateTeacher, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        let current = self.get_teacher_details(teacher_id).await?;
+        if_match.check(current.version)?;
+        self.save_teacher(update_teacher.apply_to(current)).await
+    }
+
+    async fn patch_teacher_details(
+        &self, teacher_id: i32, patch: &MergePatch, if_match: &IfMatch
+    ) -> Result<Teacher, MyError> {
+        let current = self.get_teacher_details(teacher_id).await?;
+        if_match.check(current.version)?;
+
+        self.save_teacher(current.patched(patch)?).await
+    }
+
+    async fn delete_teacher(
+        &self, teacher_id: i32, deletion: TeacherDeletion, if_match: &IfMatch
+    ) -> Result<TrashItem, MyError> {
+        let current = self.get_teacher_details(teacher_id).await?;
+        if_match.check(current.version)?;
+
+        //checked before anything is written, the checks and writes below go in one transaction
+        let courses = self.live_courses(teacher_id).await?;
+        let mut tx = self.pool.begin().await?;
+        let mut transfers = vec![];
+        match deletion {
+            TeacherDeletion::Refuse if !courses.is_empty() => {
+                return Err(MyError::Conflict("Unable to delete teacher, it still has courses".into()));
+            }
+            TeacherDeletion::Reassign(to_teacher_id) => {
+                for course in courses {
+                    transfers.push(self.transferred_course(&mut tx, course, to_teacher_id).await?);
+                }
+            }
+            TeacherDeletion::Refuse | TeacherDeletion::Cascade => {}
+        }
+
+        let deleted_at = Utc::now();
+        for course in &transfers {
+            Self::save_transfer(&mut tx, teacher_id, course).await?;
+        }
+        if deletion == TeacherDeletion::Cascade {
+            sqlx::query(
+                "UPDATE course SET deleted_at = $1, version = version + 1 WHERE teacher_id = $2 AND deleted_at IS NULL"
+            )
+                .bind(deleted_at)
+                .bind(teacher_id)
+                .execute(&mut tx).await?;
+        }
+        //a course added in the meantime keeps the teacher
+        let remaining = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM course WHERE teacher_id = $1 AND deleted_at IS NULL"
+        )
+            .bind(teacher_id)
+            .fetch_one(&mut tx).await?;
+        if remaining > 0 {
+            return Err(MyError::Conflict("Unable to delete teacher, it still has courses".into()));
+        }
+
+        let teacher = sqlx::query_as::<_, Teacher>(
+            "UPDATE teacher SET deleted_at = $1, version = version + 1 \
+            WHERE id = $2 AND version = $3 AND deleted_at IS NULL RETURNING *"
+        )
+            .bind(deleted_at)
+            .bind(teacher_id)
+            .bind(current.version)
+            .fetch_optional(&mut tx).await?
+            .ok_or_else(|| MyError::PreconditionFailed("The teacher has changed since it was read".into()))?;
+        tx.commit().await?;
+
+        Ok(TrashItem::teacher(&teacher, deleted_at))
+    }
+}
+
+impl PgRepository {
+    //why a teacher could not be read: 410 while it is in the trash, 404 otherwise
+    async fn missing_teacher(&self, teacher_id: i32) -> MyError {
+        let trashed = sqlx::query("SELECT id FROM teacher WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(teacher_id)
+            .fetch_optional(&self.pool).await;
+
+        match trashed {
+            Ok(Some(_)) => MyError::Gone(Resource::Teacher, "Teacher was deleted".into()),
+            Ok(None) => MyError::NotFound(Resource::Teacher, "Teacher id not found".into()),
+            Err(err) => err.into(),
+        }
+    }
+
+    //same for a course
+    async fn missing_course(&self, teacher_id: i32, course_id: i32) -> MyError {
+        let trashed = sqlx::query("SELECT id FROM course WHERE teacher_id = $1 and id = $2 and deleted_at IS NOT NULL")
+            .bind(teacher_id)
+            .bind(course_id)
+            .fetch_optional(&self.pool).await;
+
+        match trashed {
+            Ok(Some(_)) => MyError::Gone(Resource::Course, "Course was deleted".into()),
+            Ok(None) => MyError::NotFound(Resource::Course, "Course ID not found".into()),
+            Err(err) => err.into(),
+        }
+    }
+
+    //only applies if nobody saved in between the read and this write
+    async fn save_teacher(&self, teacher: Teacher) -> Result<Teacher, MyError> {
+        sqlx::query_as::<_, Teacher>(
+            "UPDATE teacher SET name = $1, picture_url = $2, profile = $3, version = version + 1 \
+            WHERE id = $4 AND version = $5 AND deleted_at IS NULL RETURNING *"
+        )
+            .bind(teacher.name)
+            .bind(teacher.picture_url)
+            .bind(teacher.profile)
+            .bind(teacher.id)
+            .bind(teacher.version)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::PreconditionFailed("The teacher has changed since it was read".into()))
+    }
+
+    //fails with the courses the booking would overlap, locking them until the transaction ends
+    async fn check_timetable(tx: &mut Transaction<'_, Postgres>, booking: &Booking) -> Result<(), MyError> {
+        let rows = sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course \
+            WHERE (teacher_id = $1 OR room = $2) AND starts_at < $3 AND ends_at > $4 AND id <> $5 \
+            AND deleted_at IS NULL FOR UPDATE"
+        )
+            .bind(booking.teacher_id)
+            .bind(&booking.room)
+            .bind(booking.ends_at)
+            .bind(booking.starts_at)
+            .bind(booking.course_id.unwrap_or(0))
+            .fetch_all(&mut *tx).await?;
+
+        booking.check(&rows.into_iter().map(Course::from).collect::<Vec<_>>())
+    }
+
+    //the teacher's courses outside the trash
+    async fn live_courses(&self, teacher_id: i32) -> Result<Vec<Course>, MyError> {
+        let rows = sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course WHERE teacher_id = $1 AND deleted_at IS NULL ORDER BY id"
+        )
+            .bind(teacher_id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(Course::from).collect())
+    }
+
+    //the course as it will be once moved, the new teacher has to be free at the course's time
+    async fn transferred_course(
+        &self, tx: &mut Transaction<'_, Postgres>, course: Course, to_teacher_id: i32
+    ) -> Result<Course, MyError> {
+        match self.get_teacher_details(to_teacher_id).await {
+            Ok(_) => {}
+            Err(MyError::NotFound(..)) | Err(MyError::Gone(..)) => {
+                return Err(MyError::InvalidReference("The teacher to transfer to does not exist".into()))
+            }
+            Err(err) => return Err(err),
+        }
+
+        let course = Course { teacher_id: to_teacher_id, version: course.version + 1, ..course };
+        if let Some(booking) = Booking::of(&course)? {
+            Self::check_timetable(tx, &booking).await?;
+        }
+        Ok(course)
+    }
+
+    //saves a course from transferred_course unless it changed since it was read
+    async fn save_transfer(
+        tx: &mut Transaction<'_, Postgres>, from_teacher_id: i32, course: &Course
+    ) -> Result<(), MyError> {
+        let row = sqlx::query(
+            "UPDATE course SET teacher_id = $1, version = version + 1 \
+            WHERE id = $2 AND teacher_id = $3 AND version = $4 AND deleted_at IS NULL"
+        )
+            .bind(course.teacher_id)
+            .bind(course.id)
+            .bind(from_teacher_id)
+            .bind(course.version - 1)
+            .execute(&mut *tx).await?;
+        if row.rows_affected() == 0 {
+            return Err(MyError::PreconditionFailed("The course has changed since it was read".into()));
+        }
+
+        sqlx::query("INSERT INTO course_transfer (course_id, from_teacher_id, to_teacher_id) VALUES ($1, $2, $3)")
+            .bind(course.id)
+            .bind(from_teacher_id)
+            .bind(course.teacher_id)
+            .execute(&mut *tx).await?;
+        //a co-teacher who gets the course leads it from now on
+        sqlx::query("DELETE FROM course_instructor WHERE course_id = $1 AND teacher_id = $2")
+            .bind(course.id)
+            .bind(course.teacher_id)
+            .execute(&mut *tx).await?;
+
+        Ok(())
+    }
+
+    //stores changed fields of an existing course, the same way for updates, patches and restores,
+    //unless another save got in after the course was read
+    async fn save_course(
+        tx: &mut Transaction<'_, Postgres>, course: Course, changed_by: i32
+    ) -> Result<Course, MyError> {
+        let saved: Course = sqlx::query_as::<_, CourseRow>(
+            "UPDATE course SET name = $1, starts_at = $2, ends_at = $3, room = $4, capacity = $5, description = $6, \
+            format = $7, structure = $8, duration = $9, price = $10, currency = $11, language = $12, level = $13, \
+            version = version + 1 WHERE teacher_id = $14 and id = $15 and version = $16 and deleted_at IS NULL \
+            RETURNING *"
+        )
+            .bind(course.name)
+            .bind(course.starts_at)
+            .bind(course.ends_at)
+            .bind(course.room)
+            .bind(course.capacity)
+            .bind(course.description)
+            .bind(course.format)
+            .bind(course.structure)
+            .bind(course.duration)
+            .bind(course.price.map(|price| price.amount))
+            .bind(course.price.map(|price| price.currency))
+            .bind(course.language)
+            .bind(course.level)
+            .bind(course.teacher_id)
+            .bind(course.id)
+            .bind(course.version)
+            .fetch_optional(&mut *tx).await?
+            .ok_or_else(|| MyError::PreconditionFailed("The course has changed since it was read".into()))?
+            .into();
+        Self::record_revision(tx, &saved, changed_by).await?;
+        Self::assign_slug(tx, &saved).await?;
+        //a raised capacity lets the waitlist in
+        Self::fill_seats(tx, saved.id, saved.capacity).await?;
+
+        Ok(saved)
+    }
+
+    //gives the course the slug next_slug picks for its name, the slugs it had before stay for redirects
+    async fn assign_slug(tx: &mut Transaction<'_, Postgres>, course: &Course) -> Result<(), MyError> {
+        let own = sqlx::query_as::<_, (String, bool)>("SELECT slug, current FROM course_slug WHERE course_id = $1")
+            .bind(course.id)
+            .fetch_all(&mut *tx).await?;
+        let (base, pattern) = taken_pattern(&course.name);
+        let taken = sqlx::query_scalar::<_, String>("SELECT slug FROM course_slug WHERE slug = $1 OR slug LIKE $2")
+            .bind(base)
+            .bind(pattern)
+            .fetch_all(&mut *tx).await?;
+
+        let current = own.iter().find(|(_, current)| *current).map(|(slug, _)| slug.as_str());
+        let own: Vec<String> = own.iter().map(|(slug, _)| slug.clone()).collect();
+        let slug = match next_slug(&course.name, current, &own, &taken) {
+            Some(slug) => slug,
+            None => return Ok(()),
+        };
+
+        sqlx::query("UPDATE course_slug SET current = FALSE WHERE course_id = $1")
+            .bind(course.id)
+            .execute(&mut *tx).await?;
+        if own.contains(&slug) {
+            sqlx::query("UPDATE course_slug SET current = TRUE WHERE slug = $1")
+                .bind(slug)
+                .execute(&mut *tx).await?;
+        } else {
+            sqlx::query("INSERT INTO course_slug (slug, course_id) VALUES ($1, $2)")
+                .bind(slug)
+                .bind(course.id)
+                .execute(&mut *tx).await?;
+        }
+
+        Ok(())
+    }
+
+    //keeps the course as just saved under the next revision number,
+    //the course row is written first in the same transaction so no other save takes the number
+    async fn record_revision(
+        tx: &mut Transaction<'_, Postgres>, course: &Course, changed_by: i32
+    ) -> Result<(), MyError> {
+        let revision = sqlx::query_scalar::<_, i32>(
+            "SELECT COALESCE(MAX(revision), 0) + 1 FROM course_revision WHERE course_id = $1"
+        )
+            .bind(course.id)
+            .fetch_one(&mut *tx).await?;
+
+        sqlx::query(
+            "INSERT INTO course_revision (course_id, revision, name, starts_at, ends_at, room, capacity, \
+            description, format, structure, duration, price, currency, language, level, changed_by, created_at) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)"
+        )
+            .bind(course.id)
+            .bind(revision)
+            .bind(&course.name)
+            .bind(course.starts_at)
+            .bind(course.ends_at)
+            .bind(&course.room)
+            .bind(course.capacity)
+            .bind(&course.description)
+            .bind(course.format)
+            .bind(&course.structure)
+            .bind(&course.duration)
+            .bind(course.price.map(|price| price.amount))
+            .bind(course.price.map(|price| price.currency))
+            .bind(course.language)
+            .bind(course.level)
+            .bind(changed_by)
+            .bind(Utc::now())
+            .execute(&mut *tx).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CourseRepository for PgRepository {
+    async fn get_course_for_teacher(
+        &self, teacher_id: i32, query: &CourseQuery
+    ) -> Result<(Vec<Course>, i64), MyError> {
+        let (page, per_page) = query.page_bounds();
+        let order_by = query.order_by()?;
+
+        let mut args = vec![];
+        //courses the teacher leads or helps with
+        let lead = push_arg(&mut args, SqlArg::Int(teacher_id));
+        let mut filters = format!(
+            "WHERE (teacher_id = {} OR id IN (SELECT course_id FROM course_instructor WHERE teacher_id = {})) \
+            AND deleted_at IS NULL",
+            lead, lead
+        );
+        if let Some(language) = &query.language {
+            let p = push_arg(&mut args, SqlArg::Str(language.as_str().into()));
+            filters.push_str(&format!(" AND language = {}", p));
+        }
+        if let Some(level) = &query.level {
+            let p = push_arg(&mut args, SqlArg::Str(level.as_str().into()));
+            filters.push_str(&format!(" AND level = {}", p));
+        }
+        if let Some(min_price) = query.min_price {
+            let p = push_arg(&mut args, SqlArg::BigInt(min_price));
+            filters.push_str(&format!(" AND price >= {}", p));
+        }
+        if let Some(max_price) = query.max_price {
+            let p = push_arg(&mut args, SqlArg::BigInt(max_price));
+            filters.push_str(&format!(" AND price <= {}", p));
+        }
+        if let Some(currency) = &query.currency {
+            let p = push_arg(&mut args, SqlArg::Str(currency.as_str().into()));
+            filters.push_str(&format!(" AND currency = {}", p));
+        }
+        if let Some(status) = query.status_filter() {
+            let condition = status_condition(status, Utc::now(), |arg| push_arg(&mut args, arg));
+            filters.push_str(&format!(" AND {}", condition));
+        }
+
+        let count_sql = format!("SELECT COUNT(*) FROM course {}", filters);
+        let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+            .fetch_one(&self.pool).await?;
+
+        let rows_sql = format!(
+            "SELECT * FROM course {} {} LIMIT ${} OFFSET ${}",
+            filters, order_by, args.len() + 1, args.len() + 2
+        );
+        let rows: Vec<Course> = bind_args(sqlx::query_as::<_, CourseRow>(&rows_sql), &args)
+            .bind(per_page as i64)
+            .bind(((page - 1) * per_page) as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(Course::from)
+            .collect();
+
+        Ok((rows, total))
+    }
+
+    async fn search_courses(
+        &self, query: &CourseSearchQuery
+    ) -> Result<(Vec<Course>, i64, SearchFacets), MyError> {
+        let terms = query.terms()?;
+        let (page, per_page) = query.page_bounds();
+
+        let mut args = vec![];
+        let mut filters = format!(
+            "WHERE {} @@ plainto_tsquery('simple', {}) AND deleted_at IS NULL",
+            COURSE_TEXT, push_arg(&mut args, SqlArg::Str(terms.to_string()))
+        );
+        if let Some(language) = &query.language {
+            let p = push_arg(&mut args, SqlArg::Str(language.as_str().into()));
+            filters.push_str(&format!(" AND language = {}", p));
+        }
+        if let Some(level) = &query.level {
+            let p = push_arg(&mut args, SqlArg::Str(level.as_str().into()));
+            filters.push_str(&format!(" AND level = {}", p));
+        }
+        if let Some(format) = &query.format {
+            let p = push_arg(&mut args, SqlArg::Str(format.as_str().into()));
+            filters.push_str(&format!(" AND format = {}", p));
+        }
+        //search is public, drafts and archived courses never show up
+        let live = status_condition(CourseStatus::Published, Utc::now(), |arg| push_arg(&mut args, arg));
+        filters.push_str(&format!(" AND {}", live));
+
+        let count_sql = format!("SELECT COUNT(*) FROM course {}", filters);
+        let total = bind_scalar_args(sqlx::query_scalar::<_, i64>(&count_sql), &args)
+            .fetch_one(&self.pool).await?;
+
+        //most relevant first, $1 is always the search terms
+        let rows_sql = format!(
+            "SELECT * FROM course {} ORDER BY ts_rank({}, plainto_tsquery('simple', $1)) DESC, id \
+            LIMIT ${} OFFSET ${}",
+            filters, COURSE_TEXT, args.len() + 1, args.len() + 2
+        );
+        let rows: Vec<Course> = bind_args(sqlx::query_as::<_, CourseRow>(&rows_sql), &args)
+            .bind(per_page as i64)
+            .bind(((page - 1) * per_page) as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(Course::from)
+            .collect();
+
+        let mut facets = SearchFacets::default();
+        for (field, counts) in [
+            ("language", &mut facets.language),
+            ("level", &mut facets.level),
+            ("format", &mut facets.format),
+        ] {
+            let facet_sql = format!(
+                "SELECT {} AS value, COUNT(*) AS count FROM course {} GROUP BY {} ORDER BY count DESC",
+                field, filters, field
+            );
+            *counts = bind_args(sqlx::query_as::<_, FacetCount>(&facet_sql), &args)
+                .fetch_all(&self.pool).await?;
+        }
+
+        Ok((rows, total, facets))
+    }
+
+    async fn get_course_detail(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        let row = sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course WHERE teacher_id = $1 and id = $2 and deleted_at IS NULL"
+        )
+            .bind(teacher_id)
+            .bind(course_id)
+            .fetch_optional(&self.pool).await?;
+
+        match row {
+            Some(row) => Ok(row.into()),
+            None => Err(self.missing_course(teacher_id, course_id).await),
+        }
+    }
+
+    async fn post_new_course(&self, new_course: CreateCourse, changed_by: i32) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(booking) = Booking::of_new(&new_course)? {
+            Self::check_timetable(&mut tx, &booking).await?;
+        }
+        let course: Course = sqlx::query_as::<_, CourseRow>(
+            "INSERT INTO course (teacher_id, name, starts_at, ends_at, room, capacity, description, format, structure, \
+            duration, price, currency, language, level) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) RETURNING *"
+        )
+            .bind(new_course.teacher_id)
+            .bind(new_course.name)
+            .bind(new_course.starts_at)
+            .bind(new_course.ends_at)
+            .bind(new_course.room)
+            .bind(new_course.capacity)
+            .bind(new_course.description)
+            .bind(new_course.format)
+            .bind(new_course.structure)
+            .bind(new_course.duration)
+            .bind(new_course.price.map(|price| price.amount))
+            .bind(new_course.price.map(|price| price.currency))
+            .bind(new_course.language)
+            .bind(new_course.level)
+            .fetch_one(&mut tx).await?
+            .into();
+        Self::record_revision(&mut tx, &course, changed_by).await?;
+        Self::assign_slug(&mut tx, &course).await?;
+        tx.commit().await?;
+
+        Ok(course)
+    }
+
+    async fn update_course(
+        &self, teacher_id: i32, course_id: i32, update_course: UpdateCourse, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let current = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+        if_match.check(current.version)?;
+        let course = update_course.replace(current);
+        if let Some(booking) = Booking::of(&course)? {
+            Self::check_timetable(&mut tx, &booking).await?;
+        }
+        let course = Self::save_course(&mut tx, course, changed_by).await?;
+        tx.commit().await?;
+
+        Ok(course)
+    }
+
+    async fn patch_course(
+        &self, teacher_id: i32, course_id: i32, patch: &MergePatch, changed_by: i32, if_match: &IfMatch
+    ) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let current = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+        if_match.check(current.version)?;
+        let course = current.patched(patch)?;
+        if let Some(booking) = Booking::of(&course)? {
+            Self::check_timetable(&mut tx, &booking).await?;
+        }
+        let course = Self::save_course(&mut tx, course, changed_by).await?;
+        tx.commit().await?;
+
+        Ok(course)
+    }
+
+    async fn delete_course(&self, teacher_id: i32, course_id: i32, if_match: &IfMatch) -> Result<TrashItem, MyError> {
+        let current = self.get_course_detail(teacher_id, course_id).await?;
+        if_match.check(current.version)?;
+
+        let deleted_at = Utc::now();
+        let course: Course = sqlx::query_as::<_, CourseRow>(
+            "UPDATE course SET deleted_at = $1, version = version + 1 \
+            WHERE teacher_id = $2 and id = $3 and version = $4 and deleted_at IS NULL RETURNING *"
+        )
+            .bind(deleted_at)
+            .bind(teacher_id)
+            .bind(course_id)
+            .bind(current.version)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::PreconditionFailed("The course has changed since it was read".into()))?
+            .into();
+
+        Ok(TrashItem::course(&course, deleted_at))
+    }
+
+    async fn get_courses_between(
+        &self, teacher_id: i32, from: DateTime<Utc>, to: DateTime<Utc>
+    ) -> Result<Vec<Course>, MyError> {
+        let rows = sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course WHERE teacher_id = $1 AND starts_at < $2 AND ends_at > $3 \
+            AND deleted_at IS NULL ORDER BY starts_at, id"
+        )
+            .bind(teacher_id)
+            .bind(to)
+            .bind(from)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(Course::from).collect())
+    }
+
+    async fn change_course_status(
+        &self, teacher_id: i32, course_id: i32, transition: Transition, changed_by: i32
+    ) -> Result<Course, MyError> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        //locked so two requests cannot both move the course on from the same status
+        let course = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+        let (status, published_at) = transition.apply(&course, now)?;
+
+        let updated = sqlx::query_as::<_, CourseRow>(
+            "UPDATE course SET status = $1, published_at = $2, version = version + 1 WHERE id = $3 RETURNING *"
+        )
+            .bind(status)
+            .bind(published_at)
+            .bind(course.id)
+            .fetch_one(&mut tx).await?;
+        sqlx::query(
+            "INSERT INTO course_status_change (course_id, from_status, to_status, changed_by, changed_at) \
+            VALUES ($1, $2, $3, $4, $5)"
+        )
+            .bind(course.id)
+            .bind(course.status)
+            .bind(status)
+            .bind(changed_by)
+            .bind(now)
+            .execute(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(updated.into())
+    }
+
+    async fn get_status_changes(&self, teacher_id: i32, course_id: i32) -> Result<Vec<StatusChange>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let changes = sqlx::query_as::<_, StatusChange>(
+            "SELECT * FROM course_status_change WHERE course_id = $1 ORDER BY id"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(changes)
+    }
+
+    async fn get_revisions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseRevision>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, RevisionRow>(
+            "SELECT * FROM course_revision WHERE course_id = $1 ORDER BY revision"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(CourseRevision::from).collect())
+    }
+
+    async fn get_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32
+    ) -> Result<CourseRevision, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        sqlx::query_as::<_, RevisionRow>("SELECT * FROM course_revision WHERE course_id = $1 AND revision = $2")
+            .bind(course.id)
+            .bind(revision)
+            .fetch_optional(&self.pool).await?
+            .map(CourseRevision::from)
+            .ok_or_else(|| MyError::NotFound(Resource::Revision, "Revision not found".into()))
+    }
+
+    async fn restore_revision(
+        &self, teacher_id: i32, course_id: i32, revision: i32, changed_by: i32
+    ) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let current = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+        let course = self.get_revision(teacher_id, course_id, revision).await?.restore_onto(current);
+        if let Some(booking) = Booking::of(&course)? {
+            Self::check_timetable(&mut tx, &booking).await?;
+        }
+        let course = Self::save_course(&mut tx, course, changed_by).await?;
+        tx.commit().await?;
+
+        Ok(course)
+    }
+
+    async fn transfer_course(&self, teacher_id: i32, course_id: i32, to_teacher_id: i32) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let current = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+        let course = self.transferred_course(&mut tx, current, to_teacher_id).await?;
+        Self::save_transfer(&mut tx, teacher_id, &course).await?;
+        tx.commit().await?;
+
+        Ok(course)
+    }
+
+    async fn find_transferred_course(&self, teacher_id: i32, course_id: i32) -> Result<Option<i32>, MyError> {
+        let current = sqlx::query_scalar::<_, i32>(
+            "SELECT c.teacher_id FROM course_transfer t JOIN course c ON c.id = t.course_id \
+            WHERE t.course_id = $1 AND t.from_teacher_id = $2 AND c.teacher_id <> $2 LIMIT 1"
+        )
+            .bind(course_id)
+            .bind(teacher_id)
+            .fetch_optional(&self.pool).await?;
+
+        Ok(current)
+    }
+
+    async fn get_course_by_id(&self, course_id: i32) -> Result<Course, MyError> {
+        //the teacher of a course in the trash too, so the detail answers 410 for it
+        let teacher_id = sqlx::query_scalar::<_, i32>("SELECT teacher_id FROM course WHERE id = $1")
+            .bind(course_id)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::NotFound(Resource::Course, "Course ID not found".into()))?;
+
+        self.get_course_detail(teacher_id, course_id).await
+    }
+
+    async fn get_course_slug(&self, course_id: i32) -> Result<Option<String>, MyError> {
+        let slug = sqlx::query_scalar::<_, String>("SELECT slug FROM course_slug WHERE course_id = $1 AND current")
+            .bind(course_id)
+            .fetch_optional(&self.pool).await?;
+
+        Ok(slug)
+    }
+
+    async fn find_course_slug(&self, slug: &str) -> Result<Option<SlugLookup>, MyError> {
+        let row = sqlx::query_as::<_, (i32, String)>(
+            "SELECT s.course_id, c.slug FROM course_slug s \
+            JOIN course_slug c ON c.course_id = s.course_id AND c.current WHERE s.slug = $1"
+        )
+            .bind(slug)
+            .fetch_optional(&self.pool).await?;
+
+        Ok(row.map(|(course_id, current)| SlugLookup::of(slug, course_id, current)))
+    }
+}
+
+#[async_trait]
+impl StudentRepository for PgRepository {
+    async fn get_all_students(&self) -> Result<Vec<Student>, MyError> {
+        let rows = sqlx::query_as::<_, Student>("SELECT id, name, email, profile FROM student ORDER BY id")
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn get_student_details(&self, student_id: i32) -> Result<Student, MyError> {
+        sqlx::query_as::<_, Student>("SELECT id, name, email, profile FROM student WHERE id = $1")
+            .bind(student_id)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::NotFound(Resource::Student, "Student id not found".into()))
+    }
+
+    async fn post_new_student(&self, new_student: CreateStudent) -> Result<Student, MyError> {
+        let student = sqlx::query_as::<_, Student>(
+            "INSERT INTO student (name, email, profile) VALUES ($1, $2, $3) RETURNING id, name, email, profile"
+        )
+            .bind(new_student.name)
+            .bind(new_student.email)
+            .bind(new_student.profile)
+            .fetch_one(&self.pool).await?;
+
+        Ok(student)
+    }
+
+    async fn update_student_details(
+        &self, student_id: i32, update_student: UpdateStudent
+    ) -> Result<Student, MyError> {
+        let current = self.get_student_details(student_id).await?;
+        let student = update_student.apply_to(current);
+
+        let _ = sqlx::query("UPDATE student SET name = $1, email = $2, profile = $3 WHERE id = $4")
+            .bind(&student.name)
+            .bind(&student.email)
+            .bind(&student.profile)
+            .bind(student.id)
+            .execute(&self.pool).await?;
+
+        Ok(student)
+    }
+
+    async fn delete_student(&self, student_id: i32) -> Result<String, MyError> {
+        //enrollments go with the student through ON DELETE CASCADE
+        let row = sqlx::query("DELETE FROM student WHERE id = $1")
+            .bind(student_id)
+            .execute(&self.pool).await?;
+
+        match row.rows_affected() {
+            0 => Err(MyError::NotFound(Resource::Student, "Student id not found".into())),
+            deleted => Ok(format!("Deleted {} record", deleted)),
+        }
+    }
+}
+
+#[async_trait]
+impl EnrollmentRepository for PgRepository {
+    async fn post_new_enrollment(
+        &self, teacher_id: i32, course_id: i32, new_enrollment: CreateEnrollment
+    ) -> Result<Enrollment, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+        let student = self.get_student_details(new_enrollment.student_id).await?;
+
+        let existing = sqlx::query("SELECT id FROM enrollment WHERE student_id = $1 and course_id = $2")
+            .bind(student.id)
+            .bind(course.id)
+            .fetch_optional(&self.pool).await?;
+        if existing.is_some() {
+            return Err(MyError::Conflict("Student already enrolled in this course".into()));
+        }
+
+        let enrollment = sqlx::query_as::<_, Enrollment>(
+            "INSERT INTO enrollment (student_id, course_id) VALUES ($1, $2) RETURNING *"
+        )
+            .bind(student.id)
+            .bind(course.id)
+            .fetch_one(&self.pool).await?;
+
+        Ok(enrollment)
+    }
+
+    async fn get_enrollments_for_course(
+        &self, teacher_id: i32, course_id: i32
+    ) -> Result<Vec<Enrollment>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, Enrollment>(
+            "SELECT * FROM enrollment WHERE course_id = $1 ORDER BY enrolled_at"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn get_enrollments_for_student(&self, student_id: i32) -> Result<Vec<Enrollment>, MyError> {
+        let student = self.get_student_details(student_id).await?;
+
+        let rows = sqlx::query_as::<_, Enrollment>(
+            "SELECT e.* FROM enrollment e JOIN course c ON c.id = e.course_id \
+            WHERE e.student_id = $1 AND c.deleted_at IS NULL ORDER BY e.enrolled_at"
+        )
+            .bind(student.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn delete_enrollment(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<String, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let row = sqlx::query("DELETE FROM enrollment WHERE course_id = $1 and student_id = $2")
+            .bind(course.id)
+            .bind(student_id)
+            .execute(&self.pool).await?;
+
+        if row.rows_affected() == 0 {
+            return Err(MyError::NotFound(Resource::Enrollment, "Enrollment not found".into()));
+        }
+
+        Ok(format!("Deleted {} record", row.rows_affected()))
+    }
+}
+
+//modules and lessons are only reachable through the teacher's course
+impl PgRepository {
+    async fn module(&self, teacher_id: i32, course_id: i32, module_id: i32) -> Result<CourseModule, MyError> {
+        sqlx::query_as::<_, CourseModule>(
+            "SELECT m.* FROM course_module m JOIN course c ON c.id = m.course_id \
+            WHERE c.teacher_id = $1 and m.course_id = $2 and m.id = $3 and c.deleted_at IS NULL"
+        )
+            .bind(teacher_id)
+            .bind(course_id)
+            .bind(module_id)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::NotFound(Resource::Module, "Module not found".into()))
+    }
+
+    async fn lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+    ) -> Result<Lesson, MyError> {
+        let module = self.module(teacher_id, course_id, module_id).await?;
+
+        sqlx::query_as::<_, LessonRow>("SELECT * FROM lesson WHERE module_id = $1 and id = $2")
+            .bind(module.id)
+            .bind(lesson_id)
+            .fetch_optional(&self.pool).await?
+            .map(Lesson::from)
+            .ok_or_else(|| MyError::NotFound(Resource::Lesson, "Lesson not found".into()))
+    }
+}
+
+#[async_trait]
+impl SyllabusRepository for PgRepository {
+    async fn get_modules(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseModule>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, CourseModule>(
+            "SELECT * FROM course_module WHERE course_id = $1 ORDER BY position, id"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn post_new_module(
+        &self, teacher_id: i32, course_id: i32, new_module: CreateModule
+    ) -> Result<CourseModule, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        //new modules go last
+        let module = sqlx::query_as::<_, CourseModule>(
+            "INSERT INTO course_module (course_id, title, summary, position) \
+            SELECT $1, $2, $3, COALESCE(MAX(position), 0) + 1 FROM course_module WHERE course_id = $1 \
+            RETURNING *"
+        )
+            .bind(course.id)
+            .bind(new_module.title)
+            .bind(new_module.summary)
+            .fetch_one(&self.pool).await?;
+
+        Ok(module)
+    }
+
+    async fn update_module(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, update_module: UpdateModule
+    ) -> Result<CourseModule, MyError> {
+        let current = self.module(teacher_id, course_id, module_id).await?;
+        let module = update_module.apply_to(current);
+
+        let module = sqlx::query_as::<_, CourseModule>(
+            "UPDATE course_module SET title = $1, summary = $2 WHERE id = $3 RETURNING *"
+        )
+            .bind(module.title)
+            .bind(module.summary)
+            .bind(module.id)
+            .fetch_one(&self.pool).await?;
+
+        Ok(module)
+    }
+
+    async fn delete_module(&self, teacher_id: i32, course_id: i32, module_id: i32) -> Result<String, MyError> {
+        let module = self.module(teacher_id, course_id, module_id).await?;
+
+        //its lessons go with it, ON DELETE CASCADE
+        let row = sqlx::query("DELETE FROM course_module WHERE id = $1")
+            .bind(module.id)
+            .execute(&self.pool).await?;
+
+        Ok(format!("Deleted {} record", row.rows_affected()))
+    }
+
+    async fn reorder_modules(
+        &self, teacher_id: i32, course_id: i32, reorder: Reorder
+    ) -> Result<Vec<CourseModule>, MyError> {
+        let current = self.get_modules(teacher_id, course_id).await?;
+        reorder.check(&current.iter().map(|m| m.id).collect::<Vec<_>>())?;
+
+        let mut tx = self.pool.begin().await?;
+        for (position, module_id) in reorder.ids.iter().enumerate() {
+            sqlx::query("UPDATE course_module SET position = $1 WHERE id = $2")
+                .bind(position as i32 + 1)
+                .bind(module_id)
+                .execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+
+        self.get_modules(teacher_id, course_id).await
+    }
+
+    async fn get_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32
+    ) -> Result<Vec<Lesson>, MyError> {
+        let module = self.module(teacher_id, course_id, module_id).await?;
+
+        let rows = sqlx::query_as::<_, LessonRow>(
+            "SELECT * FROM lesson WHERE module_id = $1 ORDER BY position, id"
+        )
+            .bind(module.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(Lesson::from).collect())
+    }
+
+    async fn get_lessons_for_course(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Lesson>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, LessonRow>(
+            "SELECT l.* FROM lesson l JOIN course_module m ON m.id = l.module_id \
+            WHERE m.course_id = $1 ORDER BY m.position, m.id, l.position, l.id"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(Lesson::from).collect())
+    }
+
+    async fn post_new_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, new_lesson: CreateLesson
+    ) -> Result<Lesson, MyError> {
+        let module = self.module(teacher_id, course_id, module_id).await?;
+
+        //new lessons go last in their module
+        let lesson = sqlx::query_as::<_, LessonRow>(
+            "INSERT INTO lesson (module_id, title, body, duration_minutes, attachments, position) \
+            SELECT $1, $2, $3, $4, $5, COALESCE(MAX(position), 0) + 1 FROM lesson WHERE module_id = $1 \
+            RETURNING *"
+        )
+            .bind(module.id)
+            .bind(new_lesson.title)
+            .bind(new_lesson.body)
+            .bind(new_lesson.duration_minutes)
+            .bind(attachments_json(&new_lesson.attachments))
+            .fetch_one(&self.pool).await?;
+
+        Ok(lesson.into())
+    }
+
+    async fn update_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32, update_lesson: UpdateLesson
+    ) -> Result<Lesson, MyError> {
+        let current = self.lesson(teacher_id, course_id, module_id, lesson_id).await?;
+        let lesson = update_lesson.apply_to(current);
+
+        let lesson = sqlx::query_as::<_, LessonRow>(
+            "UPDATE lesson SET title = $1, body = $2, duration_minutes = $3, attachments = $4 \
+            WHERE id = $5 RETURNING *"
+        )
+            .bind(lesson.title)
+            .bind(lesson.body)
+            .bind(lesson.duration_minutes)
+            .bind(attachments_json(&lesson.attachments))
+            .bind(lesson.id)
+            .fetch_one(&self.pool).await?;
+
+        Ok(lesson.into())
+    }
+
+    async fn delete_lesson(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, lesson_id: i32
+    ) -> Result<String, MyError> {
+        let lesson = self.lesson(teacher_id, course_id, module_id, lesson_id).await?;
+
+        let row = sqlx::query("DELETE FROM lesson WHERE id = $1")
+            .bind(lesson.id)
+            .execute(&self.pool).await?;
+
+        Ok(format!("Deleted {} record", row.rows_affected()))
+    }
+
+    async fn reorder_lessons(
+        &self, teacher_id: i32, course_id: i32, module_id: i32, reorder: Reorder
+    ) -> Result<Vec<Lesson>, MyError> {
+        let current = self.get_lessons(teacher_id, course_id, module_id).await?;
+        reorder.check(&current.iter().map(|l| l.id).collect::<Vec<_>>())?;
+
+        let mut tx = self.pool.begin().await?;
+        for (position, lesson_id) in reorder.ids.iter().enumerate() {
+            sqlx::query("UPDATE lesson SET position = $1 WHERE id = $2")
+                .bind(position as i32 + 1)
+                .bind(lesson_id)
+                .execute(&mut tx).await?;
+        }
+        tx.commit().await?;
+
+        self.get_lessons(teacher_id, course_id, module_id).await
+    }
+}
+
+impl PgRepository {
+    async fn session(&self, teacher_id: i32, course_id: i32, session_id: i32) -> Result<CourseSession, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        sqlx::query_as::<_, CourseSession>("SELECT * FROM course_session WHERE course_id = $1 and id = $2")
+            .bind(course.id)
+            .bind(session_id)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::NotFound(Resource::Session, "Session not found".into()))
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PgRepository {
+    async fn get_sessions(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, CourseSession>(
+            "SELECT * FROM course_session WHERE course_id = $1 ORDER BY starts_at, id"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn get_sessions_for_teacher(&self, teacher_id: i32) -> Result<Vec<CourseSession>, MyError> {
+        let teacher = self.get_teacher_details(teacher_id).await?;
+
+        let rows = sqlx::query_as::<_, CourseSession>(
+            "SELECT s.* FROM course_session s JOIN course c ON c.id = s.course_id \
+            WHERE c.teacher_id = $1 AND c.deleted_at IS NULL ORDER BY s.starts_at, s.id"
+        )
+            .bind(teacher.id)
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows)
+    }
+
+    async fn post_new_session(
+        &self, teacher_id: i32, course_id: i32, new_session: CreateSession
+    ) -> Result<CourseSession, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let session = sqlx::query_as::<_, CourseSession>(
+            "INSERT INTO course_session (course_id, starts_at, ends_at, timezone, location, meeting_url, \
+            frequency, occurrences, repeat_until) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *"
+        )
+            .bind(course.id)
+            .bind(new_session.starts_at)
+            .bind(new_session.ends_at)
+            .bind(new_session.timezone)
+            .bind(new_session.location)
+            .bind(new_session.meeting_url)
+            .bind(new_session.frequency)
+            .bind(new_session.occurrences)
+            .bind(new_session.repeat_until)
+            .fetch_one(&self.pool).await?;
+
+        Ok(session)
+    }
+
+    async fn update_session(
+        &self, teacher_id: i32, course_id: i32, session_id: i32, update_session: UpdateSession
+    ) -> Result<CourseSession, MyError> {
+        let current = self.session(teacher_id, course_id, session_id).await?;
+        let session = update_session.apply_to(current)?;
+
+        let session = sqlx::query_as::<_, CourseSession>(
+            "UPDATE course_session SET starts_at = $1, ends_at = $2, timezone = $3, location = $4, \
+            meeting_url = $5, frequency = $6, occurrences = $7, repeat_until = $8 WHERE id = $9 RETURNING *"
+        )
+            .bind(session.starts_at)
+            .bind(session.ends_at)
+            .bind(session.timezone)
+            .bind(session.location)
+            .bind(session.meeting_url)
+            .bind(session.frequency)
+            .bind(session.occurrences)
+            .bind(session.repeat_until)
+            .bind(session.id)
+            .fetch_one(&self.pool).await?;
+
+        Ok(session)
+    }
+
+    async fn delete_session(&self, teacher_id: i32, course_id: i32, session_id: i32) -> Result<String, MyError> {
+        let session = self.session(teacher_id, course_id, session_id).await?;
+
+        let row = sqlx::query("DELETE FROM course_session WHERE id = $1")
+            .bind(session.id)
+            .execute(&self.pool).await?;
+
+        Ok(format!("Deleted {} record", row.rows_affected()))
+    }
+}
+
+impl PgRepository {
+    //locks the course row until the transaction ends, so seat counts read after it stay true
+    async fn lock_course(
+        tx: &mut Transaction<'_, Postgres>, teacher_id: i32, course_id: i32
+    ) -> Result<Course, MyError> {
+        let row = sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course WHERE teacher_id = $1 and id = $2 and deleted_at IS NULL FOR UPDATE"
+        )
+            .bind(teacher_id)
+            .bind(course_id)
+            .fetch_optional(&mut *tx).await?;
+        if let Some(row) = row {
+            return Ok(row.into());
+        }
+
+        //not there or in the trash
+        let trashed = sqlx::query("SELECT id FROM course WHERE teacher_id = $1 and id = $2")
+            .bind(teacher_id)
+            .bind(course_id)
+            .fetch_optional(&mut *tx).await?;
+        match trashed {
+            Some(_) => Err(MyError::Gone(Resource::Course, "Course was deleted".into())),
+            None => Err(MyError::NotFound(Resource::Course, "Course ID not found".into())),
+        }
+    }
+
+    //moves the head of the waitlist into any free seats
+    async fn fill_seats(
+        tx: &mut Transaction<'_, Postgres>, course_id: i32, capacity: Option<i32>
+    ) -> Result<(), MyError> {
+        let reserved = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM seat_reservation WHERE course_id = $1 AND status = 'reserved'"
+        )
+            .bind(course_id)
+            .fetch_one(&mut *tx).await?;
+
+        sqlx::query(
+            "UPDATE seat_reservation SET status = 'reserved' WHERE id IN ( \
+            SELECT id FROM seat_reservation WHERE course_id = $1 AND status = 'waitlisted' ORDER BY id LIMIT $2)"
+        )
+            .bind(course_id)
+            .bind(open_seats(capacity, reserved))
+            .execute(&mut *tx).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SeatRepository for PgRepository {
+    async fn get_reservations(&self, teacher_id: i32, course_id: i32) -> Result<Vec<Reservation>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let rows = sqlx::query_as::<_, ReservationRow>("SELECT * FROM seat_reservation WHERE course_id = $1 ORDER BY id")
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        let mut reservations = with_positions(rows)?;
+        reservations.sort_by_key(|r| (r.waitlist_position, r.id));
+        Ok(reservations)
+    }
+
+    async fn get_reservation(
+        &self, teacher_id: i32, course_id: i32, student_id: i32
+    ) -> Result<Reservation, MyError> {
+        //the waitlist position depends on the reservations made before this one
+        self.get_reservations(teacher_id, course_id).await?
+            .into_iter()
+            .find(|r| r.student_id == student_id)
+            .ok_or_else(|| MyError::NotFound(Resource::Reservation, "Reservation not found".into()))
+    }
+
+    async fn reserve_seat(
+        &self, teacher_id: i32, course_id: i32, new_reservation: CreateReservation
+    ) -> Result<Reservation, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let course = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+
+        let student_id = sqlx::query_scalar::<_, i32>("SELECT id FROM student WHERE id = $1")
+            .bind(new_reservation.student_id)
+            .fetch_optional(&mut tx).await?
+            .ok_or_else(|| MyError::NotFound(Resource::Student, "Student id not found".into()))?;
+
+        let existing = sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM seat_reservation WHERE course_id = $1 and student_id = $2"
+        )
+            .bind(course.id)
+            .bind(student_id)
+            .fetch_optional(&mut tx).await?;
+        if existing.is_some() {
+            return Err(MyError::Conflict("Student already has a seat or a waitlist place in this course".into()));
+        }
+
+        let reserved = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM seat_reservation WHERE course_id = $1 AND status = 'reserved'"
+        )
+            .bind(course.id)
+            .fetch_one(&mut tx).await?;
+
+        sqlx::query("INSERT INTO seat_reservation (course_id, student_id, status) VALUES ($1, $2, $3)")
+            .bind(course.id)
+            .bind(student_id)
+            .bind(seat_status(course.capacity, reserved).as_str())
+            .execute(&mut tx).await?;
+        tx.commit().await?;
+
+        self.get_reservation(teacher_id, course_id, student_id).await
+    }
+
+    async fn release_seat(&self, teacher_id: i32, course_id: i32, student_id: i32) -> Result<String, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let course = Self::lock_course(&mut tx, teacher_id, course_id).await?;
+
+        let row = sqlx::query("DELETE FROM seat_reservation WHERE course_id = $1 and student_id = $2")
+            .bind(course.id)
+            .bind(student_id)
+            .execute(&mut tx).await?;
+        if row.rows_affected() == 0 {
+            return Err(MyError::NotFound(Resource::Reservation, "Reservation not found".into()));
+        }
+
+        Self::fill_seats(&mut tx, course.id, course.capacity).await?;
+        tx.commit().await?;
+
+        Ok(format!("Deleted {} record", row.rows_affected()))
+    }
+}
+
+#[async_trait]
+impl InstructorRepository for PgRepository {
+    async fn get_instructors(&self, teacher_id: i32, course_id: i32) -> Result<Vec<CourseInstructor>, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+        let lead = self.get_teacher_details(teacher_id).await?;
+
+        //teachers in the trash are left out
+        let rows = sqlx::query_as::<_, InstructorRow>(
+            "SELECT i.course_id, i.teacher_id, t.name, i.role, i.added_at \
+            FROM course_instructor i JOIN teacher t ON t.id = i.teacher_id \
+            WHERE i.course_id = $1 AND t.deleted_at IS NULL \
+            ORDER BY i.added_at, i.teacher_id"
+        )
+            .bind(course.id)
+            .fetch_all(&self.pool).await?;
+
+        let mut instructors = vec![CourseInstructor::lead(&course, &lead)];
+        for row in rows {
+            instructors.push(row.try_into()?);
+        }
+        Ok(instructors)
+    }
+
+    async fn add_instructor(
+        &self, teacher_id: i32, course_id: i32, new_instructor: AddInstructor
+    ) -> Result<CourseInstructor, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+        //the foreign key does not see teachers in the trash
+        let teacher = match self.get_teacher_details(new_instructor.teacher_id).await {
+            Ok(teacher) => teacher,
+            Err(MyError::NotFound(..)) | Err(MyError::Gone(..)) => {
+                return Err(MyError::InvalidReference("A referenced record does not exist".into()))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let row = sqlx::query_as::<_, InstructorRow>(
+            "INSERT INTO course_instructor (course_id, teacher_id, role, added_at) VALUES ($1, $2, $3, $4) \
+            RETURNING course_id, teacher_id, $5::VARCHAR AS name, role, added_at"
+        )
+            .bind(course.id)
+            .bind(teacher.id)
+            .bind(new_instructor.role.as_str())
+            .bind(Utc::now())
+            .bind(&teacher.name)
+            .fetch_one(&self.pool).await?;
+
+        row.try_into()
+    }
+
+    async fn remove_instructor(
+        &self, teacher_id: i32, course_id: i32, instructor_id: i32
+    ) -> Result<String, MyError> {
+        let course = self.get_course_detail(teacher_id, course_id).await?;
+
+        let row = sqlx::query("DELETE FROM course_instructor WHERE course_id = $1 AND teacher_id = $2")
+            .bind(course.id)
+            .bind(instructor_id)
+            .execute(&self.pool).await?;
+
+        match row.rows_affected() {
+            0 => Err(MyError::NotFound(Resource::Instructor, "Instructor not found".into())),
+            deleted => Ok(format!("Deleted {} record", deleted)),
+        }
+    }
+}
+
+#[async_trait]
+impl TrashRepository for PgRepository {
+    async fn get_trash(&self) -> Result<Vec<TrashItem>, MyError> {
+        let rows = sqlx::query_as::<_, TrashRow>(
+            "SELECT id, NULL::INT AS teacher_id, name, deleted_at FROM teacher WHERE deleted_at IS NOT NULL \
+            UNION ALL \
+            SELECT id, teacher_id, name, deleted_at FROM course WHERE deleted_at IS NOT NULL \
+            ORDER BY deleted_at DESC, id DESC"
+        )
+            .fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(TrashItem::from).collect())
+    }
+
+    async fn restore_teacher(&self, teacher_id: i32) -> Result<Teacher, MyError> {
+        let teacher = sqlx::query_as::<_, Teacher>(
+            "UPDATE teacher SET deleted_at = NULL, version = version + 1 \
+            WHERE id = $1 AND deleted_at IS NOT NULL RETURNING *"
+        )
+            .bind(teacher_id)
+            .fetch_optional(&self.pool).await?;
+
+        match teacher {
+            Some(teacher) => Ok(teacher),
+            None => {
+                //404 when it does not exist at all
+                self.get_teacher_details(teacher_id).await?;
+                Err(MyError::Conflict("Teacher is not deleted".into()))
+            }
+        }
+    }
+
+    async fn restore_course(&self, teacher_id: i32, course_id: i32) -> Result<Course, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let course: Course = match sqlx::query_as::<_, CourseRow>(
+            "SELECT * FROM course WHERE teacher_id = $1 and id = $2 and deleted_at IS NOT NULL FOR UPDATE"
+        )
+            .bind(teacher_id)
+            .bind(course_id)
+            .fetch_optional(&mut tx).await?
+        {
+            Some(row) => row.into(),
+            None => {
+                self.get_course_detail(teacher_id, course_id).await?;
+                return Err(MyError::Conflict("Course is not deleted".into()));
+            }
+        };
+
+        match self.get_teacher_details(teacher_id).await {
+            Ok(_) => {}
+            Err(MyError::Gone(..)) => {
+                return Err(MyError::Conflict("The course's teacher is deleted, restore the teacher first".into()))
+            }
+            Err(err) => return Err(err),
+        }
+        //the slot may have been taken while the course was in the trash
+        if let Some(booking) = Booking::of(&course)? {
+            Self::check_timetable(&mut tx, &booking).await?;
+        }
+
+        let restored = sqlx::query_as::<_, CourseRow>(
+            "UPDATE course SET deleted_at = NULL, version = version + 1 \
+            WHERE id = $1 and version = $2 and deleted_at IS NOT NULL RETURNING *"
+        )
+            .bind(course.id)
+            .bind(course.version)
+            .fetch_optional(&mut tx).await?
+            .ok_or_else(|| MyError::PreconditionFailed("The course has changed since it was read".into()))?;
+        tx.commit().await?;
+
+        Ok(restored.into())
+    }
+
+    //courses go first so the teachers they kept in the trash can go in the same run
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, MyError> {
+        let mut tx = self.pool.begin().await?;
+        let courses = sqlx::query("DELETE FROM course WHERE deleted_at < $1")
+            .bind(before)
+            .execute(&mut tx).await?;
+        let teachers = sqlx::query(
+            "DELETE FROM teacher t WHERE t.deleted_at < $1 \
+            AND NOT EXISTS (SELECT 1 FROM course c WHERE c.teacher_id = t.id)"
+        )
+            .bind(before)
+            .execute(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(courses.rows_affected() + teachers.rows_affected())
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgRepository {
+    async fn get_user_by_username(&self, username: &str) -> Result<User, MyError> {
+        let row = sqlx::query_as::<_, UserRow>("SELECT * FROM app_user WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.pool).await?
+            .ok_or_else(|| MyError::NotFound(Resource::User, "User not found".into()))?;
+
+        User::try_from(row).map_err(MyError::DBError)
+    }
+
+    async fn post_new_user(&self, new_user: CreateUser, password_hash: String) -> Result<User, MyError> {
+        let existing = sqlx::query("SELECT id FROM app_user WHERE username = $1")
+            .bind(&new_user.username)
+            .fetch_optional(&self.pool).await?;
+        if existing.is_some() {
+            return Err(MyError::Conflict("Username already taken".into()));
+        }
+
+        let row = sqlx::query_as::<_, UserRow>(
+            "INSERT INTO app_user (username, password_hash, role, teacher_id, student_id) \
+            VALUES ($1, $2, $3, $4, $5) RETURNING *"
+        )
+            .bind(new_user.username)
+            .bind(password_hash)
+            .bind(new_user.role.as_str())
+            .bind(new_user.teacher_id)
+            .bind(new_user.student_id)
+            .fetch_one(&self.pool).await?;
+
+        User::try_from(row).map_err(MyError::DBError)
+    }
+
+    async fn count_admins(&self) -> Result<i64, MyError> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM app_user WHERE role = $1")
+            .bind(Role::Admin.as_str())
+            .fetch_one(&self.pool).await?;
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl SchemaMigrations for PgRepository {
+    async fn run_migrations(&self) -> Result<(), MyError> {
+        migrate::run_migrations(&MIGRATOR, &self.pool).await
+    }
+
+    async fn revert_last_migration(&self) -> Result<Option<i64>, MyError> {
+        migrate::revert_last_migration(&MIGRATOR, &self.pool).await
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, MyError> {
+        migrate::migration_status(&MIGRATOR, &self.pool).await
+    }
+}
@@ -0,0 +1,49 @@
+use crate::errors::MyError;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use std::future::Future;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+//id of the current request, taken from X-Request-Id or generated
+//no handler reads it yet, it is there for the ones that want to log it
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+//middleware for App::wrap_fn: tags every request with an id, echoes it in the
+//X-Request-Id response header and puts it into MyError problem documents
+pub fn assign<S>(req: ServiceRequest, srv: &S) -> impl Future<Output = Result<ServiceResponse, Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+{
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let fut = srv.call(req);
+    async move {
+        let mut res = fut.await?;
+
+        let problem = res
+            .response()
+            .error()
+            .and_then(|err| err.as_error::<MyError>())
+            .map(|err| err.problem_response(Some(request_id.clone())));
+        if let Some(problem) = problem {
+            res = res.into_response(problem);
+        }
+
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+        Ok(res)
+    }
+}
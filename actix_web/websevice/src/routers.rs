@@ -1,6 +1,49 @@
 use crate::handlers::{general::*, course::*};
 use actix_web::web;
 use crate::handlers::teacher::*;
+use crate::handlers::{student::*, enrollment::*};
+use crate::handlers::syllabus::*;
+use crate::handlers::session::*;
+use crate::handlers::seat::*;
+use crate::handlers::lifecycle::*;
+use crate::handlers::revision::*;
+use crate::handlers::auth::*;
+use crate::handlers::meta::*;
+use crate::handlers::transfer::*;
+use crate::handlers::instructor::*;
+use crate::handlers::trash::*;
+use crate::errors::MyError;
+use crate::openapi::ApiDoc;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+//every route of the service plus its json config, shared by the server and the tests
+pub fn app_config(cfg: &mut web::ServiceConfig) {
+    cfg
+        .app_data(web::JsonConfig::default().error_handler(|err, _req| {
+            MyError::InvalidInput(format!("Please provide valid Json input: {}", err)).into()
+        }))
+        .app_data(web::QueryConfig::default().error_handler(|err, _req| {
+            MyError::InvalidInput(format!("Invalid query string: {}", err)).into()
+        }))
+        .app_data(web::PathConfig::default().error_handler(|err, _req| {
+            MyError::InvalidInput(format!("Invalid path: {}", err)).into()
+        }))
+        .configure(general_routes)
+        .configure(course_routes)
+        .configure(teacher_routes)
+        .configure(student_routes)
+        .configure(auth_routes)
+        .configure(meta_routes)
+        .configure(trash_routes)
+        .configure(slug_routes)
+        .configure(docs_routes);
+}
+
+//OpenAPI document at /openapi.json and the swagger ui browsing it
+pub fn docs_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/openapi.json", ApiDoc::openapi()));
+}
 
 pub fn general_routes(cfg: &mut web::ServiceConfig) {
     cfg.route("/health", web::get().to(health_check_handler));
@@ -9,8 +52,15 @@ pub fn general_routes(cfg: &mut web::ServiceConfig) {
 pub fn course_routes(cfg: &mut web::ServiceConfig) {
      cfg
          .service(web::scope("/courses")
+             //old paths of transferred courses redirect to the new teacher
+             .wrap_fn(follow_transfers)
              .route("/",
                     web::post().to(post_new_course))
+             //registered before /{teacher_id} so "search" and "by-id" are not taken for ids
+             .route("/search",
+                    web::get().to(search_courses))
+             .route("/by-id/{course_id}",
+                    web::get().to(get_course_by_id))
              .route("/{teacher_id}",
                     web::get().to(get_courses_for_teacher))
              .route("/{teacher_id}/{course_id}",
@@ -19,6 +69,78 @@ pub fn course_routes(cfg: &mut web::ServiceConfig) {
                     web::delete().to(delete_course))
              .route("/{teacher_id}/{course_id}",
                     web::put().to(update_course_detail))
+             .route("/{teacher_id}/{course_id}",
+                    web::patch().to(patch_course_detail))
+             .route("/{teacher_id}/{course_id}/publish",
+                    web::post().to(publish_course))
+             .route("/{teacher_id}/{course_id}/archive",
+                    web::post().to(archive_course))
+             .route("/{teacher_id}/{course_id}/restore",
+                    web::post().to(restore_course))
+             .route("/{teacher_id}/{course_id}/transfer",
+                    web::post().to(transfer_course))
+             .route("/{teacher_id}/{course_id}/instructors",
+                    web::get().to(get_instructors))
+             .route("/{teacher_id}/{course_id}/instructors",
+                    web::post().to(add_instructor))
+             .route("/{teacher_id}/{course_id}/instructors/{instructor_id}",
+                    web::delete().to(remove_instructor))
+             .route("/{teacher_id}/{course_id}/status-changes",
+                    web::get().to(get_status_changes))
+             .route("/{teacher_id}/{course_id}/revisions",
+                    web::get().to(get_revisions))
+             //registered before /{revision} so "diff" is not taken for a revision number
+             .route("/{teacher_id}/{course_id}/revisions/diff",
+                    web::get().to(get_revision_diff))
+             .route("/{teacher_id}/{course_id}/revisions/{revision}",
+                    web::get().to(get_revision))
+             .route("/{teacher_id}/{course_id}/revisions/{revision}/restore",
+                    web::post().to(restore_revision))
+             .route("/{teacher_id}/{course_id}/enrollments",
+                    web::post().to(post_new_enrollment))
+             .route("/{teacher_id}/{course_id}/enrollments",
+                    web::get().to(get_enrollments_for_course))
+             .route("/{teacher_id}/{course_id}/enrollments/{student_id}",
+                    web::delete().to(delete_enrollment))
+             .route("/{teacher_id}/{course_id}/seats",
+                    web::get().to(get_reservations))
+             .route("/{teacher_id}/{course_id}/seats",
+                    web::post().to(reserve_seat))
+             .route("/{teacher_id}/{course_id}/seats/{student_id}",
+                    web::get().to(get_reservation))
+             .route("/{teacher_id}/{course_id}/seats/{student_id}",
+                    web::delete().to(release_seat))
+             .route("/{teacher_id}/{course_id}/modules",
+                    web::get().to(get_modules))
+             .route("/{teacher_id}/{course_id}/modules",
+                    web::post().to(post_new_module))
+             //registered before /{module_id} so "order" is not taken for an id
+             .route("/{teacher_id}/{course_id}/modules/order",
+                    web::put().to(reorder_modules))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}",
+                    web::put().to(update_module))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}",
+                    web::delete().to(delete_module))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}/lessons",
+                    web::get().to(get_lessons))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}/lessons",
+                    web::post().to(post_new_lesson))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}/lessons/order",
+                    web::put().to(reorder_lessons))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+                    web::put().to(update_lesson))
+             .route("/{teacher_id}/{course_id}/modules/{module_id}/lessons/{lesson_id}",
+                    web::delete().to(delete_lesson))
+             .route("/{teacher_id}/{course_id}/sessions",
+                    web::get().to(get_sessions))
+             .route("/{teacher_id}/{course_id}/sessions",
+                    web::post().to(post_new_session))
+             .route("/{teacher_id}/{course_id}/sessions/{session_id}",
+                    web::put().to(update_session))
+             .route("/{teacher_id}/{course_id}/sessions/{session_id}",
+                    web::delete().to(delete_session))
+             .route("/{teacher_id}/{course_id}/calendar.ics",
+                    web::get().to(get_course_calendar))
          );
 }
 
@@ -30,5 +152,804 @@ pub fn teacher_routes(cfg: &mut web::ServiceConfig) {
             .route("/{teacher_id}", web::get().to(get_teacher_details))
             .route("/{teacher_id}", web::delete().to(delete_teacher))
             .route("/{teacher_id}", web::put().to(update_teacher_details))
+            .route("/{teacher_id}", web::patch().to(patch_teacher_details))
+            .route("/{teacher_id}/restore", web::post().to(restore_teacher))
+            .route("/{teacher_id}/calendar.ics", web::get().to(get_teacher_calendar))
+            .route("/{teacher_id}/availability", web::get().to(get_teacher_availability))
+        );
+}
+
+pub fn student_routes(cfg: &mut web::ServiceConfig) {
+    cfg
+        .service(web::scope("/students")
+            .route("/", web::post().to(post_new_student))
+            .route("/", web::get().to(get_all_students))
+            .route("/{student_id}", web::get().to(get_student_details))
+            .route("/{student_id}", web::delete().to(delete_student))
+            .route("/{student_id}", web::put().to(update_student_details))
+            .route("/{student_id}/enrollments", web::get().to(get_enrollments_for_student))
+        );
+}
+
+pub fn meta_routes(cfg: &mut web::ServiceConfig) {
+    cfg
+        .service(web::scope("/meta")
+            .route("/levels", web::get().to(get_levels))
+            .route("/formats", web::get().to(get_formats))
+            .route("/languages", web::get().to(get_languages))
+            .route("/currencies", web::get().to(get_currencies))
+            .route("/frequencies", web::get().to(get_frequencies))
+            .route("/statuses", web::get().to(get_statuses))
+        );
+}
+
+pub fn trash_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/trash", web::get().to(get_trash));
+}
+
+//public course links that survive transfers and renames
+pub fn slug_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/c/{slug}", web::get().to(get_course_by_slug));
+}
+
+pub fn auth_routes(cfg: &mut web::ServiceConfig) {
+    cfg
+        .service(web::scope("/auth")
+            .route("/login", web::post().to(login))
+            .route("/users", web::post().to(post_new_user))
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::hash_password;
+    use crate::models::user::{CreateUser, Role};
+    use crate::test_support::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, read_body, read_body_json, TestRequest};
+    use serde_json::{json, Value};
+
+    #[actix_rt::test]
+    async fn health_check_counts_visits() {
+        let state = memory_state();
+        let app = test_app!(state);
+
+        let req = TestRequest::get().uri("/health").to_request();
+        let body: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(body, json!("I'm OK. 0 times"));
+
+        let req = TestRequest::get().uri("/health").to_request();
+        let body: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(body, json!("I'm OK. 1 times"));
+    }
+
+    #[actix_rt::test]
+    async fn openapi_document_is_served() {
+        let state = memory_state();
+        let app = test_app!(state);
+
+        let req = TestRequest::get().uri("/openapi.json").to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let doc: Value = read_body_json(resp).await;
+        assert!(doc["paths"]["/courses/{teacher_id}/{course_id}"]["put"].is_object());
+        assert!(doc["components"]["schemas"]["MyErrorResponse"].is_object());
+        assert!(doc["components"]["schemas"]["CreateCourse"].is_object());
+    }
+
+    #[actix_rt::test]
+    async fn errors_are_problem_documents() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let app = test_app!(state);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/999", t.id))
+            .insert_header(("X-Request-Id", "trace-42"))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "trace-42");
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("course_not_found"));
+        assert_eq!(problem["status"], json!(404));
+        assert_eq!(problem["request_id"], json!("trace-42"));
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}?sort=rating", t.id))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(resp.headers().contains_key("x-request-id"));
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("validation_failed"));
+        assert_eq!(problem["errors"][0]["field"], json!("sort"));
+        assert!(problem["request_id"].is_string());
+
+        let req = TestRequest::get().uri("/teacher/abc").to_request();
+        let problem: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(problem["code"], json!("invalid_input"));
+    }
+
+    #[actix_rt::test]
+    async fn broken_json_is_400_and_broken_fields_are_422() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri("/courses/")
+            .insert_header(bearer(&teacher(t.id)))
+            .insert_header(("content-type", "application/json"))
+            .set_payload("{\"teacher_id\": ")
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("invalid_input"));
+
+        let req = TestRequest::post()
+            .uri("/courses/")
+            .insert_header(bearer(&teacher(t.id)))
+            .set_json(json!({"teacher_id": t.id, "name": "", "price": {"amount": -1, "currency": "CNY"}}))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("validation_failed"));
+        let fields: Vec<&Value> = problem["errors"].as_array().unwrap().iter().map(|e| &e["field"]).collect();
+        assert_eq!(fields, vec![&json!("name"), &json!("price")]);
+    }
+
+    #[actix_rt::test]
+    async fn duplicate_enrollment_is_a_conflict() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let s = seed_student(&state, "li.lei@example.com").await;
+        let app = test_app!(state);
+
+        for expected in [StatusCode::OK, StatusCode::CONFLICT] {
+            let req = TestRequest::post()
+                .uri(&format!("/courses/{}/{}/enrollments", t.id, c.id))
+                .insert_header(bearer(&student(s.id)))
+                .set_json(json!({"student_id": s.id}))
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), expected);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn meta_lists_the_vocabularies() {
+        let state = memory_state();
+        let app = test_app!(state);
+
+        let req = TestRequest::get().uri("/meta/levels").to_request();
+        let levels: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(levels[1], json!({"value": "intermediate", "label": "Intermediate"}));
+
+        let req = TestRequest::get().uri("/meta/languages").to_request();
+        let languages: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(languages[0]["value"], json!("zh"));
+    }
+
+    #[actix_rt::test]
+    async fn prices_convert_to_the_display_currency() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}?display_currency=USD", t.id, c.id))
+            .to_request();
+        let course: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(course["price"], json!({"amount": 10000, "currency": "CNY"}));
+        assert_eq!(course["display_price"], json!({"amount": 1500, "currency": "USD"}));
+
+        //no rate for JPY, the stored price is still there
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}?currency=CNY&display_currency=JPY", t.id))
+            .to_request();
+        let page: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(page["total"], json!(1));
+        assert!(page["items"][0].get("display_price").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn course_outline_in_order() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let app = test_app!(state);
+        let modules_uri = format!("/courses/{}/{}/modules", t.id, c.id);
+
+        let mut module_ids = vec![];
+        for title in ["Basics", "Ownership"] {
+            let req = TestRequest::post()
+                .uri(&modules_uri)
+                .insert_header(bearer(&teacher(t.id)))
+                .set_json(json!({"title": title}))
+                .to_request();
+            let module: Value = read_body_json(call_service(&app, req).await).await;
+            module_ids.push(module["id"].as_i64().unwrap());
+        }
+
+        let req = TestRequest::post()
+            .uri(&format!("{}/{}/lessons", modules_uri, module_ids[1]))
+            .insert_header(bearer(&teacher(t.id)))
+            .set_json(json!({
+                "title": "Borrowing",
+                "body": "# Borrowing",
+                "duration_minutes": 45,
+                "attachments": [{"name": "slides.pdf", "url": "https://onederive.com/slides.pdf"}]
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::put()
+            .uri(&format!("{}/order", modules_uri))
+            .insert_header(bearer(&teacher(t.id)))
+            .set_json(json!({"ids": [module_ids[1], module_ids[0]]}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}?include=outline", t.id, c.id))
+            .to_request();
+        let course: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(course["name"], json!("Rust"));
+        assert_eq!(course["outline"][0]["title"], json!("Ownership"));
+        assert_eq!(course["outline"][0]["lessons"][0]["attachments"][0]["name"], json!("slides.pdf"));
+        assert_eq!(course["outline"][1]["lessons"], json!([]));
+
+        let req = TestRequest::get().uri(&format!("/courses/{}/{}", t.id, c.id)).to_request();
+        let course: Value = read_body_json(call_service(&app, req).await).await;
+        assert!(course.get("outline").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn course_calendar_is_an_ics_feed() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri(&format!("/courses/{}/{}/sessions", t.id, c.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .set_json(json!({
+                "starts_at": "2022-03-01T11:00:00Z",
+                "ends_at": "2022-03-01T12:00:00Z",
+                "timezone": "Europe/Berlin",
+                "frequency": "weekly",
+                "repeat_until": "2022-06-01T00:00:00Z"
+            }))
+            .to_request();
+        let session: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(session["frequency"], json!("weekly"));
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}/calendar.ics", t.id, c.id))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/calendar; charset=utf-8");
+
+        let body = read_body(resp).await;
+        let ics = std::str::from_utf8(&body).unwrap();
+        assert!(ics.contains("DTSTART;TZID=Europe/Berlin:20220301T120000\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;UNTIL=20220601T000000Z\r\n"));
+    }
+
+    #[actix_rt::test]
+    async fn room_clash_is_a_conflict_and_shows_in_availability() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let other = seed_teacher(&state).await;
+        let app = test_app!(state);
+
+        let mut statuses = vec![];
+        for (teacher_id, name, starts_at, ends_at) in [
+            (t.id, "Rust", "2022-03-01T09:00:00Z", "2022-03-01T11:00:00Z"),
+            (other.id, "Go", "2022-03-01T10:00:00Z", "2022-03-01T12:00:00Z"),
+        ] {
+            let req = TestRequest::post()
+                .uri("/courses/")
+                .insert_header(bearer(&teacher(teacher_id)))
+                .set_json(json!({
+                    "teacher_id": teacher_id,
+                    "name": name,
+                    "starts_at": starts_at,
+                    "ends_at": ends_at,
+                    "room": "A101"
+                }))
+                .to_request();
+            let resp = call_service(&app, req).await;
+            statuses.push(resp.status());
+            if resp.status() == StatusCode::CONFLICT {
+                let problem: Value = read_body_json(resp).await;
+                assert_eq!(problem["code"], json!("schedule_conflict"));
+                assert_eq!(problem["conflicts"][0]["name"], json!("Rust"));
+                assert_eq!(problem["conflicts"][0]["reason"], json!("room"));
+            }
+        }
+        assert_eq!(statuses, vec![StatusCode::OK, StatusCode::CONFLICT]);
+
+        let req = TestRequest::get()
+            .uri(&format!(
+                "/teacher/{}/availability?from=2022-03-01T08:00:00Z&to=2022-03-01T18:00:00Z",
+                t.id
+            ))
+            .to_request();
+        let availability: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(availability["busy"][0]["starts_at"], json!("2022-03-01T09:00:00Z"));
+        assert_eq!(availability["free"][1], json!({"starts_at": "2022-03-01T11:00:00Z", "ends_at": "2022-03-01T18:00:00Z"}));
+    }
+
+    #[actix_rt::test]
+    async fn released_seat_promotes_the_waitlist() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let first = seed_student(&state, "a@example.com").await;
+        let second = seed_student(&state, "b@example.com").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::put()
+            .uri(&format!("/courses/{}/{}", t.id, c.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .insert_header(("If-Match", "*"))
+            .set_json(json!({"name": "Rust", "capacity": 1}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let mut reservations = vec![];
+        for s in [&first, &second] {
+            let req = TestRequest::post()
+                .uri(&format!("/courses/{}/{}/seats", t.id, c.id))
+                .insert_header(bearer(&student(s.id)))
+                .set_json(json!({"student_id": s.id}))
+                .to_request();
+            let reservation: Value = read_body_json(call_service(&app, req).await).await;
+            reservations.push(reservation);
+        }
+        assert_eq!(reservations[0]["status"], json!("reserved"));
+        assert_eq!(reservations[1]["status"], json!("waitlisted"));
+        assert_eq!(reservations[1]["waitlist_position"], json!(1));
+
+        let req = TestRequest::post()
+            .uri(&format!("/courses/{}/{}/seats", t.id, c.id))
+            .insert_header(bearer(&student(first.id)))
+            .set_json(json!({"student_id": first.id}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+        let req = TestRequest::delete()
+            .uri(&format!("/courses/{}/{}/seats/{}", t.id, c.id, first.id))
+            .insert_header(bearer(&student(first.id)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}/seats/{}", t.id, c.id, second.id))
+            .insert_header(bearer(&student(second.id)))
+            .to_request();
+        let promoted: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(promoted["status"], json!("reserved"));
+        assert!(promoted.get("waitlist_position").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn only_published_courses_are_listed_publicly() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        seed_course(&state, t.id, "Rust").await;
+        let draft = seed_draft_course(&state, t.id, "Go").await;
+        let scheduled = seed_draft_course(&state, t.id, "Zig").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri(&format!("/courses/{}/{}/publish", t.id, scheduled.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .set_json(json!({"publish_at": "2099-01-01T00:00:00Z"}))
+            .to_request();
+        let course: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(course["status"], json!("scheduled"));
+
+        let req = TestRequest::get().uri(&format!("/courses/{}", t.id)).to_request();
+        let public: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(public["total"], json!(1));
+        assert_eq!(public["items"][0]["name"], json!("Rust"));
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}?status=draft", t.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        let own: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(own["total"], json!(1));
+        assert_eq!(own["items"][0]["id"], json!(draft.id));
+
+        let req = TestRequest::get().uri(&format!("/courses/{}/{}", t.id, scheduled.id)).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn wiped_description_is_diffed_and_restored() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let app = test_app!(state);
+        let course_uri = format!("/courses/{}/{}", t.id, c.id);
+
+        let req = TestRequest::put()
+            .uri(&course_uri)
+            .insert_header(bearer(&teacher(t.id)))
+            .insert_header(("If-Match", "*"))
+            //PUT replaces the whole course, everything but the description is sent as it was
+            .set_json(json!({
+                "name": "Rust",
+                "description": "",
+                "price": {"amount": 10000, "currency": "CNY"},
+                "language": "zh",
+                "level": "intermediate",
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri(&format!("{}/revisions/diff?from=1&to=2", course_uri))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        let diff: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(diff["changes"], json!([{"field": "description", "from": "All about Rust", "to": ""}]));
+
+        let req = TestRequest::post()
+            .uri(&format!("{}/revisions/1/restore", course_uri))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        let course: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(course["description"], json!("All about Rust"));
+
+        let req = TestRequest::get()
+            .uri(&format!("{}/revisions", course_uri))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        let revisions: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(revisions.as_array().map(Vec::len), Some(3));
+
+        let req = TestRequest::get()
+            .uri(&format!("{}/revisions/9", course_uri))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn mutations_require_a_token() {
+        let state = memory_state();
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri("/teacher/")
+            .set_json(json!({"name": "Haydn Kong", "picture_url": "https://onederive.com/Haydn.Kong", "profile": "rich"}))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn teacher_and_course_lifecycle() {
+        let state = memory_state();
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri("/teacher/")
+            .insert_header(bearer(&admin()))
+            .set_json(json!({"name": "Haydn Kong", "picture_url": "https://onederive.com/Haydn.Kong", "profile": "rich"}))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let created: Value = read_body_json(resp).await;
+        let teacher_id = created["id"].as_i64().unwrap() as i32;
+
+        let req = TestRequest::get().uri("/teacher/").to_request();
+        let page: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(page["total"], json!(1));
+
+        let req = TestRequest::post()
+            .uri("/courses/")
+            .insert_header(bearer(&teacher(teacher_id)))
+            .set_json(json!({"teacher_id": teacher_id, "name": "Rust web", "language": "English"}))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let course: Value = read_body_json(resp).await;
+        let course_uri = format!("/courses/{}/{}", teacher_id, course["id"]);
+
+        //another teacher may not touch the course
+        let req = TestRequest::put()
+            .uri(&course_uri)
+            .insert_header(bearer(&teacher(teacher_id + 1)))
+            .insert_header(("If-Match", "*"))
+            .set_json(json!({"name": "Hijacked"}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+        let req = TestRequest::put()
+            .uri(&course_uri)
+            .insert_header(bearer(&teacher(teacher_id)))
+            .insert_header(("If-Match", "\"1\""))
+            .set_json(json!({"name": "Rust web services", "language": "English"}))
+            .to_request();
+        let updated: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(updated["name"], json!("Rust web services"));
+        assert_eq!(updated["language"], json!("en"));
+        assert_eq!(updated["status"], json!("draft"));
+
+        let req = TestRequest::post()
+            .uri(&format!("{}/publish", course_uri))
+            .insert_header(bearer(&teacher(teacher_id)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get().uri("/courses/search?q=services").to_request();
+        let found: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(found["total"], json!(1));
+
+        let req = TestRequest::delete()
+            .uri(&course_uri)
+            .insert_header(bearer(&teacher(teacher_id)))
+            .insert_header(("If-Match", "*"))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        //deleted courses answer 410 until restored from the trash
+        let req = TestRequest::get().uri(&course_uri).to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::GONE);
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("course_deleted"));
+
+        let req = TestRequest::get().uri("/trash").insert_header(bearer(&admin())).to_request();
+        let trash: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(trash[0]["kind"], json!("course"));
+        assert_eq!(trash[0]["id"], course["id"]);
+
+        let req = TestRequest::post()
+            .uri(&format!("{}/restore", course_uri))
+            .insert_header(bearer(&teacher(teacher_id)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::FORBIDDEN);
+
+        let req = TestRequest::post()
+            .uri(&format!("{}/restore", course_uri))
+            .insert_header(bearer(&admin()))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get().uri(&course_uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn old_paths_of_a_transferred_course_redirect() {
+        let state = memory_state();
+        let from = seed_teacher(&state).await;
+        let to = seed_teacher(&state).await;
+        let last = seed_teacher(&state).await;
+        let c = seed_course(&state, from.id, "Rust").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri(&format!("/courses/{}/{}/transfer", from.id, c.id))
+            .insert_header(bearer(&teacher(from.id)))
+            .set_json(json!({"teacher_id": to.id}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        //deleting the new teacher hands the course on once more
+        let req = TestRequest::delete()
+            .uri(&format!("/teacher/{}?courses=reassign&reassign_to={}", to.id, last.id))
+            .insert_header(bearer(&admin()))
+            .insert_header(("If-Match", "*"))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}/modules?page=1", from.id, c.id))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            resp.headers().get("Location").unwrap().to_str().unwrap(),
+            format!("/courses/{}/{}/modules?page=1", last.id, c.id)
         );
-}
\ No newline at end of file
+
+        let req = TestRequest::get().uri(&format!("/courses/{}/{}", last.id, c.id)).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+        //a course that never belonged to the teacher is still not found
+        let req = TestRequest::get().uri(&format!("/courses/{}/{}", last.id, c.id + 100)).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn courses_keep_their_links_through_renames() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let first = seed_course(&state, t.id, "Intro to Rust").await;
+        let second = seed_course(&state, t.id, "Intro to Rust").await;
+        let draft = seed_draft_course(&state, t.id, "Async Rust").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::get().uri("/c/intro-to-rust-2").to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let course: Value = read_body_json(resp).await;
+        assert_eq!((course["id"].clone(), course["slug"].clone()), (json!(second.id), json!("intro-to-rust-2")));
+
+        let req = TestRequest::patch()
+            .uri(&format!("/courses/{}/{}", t.id, first.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .insert_header(("If-Match", "*"))
+            .insert_header(("Content-Type", "application/merge-patch+json"))
+            .set_payload(r#"{"name": "Rust Basics"}"#)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get().uri("/c/intro-to-rust?display_currency=USD").to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(resp.headers().get("Location").unwrap(), "/c/rust-basics?display_currency=USD");
+
+        let req = TestRequest::get().uri(&format!("/courses/by-id/{}", first.id)).to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let course: Value = read_body_json(resp).await;
+        assert_eq!(course["slug"], json!("rust-basics"));
+
+        //drafts stay hidden from the public however they are asked for
+        let req = TestRequest::get().uri(&format!("/courses/by-id/{}", draft.id)).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+        let req = TestRequest::get().uri("/c/async-rust").to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+        let req = TestRequest::get().uri("/c/async-rust").insert_header(bearer(&teacher(t.id))).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+        let req = TestRequest::get().uri("/c/no-such-course").to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn merge_patch_clears_a_nested_member() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let app = test_app!(state);
+        let course_uri = format!("/courses/{}/{}", t.id, c.id);
+
+        let req = TestRequest::patch()
+            .uri(&course_uri)
+            .insert_header(bearer(&teacher(t.id)))
+            .insert_header(("If-Match", "*"))
+            .insert_header(("Content-Type", "application/merge-patch+json"))
+            .set_payload(r#"{"price": {"amount": 5000}, "description": null}"#)
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let patched: Value = read_body_json(resp).await;
+        assert_eq!(patched["price"], json!({"amount": 5000, "currency": "CNY"}));
+        assert_eq!(patched["description"], Value::Null);
+        assert_eq!(patched["name"], json!("Rust"));
+    }
+
+    #[actix_rt::test]
+    async fn edits_from_a_stale_copy_are_refused() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let app = test_app!(state);
+        let teacher_uri = format!("/teacher/{}", t.id);
+
+        let req = TestRequest::get().uri(&teacher_uri).to_request();
+        let resp = call_service(&app, req).await;
+        let read = resp.headers().get("ETag").unwrap().to_str().unwrap().to_string();
+        assert_eq!(read, "\"1\"");
+
+        let req = TestRequest::get().uri(&teacher_uri).insert_header(("If-None-Match", read.as_str())).to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::put()
+            .uri(&teacher_uri)
+            .insert_header(bearer(&admin()))
+            .set_json(json!({"profile": "richer"}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::PRECONDITION_REQUIRED);
+
+        let req = TestRequest::put()
+            .uri(&teacher_uri)
+            .insert_header(bearer(&admin()))
+            .insert_header(("If-Match", read.as_str()))
+            .set_json(json!({"profile": "richer"}))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("ETag").unwrap(), "\"2\"");
+
+        //a second editor still holding version 1 would overwrite the change
+        let req = TestRequest::delete()
+            .uri(&teacher_uri)
+            .insert_header(bearer(&admin()))
+            .insert_header(("If-Match", read.as_str()))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+        let problem: Value = read_body_json(resp).await;
+        assert_eq!(problem["code"], json!("precondition_failed"));
+
+        let req = TestRequest::get().uri(&teacher_uri).insert_header(("If-None-Match", read.as_str())).to_request();
+        let current: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(current["profile"], json!("richer"));
+    }
+
+    #[actix_rt::test]
+    async fn student_enrolls_in_a_course() {
+        let state = memory_state();
+        let t = seed_teacher(&state).await;
+        let c = seed_course(&state, t.id, "Rust").await;
+        let s = seed_student(&state, "li.lei@example.com").await;
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri(&format!("/courses/{}/{}/enrollments", t.id, c.id))
+            .insert_header(bearer(&student(s.id)))
+            .set_json(json!({"student_id": s.id}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = TestRequest::get()
+            .uri(&format!("/students/{}/enrollments", s.id))
+            .insert_header(bearer(&student(s.id)))
+            .to_request();
+        let enrollments: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(enrollments.as_array().unwrap().len(), 1);
+
+        let req = TestRequest::get()
+            .uri(&format!("/courses/{}/{}/enrollments", t.id, c.id))
+            .insert_header(bearer(&teacher(t.id)))
+            .to_request();
+        let enrollments: Value = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(enrollments[0]["student_id"], json!(s.id));
+    }
+
+    #[actix_rt::test]
+    async fn login_issues_a_usable_token() {
+        let state = memory_state();
+        state.db.post_new_user(CreateUser {
+            username: "root".into(),
+            password: "secret".into(),
+            role: Role::Admin,
+            teacher_id: None,
+            student_id: None,
+        }, hash_password("secret").unwrap()).await.unwrap();
+        let app = test_app!(state);
+
+        let req = TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({"username": "root", "password": "wrong"}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+        let req = TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({"username": "root", "password": "secret"}))
+            .to_request();
+        let token: Value = read_body_json(call_service(&app, req).await).await;
+        let access_token = token["access_token"].as_str().unwrap();
+
+        let req = TestRequest::get()
+            .uri("/students/")
+            .insert_header(("Authorization", format!("Bearer {}", access_token)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
@@ -1,9 +1,11 @@
-use std::sync::Mutex;
-use sqlx::mysql::MySqlPool;
+use std::sync::{Arc, Mutex};
+use crate::models::money::ExchangeRates;
+use crate::repository::Repository;
 
-#[derive(Debug)]
 pub struct AppState {
     pub health_check_response: String,
     pub visit_count: Mutex<u32>,
-    pub db: MySqlPool,
+    pub db: Arc<dyn Repository>,
+    pub jwt_secret: String,
+    pub exchange_rates: ExchangeRates,
 }
\ No newline at end of file
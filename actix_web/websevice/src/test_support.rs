@@ -0,0 +1,182 @@
+//shared setup for the handler and route tests, backed by the in-memory repository
+//unless a test asks for db_state
+use crate::auth::{issue_token, AuthUser};
+use crate::models::course::{Course, CreateCourse};
+use crate::models::lifecycle::Transition;
+use crate::models::meta::{CourseLevel, Currency, Language};
+use crate::models::money::Money;
+use crate::models::student::{CreateStudent, Student};
+use crate::models::teacher::{CreateTeacher, Teacher};
+use crate::models::user::{CreateUser, Role, User};
+use crate::repository::memory::MemoryRepository;
+use crate::repository::{connect, Repository};
+use crate::state::AppState;
+use actix_web::web;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+pub const TEST_SECRET: &str = "test-secret";
+
+//the whole route table of routers.rs on top of `$state`, ready for test::call_service
+macro_rules! test_app {
+    ($state:expr) => {
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data($state.clone())
+                .configure($crate::routers::app_config)
+                .wrap_fn($crate::request_id::assign),
+        )
+        .await
+    };
+}
+
+pub fn memory_state() -> web::Data<AppState> {
+    state_on(Arc::new(MemoryRepository::new()))
+}
+
+//the database in TEST_DATABASE_URL with every migration applied, and an admin account in it
+//that revisions can name as their author; tests using it are #[ignore]d, run them with
+//TEST_DATABASE_URL=... cargo test -- --ignored
+pub async fn db_state() -> (web::Data<AppState>, AuthUser) {
+    let db_url = std::env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must point at a database the tests may write to");
+    let db = connect(&db_url).await.unwrap();
+    db.run_migrations().await.unwrap();
+
+    let user = db
+        .post_new_user(CreateUser {
+            username: format!("admin-{}", Uuid::new_v4()),
+            password: "".into(),
+            role: Role::Admin,
+            teacher_id: None,
+            student_id: None,
+        }, "".into())
+        .await
+        .unwrap();
+    let admin = AuthUser {
+        user_id: user.id,
+        ..admin()
+    };
+    (state_on(db), admin)
+}
+
+fn state_on(db: Arc<dyn Repository>) -> web::Data<AppState> {
+    web::Data::new(AppState {
+        health_check_response: "I'm OK.".to_string(),
+        visit_count: Mutex::new(0),
+        db,
+        jwt_secret: TEST_SECRET.to_string(),
+        exchange_rates: "CNY=1,USD=0.15,EUR=0.14".parse().unwrap(),
+    })
+}
+
+pub fn admin() -> AuthUser {
+    AuthUser {
+        user_id: 1,
+        role: Role::Admin,
+        teacher_id: None,
+        student_id: None,
+    }
+}
+
+pub fn teacher(teacher_id: i32) -> AuthUser {
+    AuthUser {
+        user_id: 1,
+        role: Role::Teacher,
+        teacher_id: Some(teacher_id),
+        student_id: None,
+    }
+}
+
+pub fn student(student_id: i32) -> AuthUser {
+    AuthUser {
+        user_id: 1,
+        role: Role::Student,
+        teacher_id: None,
+        student_id: Some(student_id),
+    }
+}
+
+//Authorization header value acting as `user`
+pub fn bearer(user: &AuthUser) -> (&'static str, String) {
+    let user = User {
+        id: user.user_id,
+        username: "test".into(),
+        password_hash: "".into(),
+        role: user.role,
+        teacher_id: user.teacher_id,
+        student_id: user.student_id,
+    };
+    let token = issue_token(TEST_SECRET, &user).unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+pub async fn seed_teacher(app_state: &web::Data<AppState>) -> Teacher {
+    app_state
+        .db
+        .post_new_teacher(CreateTeacher {
+            name: "Haydn Kong".into(),
+            picture_url: "https://onederive.com/Haydn.Kong".into(),
+            profile: "rich".into(),
+        })
+        .await
+        .unwrap()
+}
+
+//a course as created, not visible to the public yet
+pub async fn seed_draft_course(app_state: &web::Data<AppState>, teacher_id: i32, name: &str) -> Course {
+    seed_draft_course_by(app_state, teacher_id, name, admin().user_id).await
+}
+
+//the same, its revision made by the app_user `changed_by`
+pub async fn seed_draft_course_by(
+    app_state: &web::Data<AppState>, teacher_id: i32, name: &str, changed_by: i32
+) -> Course {
+    app_state
+        .db
+        .post_new_course(CreateCourse {
+            teacher_id,
+            name: name.into(),
+            starts_at: None,
+            ends_at: None,
+            room: None,
+            capacity: None,
+            description: Some(format!("All about {}", name)),
+            format: None,
+            structure: None,
+            duration: None,
+            price: Some(Money::new(10000, Currency::Cny)),
+            language: Some(Language::Zh),
+            level: Some(CourseLevel::Intermediate),
+        }, changed_by)
+        .await
+        .unwrap()
+}
+
+//a published course, what most tests want to look at
+pub async fn seed_course(app_state: &web::Data<AppState>, teacher_id: i32, name: &str) -> Course {
+    seed_course_by(app_state, teacher_id, name, admin().user_id).await
+}
+
+pub async fn seed_course_by(
+    app_state: &web::Data<AppState>, teacher_id: i32, name: &str, changed_by: i32
+) -> Course {
+    let course = seed_draft_course_by(app_state, teacher_id, name, changed_by).await;
+    app_state
+        .db
+        .change_course_status(teacher_id, course.id, Transition::Publish { at: None }, changed_by)
+        .await
+        .unwrap()
+}
+
+pub async fn seed_student(app_state: &web::Data<AppState>, email: &str) -> Student {
+    app_state
+        .db
+        .post_new_student(CreateStudent {
+            name: "Li Lei".into(),
+            email: email.into(),
+            profile: None,
+        })
+        .await
+        .unwrap()
+}